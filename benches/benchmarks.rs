@@ -2,6 +2,7 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use std::io;
+use std::path::Path;
 use zedi_gen::{config::Config, generator::Generator, population::PopulationGenerator};
 
 fn generate_claims(c: &mut Criterion) {
@@ -23,7 +24,7 @@ fn generate_claims(c: &mut Criterion) {
 fn generate_population(c: &mut Criterion) {
     c.bench_function("generate_1000_people", |b| {
         b.iter(|| {
-            let mut generator = PopulationGenerator::new(Some(42));
+            let mut generator = PopulationGenerator::new(Some(42), Path::new("data"));
             for _ in 0..1000 {
                 black_box(generator.generate_person());
             }
@@ -32,7 +33,7 @@ fn generate_population(c: &mut Criterion) {
 
     c.bench_function("generate_1000_providers", |b| {
         b.iter(|| {
-            let mut generator = PopulationGenerator::new(Some(42));
+            let mut generator = PopulationGenerator::new(Some(42), Path::new("data"));
             for _ in 0..1000 {
                 black_box(generator.generate_provider());
             }
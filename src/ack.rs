@@ -0,0 +1,650 @@
+//! TA1 and 999 acknowledgments built from conformance findings.
+
+use crate::cli::{AckArgs, AckOutcome};
+use crate::clock::Clock;
+use crate::conformance::{self, Finding, Severity, SnipLevel};
+use crate::errors::{Error, Result};
+use crate::x12::control_numbers::ControlNumbers;
+use crate::x12::delimiters::Element;
+use crate::x12::envelope::{FunctionalGroup, TransactionSet, X12Interchange};
+use crate::x12::parser::{self, RawSegment};
+use crate::x12::segments::{GenericSegment, Ta1Segment};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// The 999 implementation guide (ST03).
+const IMPLEMENTATION_GUIDE: &str = "005010X231A1";
+
+/// TA105 note codes for errors in ISA01 through ISA16.
+const ISA_NOTE_CODES: [&str; 16] = [
+    "010", "011", "012", "013", "005", "006", "007", "008", "014", "015", "016", "017", "018",
+    "019", "020", "027",
+];
+
+/// Most syntax error codes in IK5 and AK9.
+const MAX_SYNTAX_CODES: usize = 5;
+
+/// Most characters of a bad element copied into IK404.
+const MAX_BAD_VALUE: usize = 99;
+
+/// TA105 note code for a forced rejection without interchange errors.
+const PLACEHOLDER_NOTE_CODE: &str = "024";
+
+/// AK905 code for a forced rejection without group errors.
+const PLACEHOLDER_GROUP_CODE: &str = "1";
+
+/// A functional group of the input.
+struct Group<'a> {
+    gs: &'a RawSegment,
+    ge: Option<&'a RawSegment>,
+    transactions: Vec<Transaction<'a>>,
+}
+
+/// A transaction set of the input.
+struct Transaction<'a> {
+    st: &'a RawSegment,
+    end: usize,
+}
+
+/// Builds the TA1 and 999 acknowledgment for the interchange in `content`.
+pub fn acknowledge(
+    content: &str,
+    data_path: &Path,
+    outcome: Option<AckOutcome>,
+    clock: Clock,
+    control_numbers: &mut ControlNumbers,
+) -> Result<X12Interchange> {
+    let delimiters = parser::detect_delimiters(content)?;
+    let segments = parser::tokenize(content, &delimiters);
+    let Some(isa) = segments.first().filter(|raw| raw.id == "ISA") else {
+        return Err(Error::Validation(
+            "cannot acknowledge an interchange without an ISA segment".to_string(),
+        ));
+    };
+    let findings = conformance::compute_conformance(content, data_path).findings;
+    let groups = structure(&segments);
+
+    let now = clock.now();
+    let time = now.format("%H%M").to_string();
+    let mut interchange = X12Interchange::new(
+        value(isa, 8).trim_end(),
+        value(isa, 6).trim_end(),
+        &control_numbers.next_interchange(),
+        &now.format("%y%m%d").to_string(),
+        &time,
+    );
+    interchange.delimiters = delimiters;
+    interchange.isa.isa13_usage_indicator = value(isa, 15).chars().next().unwrap_or('P');
+    interchange.iea.iea01_number_of_included_functional_groups = 0;
+
+    let envelope: Vec<&Finding> = findings
+        .iter()
+        .filter(|f| is_interchange_level(f))
+        .collect();
+    if !envelope.is_empty() || value(isa, 14) == "1" || groups.is_empty() {
+        let code = match outcome {
+            Some(outcome) => outcome_code(outcome),
+            None if envelope.iter().any(|f| f.severity == Severity::Error) => 'R',
+            None if !envelope.is_empty() => 'E',
+            None => 'A',
+        };
+        interchange.ta1 = Some(Ta1Segment {
+            ta101_interchange_control_number: value(isa, 13).to_string(),
+            ta102_date: value(isa, 9).to_string(),
+            ta103_time: value(isa, 10).to_string(),
+            ta104_acknowledgment_code: code,
+            ta105_note_code: match envelope.first() {
+                Some(finding) => note_code(finding),
+                None if code == 'A' => "000",
+                None => PLACEHOLDER_NOTE_CODE,
+            }
+            .to_string(),
+        });
+        if code == 'R' {
+            return Ok(interchange);
+        }
+    }
+
+    let mut partners: Vec<((&str, &str), Vec<&Group>)> = Vec::new();
+    for received in &groups {
+        let key = (value(received.gs, 2), value(received.gs, 3));
+        match partners.iter_mut().find(|(partner, _)| *partner == key) {
+            Some((_, received_groups)) => received_groups.push(received),
+            None => partners.push((key, vec![received])),
+        }
+    }
+    for ((sender, receiver), received_groups) in partners {
+        let mut group = FunctionalGroup::new(
+            receiver,
+            sender,
+            &control_numbers.next_group(),
+            &now.format("%Y%m%d").to_string(),
+            &time,
+        );
+        group.gs.gs01_functional_identifier_code = "FA".to_string();
+        group.gs.gs08_version = IMPLEMENTATION_GUIDE.to_string();
+        for received in received_groups {
+            let control_number = control_numbers.next_transaction();
+            group.add_transaction_set(acknowledge_group(
+                received,
+                &segments,
+                &findings,
+                outcome,
+                &control_number,
+            ));
+        }
+        interchange.add_functional_group(group);
+    }
+    Ok(interchange)
+}
+
+/// Groups the input segments by functional group and transaction set.
+fn structure(segments: &[RawSegment]) -> Vec<Group<'_>> {
+    let mut groups: Vec<Group> = Vec::new();
+    let mut open = false;
+    for raw in segments {
+        let id = raw.id.as_str();
+        if matches!(id, "ISA" | "GS" | "ST" | "GE" | "IEA") {
+            open = false;
+        }
+        if id == "GS" {
+            groups.push(Group {
+                gs: raw,
+                ge: None,
+                transactions: Vec::new(),
+            });
+            continue;
+        }
+        match (id, groups.last_mut().filter(|group| group.ge.is_none())) {
+            ("ST", Some(group)) => {
+                group.transactions.push(Transaction {
+                    st: raw,
+                    end: raw.position,
+                });
+                open = true;
+            }
+            ("GE", Some(group)) => group.ge = Some(raw),
+            (_, Some(group)) if open => {
+                if let Some(transaction) = group.transactions.last_mut() {
+                    transaction.end = raw.position;
+                }
+                open = id != "SE";
+            }
+            _ => {}
+        }
+    }
+    groups
+}
+
+/// Builds the 999 for one functional group.
+fn acknowledge_group(
+    group: &Group,
+    segments: &[RawSegment],
+    findings: &[Finding],
+    outcome: Option<AckOutcome>,
+    control_number: &str,
+) -> TransactionSet {
+    let mut ack = TransactionSet::new(control_number);
+    ack.st.st01_transaction_set_id = "999".to_string();
+    ack.st.st03_implementation_reference = IMPLEMENTATION_GUIDE.to_string();
+    ack.add_segment(segment(
+        "AK1",
+        vec![
+            value(group.gs, 1).into(),
+            value(group.gs, 6).into(),
+            value(group.gs, 8).into(),
+        ],
+    ));
+
+    let mut accepted = 0;
+    let mut with_errors = false;
+    for transaction in &group.transactions {
+        let status = acknowledge_transaction(&mut ack, transaction, segments, findings, outcome);
+        if status != 'R' {
+            accepted += 1;
+        }
+        with_errors |= status == 'E';
+    }
+
+    let positions = [Some(group.gs.position), group.ge.map(|ge| ge.position)];
+    let group_errors: Vec<&Finding> = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error && !is_interchange_level(f))
+        .filter(|f| f.transaction.is_none() && positions.contains(&Some(f.segment)))
+        .collect();
+    let received = group.transactions.len();
+    let status = match outcome {
+        Some(outcome) => outcome_code(outcome),
+        None if !group_errors.is_empty() || (accepted == 0 && received > 0) => 'R',
+        None if accepted < received => 'P',
+        None if with_errors => 'E',
+        None => 'A',
+    };
+    let included = group
+        .ge
+        .map(|ge| value(ge, 1))
+        .filter(|count| !count.is_empty())
+        .map_or_else(|| received.to_string(), str::to_string);
+    let mut elements: Vec<Element> = vec![
+        status.to_string().into(),
+        included.into(),
+        received.to_string().into(),
+        accepted.to_string().into(),
+    ];
+    let mut group_codes: Vec<&str> = group_errors.iter().map(|f| group_code(f)).collect();
+    if status == 'R' && group_codes.is_empty() && outcome.is_some() {
+        group_codes.push(PLACEHOLDER_GROUP_CODE);
+    }
+    elements.extend(codes(group_codes.into_iter()));
+    ack.add_segment(segment("AK9", elements));
+    ack
+}
+
+/// Adds AK2, IK3, IK4 and IK5 for one transaction set and returns its IK501 status.
+fn acknowledge_transaction(
+    ack: &mut TransactionSet,
+    transaction: &Transaction,
+    segments: &[RawSegment],
+    findings: &[Finding],
+    outcome: Option<AckOutcome>,
+) -> char {
+    let st = transaction.st;
+    ack.add_segment(segment(
+        "AK2",
+        vec![
+            value(st, 1).into(),
+            value(st, 2).into(),
+            value(st, 3).into(),
+        ],
+    ));
+
+    let range = st.position..=transaction.end;
+    let errors = findings.iter().filter(|f| {
+        f.severity == Severity::Error
+            && f.transaction.is_some()
+            && range.contains(&f.segment)
+            && !is_interchange_level(f)
+    });
+    let (envelope, body): (Vec<&Finding>, Vec<&Finding>) = errors.partition(|f| {
+        matches!(f.segment_id.as_deref(), Some("ST" | "SE"))
+            && (f.element.is_some() || f.code == Some("2"))
+    });
+
+    let mut by_segment: BTreeMap<usize, Vec<&Finding>> = BTreeMap::new();
+    for finding in &body {
+        by_segment.entry(finding.segment).or_default().push(finding);
+    }
+    for (position, findings) in &by_segment {
+        let Some(raw) = segments.get(position - 1) else {
+            continue;
+        };
+        let code = findings
+            .iter()
+            .find(|f| f.element.is_none())
+            .and_then(|f| f.code)
+            .unwrap_or("8");
+        let loop_id = findings.iter().find_map(|f| f.loop_id).unwrap_or_default();
+        ack.add_segment(segment(
+            "IK3",
+            vec![
+                raw.id.as_str().into(),
+                (position - st.position + 1).to_string().into(),
+                loop_id.into(),
+                code.into(),
+            ],
+        ));
+        for finding in findings.iter().filter(|f| f.element.is_some()) {
+            ack.add_segment(element_error(raw, finding));
+        }
+    }
+
+    let syntax = body.iter().any(|f| f.level <= SnipLevel::Requirements);
+    let implementation = body.iter().any(|f| f.level > SnipLevel::Requirements);
+    let status = match outcome {
+        Some(outcome) => outcome_code(outcome),
+        None if syntax || !envelope.is_empty() => 'R',
+        None if !body.is_empty() => 'E',
+        None => 'A',
+    };
+    let mut syntax_codes: Vec<&str> = envelope.iter().map(|f| transaction_code(f)).collect();
+    if syntax {
+        syntax_codes.push("5");
+    }
+    if implementation {
+        syntax_codes.push("I5");
+    }
+    if status != 'A' && syntax_codes.is_empty() {
+        syntax_codes.push(if status == 'R' { "5" } else { "I5" });
+    }
+    let mut elements: Vec<Element> = vec![status.to_string().into()];
+    elements.extend(codes(syntax_codes.into_iter()));
+    ack.add_segment(segment("IK5", elements));
+    status
+}
+
+/// The IK4 for an element level finding.
+fn element_error(raw: &RawSegment, finding: &Finding) -> GenericSegment {
+    let element = finding.element.unwrap_or_default();
+    let position = match finding.component {
+        Some(component) => Element::composite(vec![element.to_string(), component.to_string()]),
+        None => element.to_string().into(),
+    };
+    let code = finding.code.unwrap_or("I12");
+    let bad_value = raw
+        .elements
+        .get(element.saturating_sub(1))
+        .map(|e| match finding.component {
+            Some(component) => e.components().get(component - 1).map_or("", String::as_str),
+            None => e.value(),
+        })
+        .filter(|_| code != "1")
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_BAD_VALUE)
+        .collect::<String>();
+    segment(
+        "IK4",
+        vec![position, Element::default(), code.into(), bad_value.into()],
+    )
+}
+
+/// Whether `finding` concerns the ISA/IEA envelope and belongs in the TA1.
+fn is_interchange_level(finding: &Finding) -> bool {
+    matches!(finding.segment_id.as_deref(), Some("ISA" | "IEA"))
+        || finding
+            .code
+            .is_some_and(|code| code.len() == 3 && code.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// The TA105 note code for `finding`.
+fn note_code(finding: &Finding) -> &'static str {
+    match (finding.segment_id.as_deref(), finding.element) {
+        (Some("ISA"), Some(element)) => ISA_NOTE_CODES.get(element - 1).copied().unwrap_or("024"),
+        (Some("IEA"), Some(1)) => "021",
+        (Some("IEA"), Some(2)) => "001",
+        _ => finding
+            .code
+            .filter(|_| is_interchange_level(finding))
+            .unwrap_or("024"),
+    }
+}
+
+/// The AK905 syntax error code for `finding`.
+fn group_code(finding: &Finding) -> &'static str {
+    match (finding.segment_id.as_deref(), finding.element) {
+        (Some("GS"), Some(1)) => "1",
+        (Some("GS"), Some(8)) => "2",
+        (Some("GE"), Some(1)) => "5",
+        (Some("GE"), Some(2)) => "4",
+        (_, Some(_)) => "6",
+        _ => finding.code.unwrap_or("6"),
+    }
+}
+
+/// The IK502 syntax error code for an ST/SE `finding`.
+fn transaction_code(finding: &Finding) -> &'static str {
+    match (finding.segment_id.as_deref(), finding.element) {
+        (Some("ST"), Some(1)) => "6",
+        (Some("ST"), Some(2)) => "7",
+        (Some("ST"), Some(3)) => "I6",
+        (Some("SE"), Some(1)) => "4",
+        (Some("SE"), Some(2)) => "3",
+        (_, None) => finding.code.unwrap_or("5"),
+        _ => "5",
+    }
+}
+
+/// The first `MAX_SYNTAX_CODES` distinct codes.
+fn codes<'a>(codes: impl Iterator<Item = &'a str>) -> Vec<Element> {
+    let mut unique: Vec<&str> = Vec::new();
+    for code in codes {
+        if !unique.contains(&code) {
+            unique.push(code);
+        }
+    }
+    unique
+        .into_iter()
+        .take(MAX_SYNTAX_CODES)
+        .map(Element::from)
+        .collect()
+}
+
+/// The TA104/IK501/AK901 code of `outcome`.
+pub fn outcome_code(outcome: AckOutcome) -> char {
+    match outcome {
+        AckOutcome::Accepted => 'A',
+        AckOutcome::AcceptedWithErrors => 'E',
+        AckOutcome::Rejected => 'R',
+    }
+}
+
+/// A generic `id` segment with `elements`.
+fn segment(id: &str, elements: Vec<Element>) -> GenericSegment {
+    GenericSegment {
+        id: id.to_string(),
+        elements,
+    }
+}
+
+/// The value of the element at 1-based `position`, or "".
+fn value(raw: &RawSegment, position: usize) -> &str {
+    raw.elements.get(position - 1).map_or("", Element::value)
+}
+
+/// Runs the `ack` subcommand.
+pub fn run(args: &AckArgs) -> Result<()> {
+    let mut content = String::new();
+    conformance::open_input(&args.input_path)?.read_to_string(&mut content)?;
+    let mut control_numbers = match args.control_number_file {
+        Some(ref path) => ControlNumbers::load(path)?,
+        None => ControlNumbers::default(),
+    };
+
+    let interchange = acknowledge(
+        &content,
+        &args.data_dir,
+        args.outcome,
+        Clock::from_as_of(args.as_of),
+        &mut control_numbers,
+    )?;
+    let output = interchange.to_string();
+    match args.output {
+        Some(ref path) => fs::write(path, output)?,
+        None => io::stdout().lock().write_all(output.as_bytes())?,
+    }
+
+    if let Some(ref path) = args.control_number_file {
+        control_numbers.save(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClaimsPerRemittance, Config};
+    use crate::generator::generate_to_string;
+    use crate::payers::Payer;
+    use chrono::NaiveDate;
+
+    fn generate() -> String {
+        generate_to_string(Config {
+            claim_count: 6,
+            seed: Some(6),
+            anomaly_rate: 0.0,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Fixed(3),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            payers: vec![Payer::default()],
+            ..Default::default()
+        })
+    }
+
+    fn ack(content: &str, outcome: Option<AckOutcome>) -> Vec<String> {
+        let clock = Clock::fixed(NaiveDate::from_ymd_opt(2024, 3, 16).unwrap());
+        let interchange = acknowledge(
+            content,
+            Path::new("data"),
+            outcome,
+            clock,
+            &mut ControlNumbers::default(),
+        )
+        .unwrap();
+        interchange
+            .to_string()
+            .lines()
+            .map(|line| line.trim_end_matches('~').to_string())
+            .collect()
+    }
+
+    fn segments<'a>(lines: &'a [String], id: &str) -> Vec<&'a str> {
+        let prefix = format!("{}*", id);
+        lines
+            .iter()
+            .filter(|line| line.starts_with(&prefix))
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_interchange_is_accepted() {
+        let lines = ack(&generate(), None);
+
+        assert!(lines[0].contains("*ZZ*RECEIVER01     *ZZ*SENDER001      *240316*"));
+        assert!(segments(&lines, "TA1").is_empty());
+        assert_eq!(
+            segments(&lines, "GS"),
+            ["GS*FA*RECEIVER01*1234567890*20240316*0000*1*X*005010X231A1"]
+        );
+        assert_eq!(segments(&lines, "ST"), ["ST*999*0001*005010X231A1"]);
+        assert_eq!(segments(&lines, "AK1"), ["AK1*HP*1*005010X221A1"]);
+        assert_eq!(segments(&lines, "AK2").len(), 2);
+        assert_eq!(segments(&lines, "IK5"), ["IK5*A", "IK5*A"]);
+        assert!(segments(&lines, "IK3").is_empty());
+        assert_eq!(segments(&lines, "AK9"), ["AK9*A*2*2*2"]);
+        assert_eq!(segments(&lines, "SE"), ["SE*8*0001"]);
+    }
+
+    #[test]
+    fn test_findings_become_segment_and_element_errors() {
+        let content = generate();
+        let second = content.match_indices("BPR*C*").nth(1).unwrap().0 + "BPR*C*".len();
+        let end = second + content[second..].find('*').unwrap();
+        let content = format!("{}1.00{}", &content[..second], &content[end..]);
+        let trn = content.find("~\nTRN").unwrap();
+        let content = format!("{}20241399{}", &content[..trn - 8], &content[trn..]);
+
+        let lines = ack(&content, None);
+        assert_eq!(segments(&lines, "IK3"), ["IK3*BPR*2**8", "IK3*BPR*2**8"]);
+        assert_eq!(
+            segments(&lines, "IK4"),
+            ["IK4*16**8*20241399", "IK4*2**I12*1.00"]
+        );
+        assert_eq!(segments(&lines, "IK5"), ["IK5*R*5", "IK5*E*I5"]);
+        assert_eq!(segments(&lines, "AK9"), ["AK9*P*2*2*1"]);
+    }
+
+    #[test]
+    fn test_segment_errors_name_their_loop() {
+        let mut content = generate().replacen("\nN1*PE*", "\nN1*PE**", 1);
+        content = content.replacen("\nSVC*HC:", "\nSVC*ZZ:", 1);
+        let clp = content.find("\nCLP*").unwrap() + 1;
+        let charge = clp + content[clp..].match_indices('*').nth(2).unwrap().0 + 1;
+        content.insert(charge, '9');
+
+        let lines = ack(&content, None);
+        let ik3 = segments(&lines, "IK3");
+        assert_eq!(ik3.len(), 3, "{:?}", ik3);
+        assert!(ik3[0].starts_with("IK3*N1*") && ik3[0].ends_with("*1000B*8"));
+        assert!(ik3[1].starts_with("IK3*CLP*") && ik3[1].ends_with("*2100*8"));
+        assert!(ik3[2].starts_with("IK3*SVC*") && ik3[2].ends_with("*2110*8"));
+    }
+
+    #[test]
+    fn test_envelope_errors_produce_ta1() {
+        let content = generate().replace("IEA*1*000000001", "IEA*1*000000009");
+        let lines = ack(&content, None);
+
+        assert_eq!(segments(&lines, "TA1"), ["TA1*000000001*240315*0000*R*001"]);
+        assert!(segments(&lines, "GS").is_empty());
+        assert_eq!(segments(&lines, "IEA"), ["IEA*0*000000001"]);
+
+        let content = generate().replacen("*0*P*", "*1*P*", 1);
+        let lines = ack(&content, None);
+        assert_eq!(segments(&lines, "TA1"), ["TA1*000000001*240315*0000*A*000"]);
+        assert_eq!(segments(&lines, "AK9"), ["AK9*A*2*2*2"]);
+    }
+
+    #[test]
+    fn test_one_fa_group_per_trading_partner() {
+        let content = generate();
+        let start = content.find("GS*").unwrap();
+        let end = content.find("IEA*").unwrap();
+        let group = &content[start..end];
+        let renumber = |sender: &str, number: &str| {
+            group
+                .replacen("GS*HP*1234567890*", &format!("GS*HP*{}*", sender), 1)
+                .replacen("*0000*1*X*", &format!("*0000*{}*X*", number), 1)
+                .replacen("GE*2*1~", &format!("GE*2*{}~", number), 1)
+        };
+        let content = format!(
+            "{}{}{}{}",
+            &content[..end],
+            renumber("60101", "2"),
+            renumber("1234567890", "3"),
+            &content[end..].replacen("IEA*1*", "IEA*3*", 1)
+        );
+
+        let lines = ack(&content, None);
+        assert_eq!(
+            segments(&lines, "GS"),
+            [
+                "GS*FA*RECEIVER01*1234567890*20240316*0000*1*X*005010X231A1",
+                "GS*FA*RECEIVER01*60101*20240316*0000*2*X*005010X231A1",
+            ]
+        );
+        assert_eq!(
+            segments(&lines, "AK1"),
+            [
+                "AK1*HP*1*005010X221A1",
+                "AK1*HP*3*005010X221A1",
+                "AK1*HP*2*005010X221A1",
+            ]
+        );
+        assert_eq!(
+            segments(&lines, "ST"),
+            [
+                "ST*999*0001*005010X231A1",
+                "ST*999*0002*005010X231A1",
+                "ST*999*0001*005010X231A1",
+            ]
+        );
+        assert_eq!(segments(&lines, "GE"), ["GE*2*1", "GE*1*2"]);
+        assert_eq!(segments(&lines, "IEA"), ["IEA*2*000000001"]);
+    }
+
+    #[test]
+    fn test_forced_outcomes() {
+        let content = generate();
+
+        let lines = ack(&content, Some(AckOutcome::AcceptedWithErrors));
+        assert_eq!(segments(&lines, "IK5"), ["IK5*E*I5", "IK5*E*I5"]);
+        assert_eq!(segments(&lines, "AK9"), ["AK9*E*2*2*2"]);
+
+        let lines = ack(&content, Some(AckOutcome::Rejected));
+        assert_eq!(segments(&lines, "IK5"), ["IK5*R*5", "IK5*R*5"]);
+        assert_eq!(segments(&lines, "AK9"), ["AK9*R*2*2*0*1"]);
+
+        let requested = content.replacen("*0*P*", "*1*P*", 1);
+        let lines = ack(&requested, Some(AckOutcome::Rejected));
+        assert_eq!(segments(&lines, "TA1"), ["TA1*000000001*240315*0000*R*024"]);
+        assert!(segments(&lines, "GS").is_empty());
+
+        let content = content.replace("IEA*1*000000001", "IEA*1*000000009");
+        let lines = ack(&content, Some(AckOutcome::Accepted));
+        assert_eq!(segments(&lines, "TA1"), ["TA1*000000001*240315*0000*A*001"]);
+        assert_eq!(segments(&lines, "IK5"), ["IK5*A", "IK5*A"]);
+    }
+}
@@ -0,0 +1,238 @@
+//! Claim and provider level adjustments and the CARC/RARC code tables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use csv;
+
+/// Claim adjustment group code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AdjustmentGroup {
+    /// `CO` contractual obligations.
+    Contractual,
+
+    /// `PR` patient responsibility.
+    PatientResponsibility,
+
+    /// `OA` other adjustments.
+    Other,
+
+    /// `PI` payer initiated reductions.
+    PayerInitiated,
+
+    /// `CR` corrections and reversals.
+    Correction,
+}
+
+impl AdjustmentGroup {
+    /// The X12 group code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AdjustmentGroup::Contractual => "CO",
+            AdjustmentGroup::PatientResponsibility => "PR",
+            AdjustmentGroup::Other => "OA",
+            AdjustmentGroup::PayerInitiated => "PI",
+            AdjustmentGroup::Correction => "CR",
+        }
+    }
+
+    /// Looks up a group by its X12 code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "CO" => Some(AdjustmentGroup::Contractual),
+            "PR" => Some(AdjustmentGroup::PatientResponsibility),
+            "OA" => Some(AdjustmentGroup::Other),
+            "PI" => Some(AdjustmentGroup::PayerInitiated),
+            "CR" => Some(AdjustmentGroup::Correction),
+            _ => None,
+        }
+    }
+}
+
+/// A claim or service line adjustment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Adjustment {
+    /// Adjustment group.
+    pub group: AdjustmentGroup,
+
+    /// Claim adjustment reason code.
+    pub reason_code: String,
+
+    /// Amount in cents.
+    pub amount: u64,
+
+    /// Adjusted quantity, e.g. units of service.
+    pub quantity: Option<f64>,
+}
+
+impl Adjustment {
+    /// An adjustment without a quantity.
+    pub fn new(group: AdjustmentGroup, reason_code: &str, amount: u64) -> Self {
+        Self {
+            group,
+            reason_code: reason_code.to_string(),
+            amount,
+            quantity: None,
+        }
+    }
+}
+
+/// Provider level adjustment reason code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderAdjustmentReason {
+    /// `L6` interest owed.
+    Interest,
+
+    /// `FB` forwarding balance.
+    ForwardingBalance,
+
+    /// `WO` overpayment recovery.
+    OverpaymentRecovery,
+
+    /// `LE` levy.
+    Levy,
+
+    /// `AH` origination fee.
+    OriginationFee,
+
+    /// `72` authorized return.
+    AuthorizedReturn,
+}
+
+impl ProviderAdjustmentReason {
+    /// The X12 reason code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProviderAdjustmentReason::Interest => "L6",
+            ProviderAdjustmentReason::ForwardingBalance => "FB",
+            ProviderAdjustmentReason::OverpaymentRecovery => "WO",
+            ProviderAdjustmentReason::Levy => "LE",
+            ProviderAdjustmentReason::OriginationFee => "AH",
+            ProviderAdjustmentReason::AuthorizedReturn => "72",
+        }
+    }
+
+    /// Looks up a reason by its X12 code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "L6" => Some(ProviderAdjustmentReason::Interest),
+            "FB" => Some(ProviderAdjustmentReason::ForwardingBalance),
+            "WO" => Some(ProviderAdjustmentReason::OverpaymentRecovery),
+            "LE" => Some(ProviderAdjustmentReason::Levy),
+            "AH" => Some(ProviderAdjustmentReason::OriginationFee),
+            "72" => Some(ProviderAdjustmentReason::AuthorizedReturn),
+            _ => None,
+        }
+    }
+}
+
+/// A provider level adjustment (PLB).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderAdjustment {
+    /// Adjustment reason.
+    pub reason: ProviderAdjustmentReason,
+
+    /// Reference identification, e.g. the claim an overpayment is recovered for.
+    pub reference_id: Option<String>,
+
+    /// Amount in cents; positive amounts reduce the payment.
+    pub amount: i64,
+}
+
+/// The shipped CARC list, used when the data directory has none.
+const CARC_CODES_CSV: &str = include_str!("../data/carc_codes.csv");
+
+/// The shipped RARC list, used when the data directory has none.
+const REMARK_CODES_CSV: &str = include_str!("../data/remark_codes.csv");
+
+/// A row of a code list.
+#[derive(Debug, Deserialize)]
+struct CsvCode {
+    code: String,
+    description: String,
+}
+
+/// Reads a `code,description` list.
+fn parse_codes<R: Read>(reader: R) -> HashMap<String, String> {
+    csv::Reader::from_reader(reader)
+        .into_deserialize::<CsvCode>()
+        .flatten()
+        .map(|rec| (rec.code, rec.description))
+        .collect()
+}
+
+/// Reads the code list at `path`, falling back to `embedded` when it is missing or empty.
+fn load_codes(path: &Path, embedded: &str) -> HashMap<String, String> {
+    let codes = File::open(path).map(parse_codes).unwrap_or_default();
+    if codes.is_empty() {
+        parse_codes(embedded.as_bytes())
+    } else {
+        codes
+    }
+}
+
+/// Claim adjustment reason codes.
+#[derive(Debug, Clone)]
+pub struct CarcTable {
+    codes: HashMap<String, String>,
+}
+
+impl CarcTable {
+    /// Loads `carc_codes.csv` from `data_path`, falling back to the embedded list.
+    pub fn load(data_path: &Path) -> Self {
+        Self {
+            codes: load_codes(&data_path.join("carc_codes.csv"), CARC_CODES_CSV),
+        }
+    }
+
+    /// Whether `code` is a known reason code.
+    pub fn contains(&self, code: &str) -> bool {
+        self.codes.contains_key(code)
+    }
+}
+
+/// Remittance advice remark codes.
+#[derive(Debug, Clone)]
+pub struct RarcTable {
+    codes: HashMap<String, String>,
+}
+
+impl RarcTable {
+    /// Loads `remark_codes.csv` from `data_path`, falling back to the embedded list.
+    pub fn load(data_path: &Path) -> Self {
+        Self {
+            codes: load_codes(&data_path.join("remark_codes.csv"), REMARK_CODES_CSV),
+        }
+    }
+
+    /// Whether `code` is a known remark code.
+    pub fn contains(&self, code: &str) -> bool {
+        self.codes.contains_key(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_carc_table_fallback() {
+        let table = CarcTable::load(Path::new("does-not-exist"));
+
+        assert!(table.contains("45"));
+        assert!(table.contains("1"));
+        for reason in crate::denials::ALL_REASONS {
+            assert!(table.contains(reason.carc()));
+        }
+        assert!(!table.contains("ZZZ"));
+
+        let remarks = RarcTable::load(Path::new("does-not-exist"));
+        for reason in crate::denials::ALL_REASONS {
+            assert!(remarks.contains(reason.rarc()));
+        }
+        assert!(!remarks.contains("ZZZ"));
+    }
+}
@@ -1,60 +1,61 @@
-
+//! Injection of deliberate data anomalies into generated claims.
 
 use crate::claims::Claim;
-use rand::{Rng, SeedableRng};
+use crate::seeding::{stream_rng, Stream};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-
+use std::collections::{BTreeMap, HashSet};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Kind of anomaly injected into a claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum AnomalyType {
-    
+    /// A required field is left empty.
     MissingField,
 
-    
+    /// A field holds a value outside its allowed range.
     InvalidValue,
 
-    
+    /// A date is written in the wrong format.
     InvalidDateFormat,
 
-    
+    /// The claim reuses an earlier claim ID.
     DuplicateClaim,
 
-    
+    /// The procedure code does not exist.
     InvalidProcedureCode,
 
-    
+    /// Procedure modifiers that cannot be used together.
     InvalidModifierCombination,
 
-    
+    /// A procedure that does not fit the patient's age or gender.
     AgeGenderMismatch,
 
-    
+    /// The provider identifiers are invalid.
     InvalidProvider,
 
-    
+    /// The patient demographics are invalid.
     InvalidPatientInfo,
 
-    
+    /// Required supporting documentation is missing.
     MissingDocumentation,
 }
 
-
+/// How often anomalies are injected.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnomalyConfig {
-    
+    /// Share of claims (0.0 to 1.0) that receive an anomaly.
     pub base_rate: f64,
 
-    
-    pub type_rates: std::collections::HashMap<AnomalyType, f64>,
+    /// Relative weight of each anomaly type.
+    pub type_rates: BTreeMap<AnomalyType, f64>,
 
-    
+    /// Whether injected anomalies are logged.
     pub log_anomalies: bool,
 }
 
 impl Default for AnomalyConfig {
     fn default() -> Self {
-        let mut type_rates = std::collections::HashMap::new();
+        let mut type_rates = BTreeMap::new();
 
         
         type_rates.insert(AnomalyType::MissingField, 0.3);
@@ -76,36 +77,36 @@ impl Default for AnomalyConfig {
     }
 }
 
-
+/// A claim after anomaly injection.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnomalyInjectionResult {
-    
+    /// The possibly modified claim.
     pub claim: Claim,
 
-    
+    /// The anomalies injected into the claim.
     pub anomalies: Vec<Anomaly>,
 }
 
-
+/// A single injected anomaly.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Anomaly {
-    
+    /// Kind of anomaly.
     pub anomaly_type: AnomalyType,
 
-    
+    /// What was changed.
     pub description: String,
 
-    
+    /// Name of the modified field.
     pub field: Option<String>,
 
-    
+    /// Value before the change.
     pub original_value: Option<String>,
 
-    
+    /// Value after the change.
     pub new_value: Option<String>,
 }
 
-
+/// Injects anomalies into claims according to an [`AnomalyConfig`].
 pub struct AnomalyInjector {
     rng: rand_chacha::ChaCha8Rng,
     config: AnomalyConfig,
@@ -113,12 +114,9 @@ pub struct AnomalyInjector {
 }
 
 impl AnomalyInjector {
-    
+    /// Creates an injector; the same seed yields the same anomalies.
     pub fn new(config: AnomalyConfig, seed: Option<u64>) -> Self {
-        let rng = match seed {
-            Some(seed) => rand_chacha::ChaCha8Rng::seed_from_u64(seed),
-            None => rand_chacha::ChaCha8Rng::from_entropy(),
-        };
+        let rng = stream_rng(seed, Stream::Anomalies);
 
         Self {
             rng,
@@ -127,7 +125,7 @@ impl AnomalyInjector {
         }
     }
 
-    
+    /// Possibly injects an anomaly into `claim`.
     pub fn inject_anomalies(&mut self, mut claim: Claim) -> AnomalyInjectionResult {
         let mut anomalies = Vec::new();
 
@@ -157,7 +155,7 @@ impl AnomalyInjector {
         result
     }
 
-    
+    /// Injects one anomaly of `anomaly_type` into `claim`.
     fn inject_anomaly(&mut self, anomaly_type: AnomalyType, claim: &mut Claim) -> Option<Anomaly> {
         match anomaly_type {
             AnomalyType::MissingField => self.inject_missing_field(claim),
@@ -175,8 +173,6 @@ impl AnomalyInjector {
         }
     }
 
-    
-
     fn inject_missing_field(&self, claim: &mut Claim) -> Option<Anomaly> {
         let original = claim.claim_id.clone();
         claim.claim_id.clear();
@@ -317,6 +313,7 @@ impl AnomalyInjector {
 mod tests {
     use super::*;
     use crate::population::PopulationGenerator;
+    use std::path::Path;
 
     #[test]
     fn test_anomaly_injection() {
@@ -331,10 +328,10 @@ mod tests {
         let mut injector = AnomalyInjector::new(config, Some(42));
 
         
-        let mut pop_gen = PopulationGenerator::new(Some(42));
+        let mut pop_gen = PopulationGenerator::new(Some(42), Path::new("data"));
         let patient = pop_gen.generate_person();
         let provider = pop_gen.generate_provider();
-        let mut claim_gen = super::super::claims::ClaimGenerator::new(Some(42));
+        let mut claim_gen = super::super::claims::ClaimGenerator::new(Some(42), Path::new("data"));
         let claim = claim_gen.generate_claim(patient, provider, None);
 
         
@@ -0,0 +1,306 @@
+//! Balancing of service line and claim totals against their adjustments.
+
+use crate::adjustments::{Adjustment, AdjustmentGroup};
+use crate::claims::{Claim, ServiceLine};
+use crate::money::Money;
+use std::fmt;
+
+/// Reason code of the contractual adjustment that absorbs line imbalances.
+const BALANCING_REASON: &str = "45";
+
+/// A balancing rule of the 835.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// Charge less payment equals the service line adjustments.
+    ServiceLine,
+
+    /// Charge less payment equals the claim and service line adjustments.
+    Claim,
+
+    /// Patient responsibility equals the PR adjustments.
+    PatientResponsibility,
+}
+
+impl Rule {
+    /// The rule in terms of the 835 elements.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::ServiceLine => "SVC02 - SVC03 = service line CAS",
+            Rule::Claim => "CLP03 - CLP04 = claim CAS + service line CAS",
+            Rule::PatientResponsibility => "CLP05 = PR CAS",
+        }
+    }
+}
+
+/// A broken balancing rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Imbalance {
+    /// The rule that does not hold.
+    pub rule: Rule,
+
+    /// Claim ID or line item control number.
+    pub reference: String,
+
+    /// The amount the rule requires.
+    pub expected: Money,
+
+    /// The amount found.
+    pub actual: Money,
+}
+
+impl fmt::Display for Imbalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} does not balance ({}): expected {}, found {}",
+            self.reference,
+            self.rule.description(),
+            self.expected,
+            self.actual
+        )
+    }
+}
+
+/// Claim totals summed from service lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClaimTotals {
+    /// Total charge.
+    pub charge: Money,
+
+    /// Total payment.
+    pub payment: Money,
+
+    /// Total adjustments.
+    pub adjustment: Money,
+
+    /// Total patient responsibility.
+    pub patient_responsibility: Money,
+}
+
+impl ClaimTotals {
+    /// Sums `lines`.
+    pub fn of(lines: &[ServiceLine]) -> Self {
+        let mut totals = Self::default();
+        for line in lines {
+            totals.charge += Money::from(line.charge_amount);
+            totals.payment += Money::from(line.paid_amount);
+            totals.adjustment += adjustment_total(&line.adjustments);
+            totals.patient_responsibility +=
+                group_total(&line.adjustments, AdjustmentGroup::PatientResponsibility);
+        }
+        totals
+    }
+}
+
+/// Balances a service line by resizing its CO-45 adjustment, capping payment when the
+/// other adjustments already exceed charge less payment.
+pub fn balance_line(line: &mut ServiceLine) {
+    let charge = Money::from(line.charge_amount);
+    let paid = Money::from(line.paid_amount);
+    let others: Money = line
+        .adjustments
+        .iter()
+        .filter(|a| !is_balancing(a))
+        .map(|a| Money::from(a.amount))
+        .sum();
+    let plug = charge - paid - others;
+
+    let position = line.adjustments.iter().position(is_balancing);
+    if plug > Money::ZERO {
+        match position {
+            Some(index) => line.adjustments[index].amount = plug.unsigned_cents(),
+            None => line.adjustments.insert(
+                0,
+                Adjustment::new(
+                    AdjustmentGroup::Contractual,
+                    BALANCING_REASON,
+                    plug.unsigned_cents(),
+                ),
+            ),
+        }
+    } else {
+        if let Some(index) = position {
+            line.adjustments.remove(index);
+        }
+        if plug < Money::ZERO {
+            line.paid_amount = (charge - others).unsigned_cents();
+        }
+    }
+
+    line.payment_amount = line.paid_amount;
+    line.adjustment_amount = adjustment_total(&line.adjustments).unsigned_cents();
+    line.allowed_amount =
+        (charge - group_total(&line.adjustments, AdjustmentGroup::Contractual)).unsigned_cents();
+}
+
+/// Balances every service line and recomputes the claim totals from them.
+pub fn balance_claim(claim: &mut Claim) {
+    for line in &mut claim.service_lines {
+        balance_line(line);
+    }
+    let totals = ClaimTotals::of(&claim.service_lines);
+    claim.total_charge = totals.charge.unsigned_cents();
+    claim.total_payment = totals.payment.unsigned_cents();
+    claim.total_adjustment = totals.adjustment.unsigned_cents();
+    claim.patient_responsibility = totals.patient_responsibility.unsigned_cents();
+}
+
+/// Checks every balancing rule of `claim`.
+pub fn check_claim(claim: &Claim) -> Vec<Imbalance> {
+    let mut imbalances = Vec::new();
+    for line in &claim.service_lines {
+        let charge = Money::from(line.charge_amount);
+        let paid = Money::from(line.paid_amount);
+        let adjustments = adjustment_total(&line.adjustments);
+        if charge - paid != adjustments {
+            imbalances.push(Imbalance {
+                rule: Rule::ServiceLine,
+                reference: line.line_item_control_number.clone(),
+                expected: charge - paid,
+                actual: adjustments,
+            });
+        }
+    }
+
+    let totals = ClaimTotals::of(&claim.service_lines);
+    let charge = Money::from(claim.total_charge);
+    let paid = Money::from(claim.total_payment);
+    if charge - paid != totals.adjustment {
+        imbalances.push(Imbalance {
+            rule: Rule::Claim,
+            reference: claim.claim_id.clone(),
+            expected: charge - paid,
+            actual: totals.adjustment,
+        });
+    }
+    let patient_responsibility = Money::from(claim.patient_responsibility);
+    if patient_responsibility != totals.patient_responsibility {
+        imbalances.push(Imbalance {
+            rule: Rule::PatientResponsibility,
+            reference: claim.claim_id.clone(),
+            expected: totals.patient_responsibility,
+            actual: patient_responsibility,
+        });
+    }
+    imbalances
+}
+
+/// Whether `adjustment` is the balancing CO-45.
+fn is_balancing(adjustment: &Adjustment) -> bool {
+    adjustment.group == AdjustmentGroup::Contractual && adjustment.reason_code == BALANCING_REASON
+}
+
+/// Sum of `adjustments`.
+fn adjustment_total(adjustments: &[Adjustment]) -> Money {
+    adjustments.iter().map(|a| Money::from(a.amount)).sum()
+}
+
+/// Sum of the `group` adjustments.
+fn group_total(adjustments: &[Adjustment], group: AdjustmentGroup) -> Money {
+    adjustments
+        .iter()
+        .filter(|a| a.group == group)
+        .map(|a| Money::from(a.amount))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomalies::{AnomalyConfig, AnomalyInjector, AnomalyType};
+    use crate::claims::ClaimGenerator;
+    use crate::config::{ClaimsPerRemittance, Config};
+    use crate::conformance::{self, SnipLevel};
+    use crate::generator::generate_to_string;
+    use crate::population::PopulationGenerator;
+    use chrono::NaiveDate;
+    use std::path::Path;
+
+    fn claims(seed: u64, count: usize) -> Vec<Claim> {
+        let mut claim_gen = ClaimGenerator::new(Some(seed), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(seed), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        (0..count)
+            .map(|_| claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None))
+            .collect()
+    }
+
+    #[test]
+    fn test_generated_claims_balance() {
+        for claim in claims(11, 200) {
+            assert_eq!(check_claim(&claim), Vec::new());
+            assert!(claim.total_payment <= claim.total_charge);
+        }
+    }
+
+    #[test]
+    fn test_balance_line_plugs_contractual_adjustment() {
+        let mut claim = claims(12, 1).remove(0);
+        let line = &mut claim.service_lines[0];
+        line.charge_amount = 10000;
+        line.paid_amount = 6000;
+        line.adjustments = vec![Adjustment::new(
+            AdjustmentGroup::PatientResponsibility,
+            "2",
+            1500,
+        )];
+
+        balance_line(line);
+        assert_eq!(line.adjustments[0].reason_code, BALANCING_REASON);
+        assert_eq!(line.adjustments[0].amount, 2500);
+        assert_eq!(line.adjustment_amount, 4000);
+        assert_eq!(line.allowed_amount, 7500);
+
+        line.paid_amount = 9000;
+        balance_line(line);
+        assert_eq!(line.adjustments.len(), 1);
+        assert_eq!(line.paid_amount, 8500);
+        assert_eq!(line.adjustment_amount, 1500);
+
+        balance_claim(&mut claim);
+        assert_eq!(check_claim(&claim), Vec::new());
+    }
+
+    #[test]
+    fn test_imbalance_only_when_injected() {
+        let config = AnomalyConfig {
+            base_rate: 1.0,
+            type_rates: [(AnomalyType::InvalidValue, 1.0)].into_iter().collect(),
+            log_anomalies: false,
+        };
+        let mut injector = AnomalyInjector::new(config, Some(13));
+        let claim = claims(13, 1).remove(0);
+
+        let result = injector.inject_anomalies(claim);
+        let imbalances = check_claim(&result.claim);
+        assert_eq!(result.anomalies.len(), 1);
+        assert_eq!(imbalances.len(), 1);
+        assert_eq!(imbalances[0].rule, Rule::Claim);
+        assert!(imbalances[0].to_string().contains("does not balance"));
+    }
+
+    #[test]
+    fn test_x12_output_balances() {
+        for seed in [1, 2, 3] {
+            let content = generate_to_string(Config {
+                claim_count: 300,
+                seed: Some(seed),
+                anomaly_rate: 0.0,
+                plb_rate: 0.5,
+                batch_remittances: true,
+                claims_per_remittance: ClaimsPerRemittance::Uniform { min: 1, max: 8 },
+                as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+                ..Default::default()
+            });
+
+            let findings = conformance::validate(&content, Path::new("data"));
+            assert!(findings
+                .iter()
+                .all(|f| !matches!(f.level, SnipLevel::Balancing | SnipLevel::Situational)));
+            assert!(content
+                .lines()
+                .filter(|line| line.starts_with("CLP*"))
+                .all(|clp| clp.split('*').nth(5).is_some_and(|clp05| !clp05.is_empty())));
+        }
+    }
+}
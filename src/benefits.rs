@@ -0,0 +1,384 @@
+//! Benefit plans and the deductible/out-of-pocket accumulators that drive patient cost share.
+
+use crate::adjustments::{Adjustment, AdjustmentGroup};
+use crate::claims::{Claim, ServiceLine};
+use crate::generator::is_lab_procedure;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Service category used to pick a plan's copay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ServiceCategory {
+    /// Office visits and anything not covered by another category.
+    Office,
+
+    /// Emergency room services (place of service 23).
+    Emergency,
+
+    /// Inpatient hospital services (place of service 21).
+    Inpatient,
+
+    /// Outpatient hospital services (place of service 22).
+    Outpatient,
+
+    /// Laboratory procedures.
+    Laboratory,
+}
+
+impl ServiceCategory {
+    /// The category of a service line.
+    pub fn of(line: &ServiceLine) -> Self {
+        match line.place_of_service.as_str() {
+            "21" => ServiceCategory::Inpatient,
+            "23" => ServiceCategory::Emergency,
+            _ if is_lab_procedure(&line.procedure_code) => ServiceCategory::Laboratory,
+            "22" => ServiceCategory::Outpatient,
+            _ => ServiceCategory::Office,
+        }
+    }
+}
+
+/// A member's benefit plan. Amounts are in cents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenefitPlan {
+    /// Plan name.
+    pub name: String,
+
+    /// Annual deductible.
+    pub deductible: u64,
+
+    /// Coinsurance percentage owed by the patient after the deductible.
+    pub coinsurance_percent: u64,
+
+    /// Copay per service category; categories with a copay skip deductible and coinsurance.
+    pub copays: BTreeMap<ServiceCategory, u64>,
+
+    /// Annual out-of-pocket maximum.
+    pub out_of_pocket_max: u64,
+}
+
+/// Year-to-date patient cost share of one member.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    /// Deductible met so far.
+    pub deductible: u64,
+
+    /// Out-of-pocket spending so far.
+    pub out_of_pocket: u64,
+}
+
+impl BenefitPlan {
+    /// Loads `benefit_plans.csv`, falling back to a single standard PPO.
+    pub fn load_all(data_path: &Path) -> Vec<Self> {
+        #[derive(Debug, Deserialize)]
+        struct CsvPlan {
+            name: String,
+            deductible: u64,
+            coinsurance_percent: u64,
+            office_copay: Option<u64>,
+            emergency_copay: Option<u64>,
+            inpatient_copay: Option<u64>,
+            outpatient_copay: Option<u64>,
+            laboratory_copay: Option<u64>,
+            out_of_pocket_max: u64,
+        }
+
+        let mut plans = Vec::new();
+        if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("benefit_plans.csv")) {
+            for rec in rdr.deserialize::<CsvPlan>().flatten() {
+                let copays = [
+                    (ServiceCategory::Office, rec.office_copay),
+                    (ServiceCategory::Emergency, rec.emergency_copay),
+                    (ServiceCategory::Inpatient, rec.inpatient_copay),
+                    (ServiceCategory::Outpatient, rec.outpatient_copay),
+                    (ServiceCategory::Laboratory, rec.laboratory_copay),
+                ]
+                .into_iter()
+                .filter_map(|(category, copay)| copay.map(|copay| (category, copay)))
+                .collect();
+                plans.push(BenefitPlan {
+                    name: rec.name,
+                    deductible: rec.deductible,
+                    coinsurance_percent: rec.coinsurance_percent.min(100),
+                    copays,
+                    out_of_pocket_max: rec.out_of_pocket_max,
+                });
+            }
+        }
+        if plans.is_empty() {
+            plans.push(BenefitPlan {
+                name: "STANDARD PPO".to_string(),
+                deductible: 150000,
+                coinsurance_percent: 20,
+                copays: [
+                    (ServiceCategory::Office, 2500),
+                    (ServiceCategory::Emergency, 25000),
+                ]
+                .into_iter()
+                .collect(),
+                out_of_pocket_max: 600000,
+            });
+        }
+
+        plans
+    }
+
+    /// Computes the patient responsibility adjustments for one service line and updates
+    /// the accumulator.
+    pub fn cost_share(
+        &self,
+        accumulator: &mut Accumulator,
+        category: ServiceCategory,
+        allowed: u64,
+        copay_paid: &mut u64,
+    ) -> Vec<Adjustment> {
+        let shares = match self.copays.get(&category) {
+            Some(&copay) => vec![("3", copay.saturating_sub(*copay_paid).min(allowed))],
+            None => {
+                let deductible = self
+                    .deductible
+                    .saturating_sub(accumulator.deductible)
+                    .min(allowed);
+                let coinsurance = (allowed - deductible) * self.coinsurance_percent / 100;
+                vec![("1", deductible), ("2", coinsurance)]
+            }
+        };
+
+        let mut adjustments = Vec::new();
+        for (reason, amount) in shares {
+            let amount = amount.min(
+                self.out_of_pocket_max
+                    .saturating_sub(accumulator.out_of_pocket),
+            );
+            if amount == 0 {
+                continue;
+            }
+            accumulator.out_of_pocket += amount;
+            match reason {
+                "1" => accumulator.deductible += amount,
+                "3" => *copay_paid += amount,
+                _ => {}
+            }
+            adjustments.push(Adjustment::new(
+                AdjustmentGroup::PatientResponsibility,
+                reason,
+                amount,
+            ));
+        }
+        adjustments
+    }
+}
+
+/// Accumulators per member and plan year.
+#[derive(Debug, Default)]
+pub struct Accumulators {
+    totals: HashMap<(String, String), Accumulator>,
+}
+
+impl Accumulators {
+    /// The accumulator of a member for a plan year.
+    pub fn get(&self, member_id: &str, plan_year: &str) -> Accumulator {
+        self.totals
+            .get(&(member_id.to_string(), plan_year.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Applies the patient's benefit plan to a primary claim and re-settles it.
+    pub fn apply(&mut self, claim: &mut Claim) {
+        let Some(plan) = claim.patient.benefit_plan.clone() else {
+            return;
+        };
+        if !claim.status_code.is_primary() {
+            return;
+        }
+        let mut copays_paid: HashMap<ServiceCategory, u64> = HashMap::new();
+        for line in &mut claim.service_lines {
+            let category = ServiceCategory::of(line);
+            let plan_year = line.service_date[..line.service_date.len().min(4)].to_string();
+            let accumulator = self
+                .totals
+                .entry((claim.patient.member_id.clone(), plan_year))
+                .or_default();
+            let cost_share = plan.cost_share(
+                accumulator,
+                category,
+                line.allowed_amount,
+                copays_paid.entry(category).or_default(),
+            );
+
+            line.adjustments
+                .retain(|a| a.group != AdjustmentGroup::PatientResponsibility);
+            line.paid_amount =
+                line.allowed_amount - cost_share.iter().map(|a| a.amount).sum::<u64>();
+            line.adjustments.extend(cost_share);
+        }
+        claim.settle();
+    }
+}
+
+/// Applies benefit plans to claims in service date order.
+pub fn apply_benefits<'a>(claims: impl IntoIterator<Item = &'a mut Claim>) -> Accumulators {
+    let mut claims: Vec<&mut Claim> = claims.into_iter().collect();
+    claims.sort_by(|a, b| first_service_date(a).cmp(&first_service_date(b)));
+
+    let mut accumulators = Accumulators::default();
+    for claim in claims {
+        accumulators.apply(claim);
+    }
+    accumulators
+}
+
+/// The earliest service date of `claim`.
+fn first_service_date(claim: &Claim) -> Option<&str> {
+    claim
+        .service_lines
+        .iter()
+        .map(|line| line.service_date.as_str())
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::balancing::check_claim;
+    use crate::claims::ClaimGenerator;
+    use crate::population::PopulationGenerator;
+
+    fn plan(
+        deductible: u64,
+        copays: &[(ServiceCategory, u64)],
+        out_of_pocket_max: u64,
+    ) -> BenefitPlan {
+        BenefitPlan {
+            name: "TEST".to_string(),
+            deductible,
+            coinsurance_percent: 20,
+            copays: copays.iter().copied().collect(),
+            out_of_pocket_max,
+        }
+    }
+
+    fn office_claims(plan: &BenefitPlan, dates: &[&str], allowed: u64) -> Vec<Claim> {
+        let mut claim_gen = ClaimGenerator::new(Some(24), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(24), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let mut patient = pop_gen.generate_person();
+        patient.benefit_plan = Some(plan.clone());
+        dates
+            .iter()
+            .map(|date| {
+                let mut claim = claim_gen.generate_claim(patient.clone(), provider.clone(), None);
+                claim.service_lines.truncate(1);
+                let line = &mut claim.service_lines[0];
+                line.procedure_code = "99213".to_string();
+                line.place_of_service = "11".to_string();
+                line.service_date = date.to_string();
+                line.service_end_date = None;
+                line.charge_amount = allowed;
+                line.allowed_amount = allowed;
+                line.paid_amount = allowed;
+                line.adjustments.clear();
+                claim
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_deductible_accumulates_in_service_date_order() {
+        let plan = plan(50000, &[], 100000);
+        let mut claims = office_claims(&plan, &["2024-03-10", "2024-01-10", "2024-02-10"], 30000);
+        let member_id = claims[0].patient.member_id.clone();
+
+        let accumulators = apply_benefits(&mut claims);
+        let patient_share = |claim: &Claim| {
+            claim.service_lines[0]
+                .adjustments
+                .iter()
+                .map(|a| (a.reason_code.clone(), a.amount))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(patient_share(&claims[1]), vec![("1".to_string(), 30000)]);
+        assert_eq!(
+            patient_share(&claims[2]),
+            vec![("1".to_string(), 20000), ("2".to_string(), 2000)]
+        );
+        assert_eq!(patient_share(&claims[0]), vec![("2".to_string(), 6000)]);
+        assert_eq!(claims[1].total_payment, 0);
+        assert_eq!(claims[2].patient_responsibility, 22000);
+        assert_eq!(claims[0].total_payment, 24000);
+        assert_eq!(
+            accumulators.get(&member_id, "2024"),
+            Accumulator {
+                deductible: 50000,
+                out_of_pocket: 58000,
+            }
+        );
+        for claim in &claims {
+            assert_eq!(check_claim(claim), Vec::new());
+        }
+    }
+
+    #[test]
+    fn test_copays_and_out_of_pocket_max() {
+        let plan = plan(
+            0,
+            &[
+                (ServiceCategory::Office, 2500),
+                (ServiceCategory::Emergency, 20000),
+            ],
+            30000,
+        );
+        let mut accumulator = Accumulator::default();
+        let mut share = |category, allowed, copay_paid: &mut u64| {
+            plan.cost_share(&mut accumulator, category, allowed, copay_paid)
+                .into_iter()
+                .map(|a| (a.reason_code, a.amount))
+                .collect::<Vec<_>>()
+        };
+
+        let mut office_visit = 0;
+        assert_eq!(
+            share(ServiceCategory::Office, 1000, &mut office_visit),
+            vec![("3".to_string(), 1000)]
+        );
+        assert_eq!(
+            share(ServiceCategory::Office, 5000, &mut office_visit),
+            vec![("3".to_string(), 1500)]
+        );
+        assert!(share(ServiceCategory::Office, 5000, &mut office_visit).is_empty());
+        assert_eq!(
+            share(ServiceCategory::Emergency, 50000, &mut 0),
+            vec![("3".to_string(), 20000)]
+        );
+        assert_eq!(
+            share(ServiceCategory::Inpatient, 100000, &mut 0),
+            vec![("2".to_string(), 7500)]
+        );
+        assert!(share(ServiceCategory::Office, 10000, &mut 0).is_empty());
+        assert_eq!(accumulator.out_of_pocket, 30000);
+    }
+
+    #[test]
+    fn test_service_category() {
+        let mut claim_gen = ClaimGenerator::new(Some(25), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(25), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let claim = claim_gen.generate_claim(pop_gen.generate_person(), provider, None);
+        let mut line = claim.service_lines[0].clone();
+
+        for (code, pos, category) in [
+            ("99213", "11", ServiceCategory::Office),
+            ("99284", "23", ServiceCategory::Emergency),
+            ("99223", "21", ServiceCategory::Inpatient),
+            ("85025", "22", ServiceCategory::Laboratory),
+            ("93000", "22", ServiceCategory::Outpatient),
+        ] {
+            line.procedure_code = code.to_string();
+            line.place_of_service = pos.to_string();
+            assert_eq!(ServiceCategory::of(&line), category);
+        }
+    }
+}
@@ -1,114 +1,445 @@
+//! The claim model and its adjudication.
 
-
+use crate::adjustments::{
+    Adjustment, AdjustmentGroup, CarcTable, ProviderAdjustment, ProviderAdjustmentReason,
+};
+use crate::balancing::{self, ClaimTotals};
+use crate::clock::Clock;
+use crate::denials::DenialReason;
+use crate::fee_schedule::FeeSchedule;
+use crate::money::Money;
+use crate::payers::Payer;
 use crate::population::{Person, Provider};
-use chrono::Utc;
+use crate::seeding::{stream_rng, Stream};
 use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
 use std::path::Path;
 
 use csv;
 
+/// Latest a service date may fall before the as-of date, in days.
+const SERVICE_DATE_WINDOW_DAYS: i64 = 90;
+
+/// Earliest a service date may fall before the as-of date, in days.
+const MIN_SERVICE_LAG_DAYS: i64 = 14;
+
+/// Copay amounts in cents.
+const COPAY_AMOUNTS: &[u64] = &[1000, 1500, 2000, 2500, 3000, 4000, 5000];
+
+/// Percent of charge allowed for codes missing from the fee schedule.
+const UNLISTED_ALLOWED_PERCENT: u64 = 60;
+
+/// Contractual reasons that may take a share of the CO adjustment besides CO-45.
+const SECONDARY_CONTRACTUAL_REASONS: &[&str] = &["253", "59", "131"];
+
+/// Chance that a claim is a crossover to a secondary payer.
+const SECONDARY_CLAIM_RATE: f64 = 0.06;
+
+/// Chance that a crossover claim is tertiary.
+const TERTIARY_CLAIM_RATE: f64 = 0.01;
+
+/// Reason code of the OA adjustment for the prior payer's payment.
+const PRIOR_PAYER_REASON: &str = "23";
+
+/// Reasons drawn for provider level adjustments.
+const PROVIDER_ADJUSTMENT_REASONS: &[ProviderAdjustmentReason] = &[
+    ProviderAdjustmentReason::Interest,
+    ProviderAdjustmentReason::ForwardingBalance,
+    ProviderAdjustmentReason::OverpaymentRecovery,
+    ProviderAdjustmentReason::Levy,
+    ProviderAdjustmentReason::OriginationFee,
+    ProviderAdjustmentReason::AuthorizedReturn,
+];
+
+/// Crossover carrier names and payer IDs.
+const CROSSOVER_CARRIERS: &[(&str, &str)] = &[
+    ("MEDIGAP SUPPLEMENT PLAN", "60054"),
+    ("STATE MEDICAID", "77027"),
+    ("SECONDARY COMMERCIAL PLAN", "87726"),
+];
 
+/// An adjudicated claim. Amounts are in cents.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claim {
-    
+    /// Patient control number (CLP01).
     pub claim_id: String,
 
-    
+    /// The patient.
     pub patient: Person,
 
-    
+    /// The billing provider, which is also the payee.
     pub billing_provider: Provider,
 
-    
+    /// The rendering provider, when different from the billing provider.
     pub rendering_provider: Option<Provider>,
 
-    
+    /// Service lines.
     pub service_lines: Vec<ServiceLine>,
 
-    
+    /// Total charge.
     pub total_charge: u64,
 
-    
+    /// Total payment.
     pub total_payment: u64,
 
-    
+    /// Total of all adjustments.
     pub total_adjustment: u64,
 
-    
+    /// Amount the patient owes.
     pub patient_responsibility: u64,
 
-    
+    /// Summary payment status.
     pub status: ClaimStatus,
+
+    /// The insured, when the patient is a dependent.
+    pub subscriber: Option<Person>,
+
+    /// Member ID corrected by the payer.
+    pub corrected_member_id: Option<String>,
+
+    /// Payer the claim was forwarded to.
+    pub crossover_carrier: Option<CrossoverCarrier>,
+
+    /// Medical record number.
+    pub medical_record_number: Option<String>,
+
+    /// Prior authorization number.
+    pub prior_authorization: Option<String>,
+
+    /// Original reference number of a corrected or replacement claim.
+    pub original_reference: Option<String>,
+
+    /// Date the payer received the claim (YYYY-MM-DD).
+    #[serde(default)]
+    pub received_date: String,
+
+    /// Date the patient's coverage expires (YYYY-MM-DD).
+    pub coverage_expiration: Option<String>,
+
+    /// Prompt pay interest included in the payment.
+    #[serde(default)]
+    pub interest_amount: u64,
+
+    /// Prompt pay discount taken from the payment.
+    #[serde(default)]
+    pub prompt_pay_discount: u64,
+
+    /// Amount the patient already paid the provider.
+    #[serde(default)]
+    pub patient_amount_paid: u64,
+
+    /// Covered days of an inpatient stay.
+    #[serde(default)]
+    pub covered_days: u32,
+
+    /// Institutional type of bill; professional claims have none.
+    pub bill_type: Option<String>,
+
+    /// Claim frequency type code (CLP09).
+    #[serde(default = "default_claim_frequency_code")]
+    pub claim_frequency_code: String,
+
+    /// Claim status code (CLP02).
+    #[serde(default)]
+    pub status_code: ClaimStatusCode,
+
+    /// Why the claim was denied.
+    #[serde(default)]
+    pub denial_reason: Option<DenialReason>,
 }
 
+impl Claim {
+    /// The facility type code (CLP08): the bill type's first two digits or the place of service.
+    pub fn facility_type_code(&self) -> &str {
+        match self.bill_type {
+            Some(ref bill_type) => &bill_type[..bill_type.len().min(2)],
+            None => self
+                .service_lines
+                .first()
+                .map_or("11", |line| line.place_of_service.as_str()),
+        }
+    }
+
+    /// Whether the claim is an institutional (837I) claim.
+    pub fn is_institutional(&self) -> bool {
+        self.bill_type.is_some()
+    }
 
+    /// Rebalances the claim after its lines or adjustments changed and updates its totals
+    /// and status.
+    pub fn settle(&mut self) {
+        balancing::balance_claim(self);
+        if self.prompt_pay_discount > 0 {
+            self.prompt_pay_discount = self.total_payment / 50;
+        }
+        self.patient_amount_paid = self.patient_amount_paid.min(self.patient_responsibility);
+        self.status = ClaimStatus::of(self.status_code, self.total_charge, self.total_payment);
+    }
+
+    /// `cents` as reported in the 835, negated for reversals.
+    pub fn signed(&self, cents: u64) -> Money {
+        if self.status_code == ClaimStatusCode::Reversal {
+            -Money::from(cents)
+        } else {
+            Money::from(cents)
+        }
+    }
+
+    /// The reversal of this claim.
+    pub fn reversal(&self) -> Claim {
+        Claim {
+            status_code: ClaimStatusCode::Reversal,
+            crossover_carrier: None,
+            interest_amount: 0,
+            prompt_pay_discount: 0,
+            ..self.clone()
+        }
+    }
+}
+
+/// The CLM05-3 frequency code of an original claim.
+fn default_claim_frequency_code() -> String {
+    "1".to_string()
+}
+
+/// Payer a claim was forwarded to for crossover.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossoverCarrier {
+    /// Payer name.
+    pub name: String,
+
+    /// Payer identifier.
+    pub payer_id: String,
+
+    /// Claim number assigned by the crossover payer.
+    pub claim_number: String,
+}
+
+/// An adjudicated service line. Amounts are in cents.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceLine {
-    
+    /// Line number within the claim.
     pub line_number: u32,
 
-    
+    /// Procedure code as adjudicated.
     pub procedure_code: String,
 
-    
+    /// Procedure description.
     pub procedure_description: String,
 
-    
+    /// Date of service (YYYY-MM-DD).
     pub service_date: String,
 
-    
+    /// Line charge.
     pub charge_amount: u64,
 
-    
+    /// Line payment; kept equal to `paid_amount`.
     pub payment_amount: u64,
 
-    
+    /// Amount paid for the line (SVC03).
     pub paid_amount: u64,
 
-    
+    /// Total of the line's adjustments.
     pub adjustment_amount: u64,
 
-    
+    /// Line adjustments.
+    #[serde(default)]
+    pub adjustments: Vec<Adjustment>,
+
+    /// Units of service.
     pub units: f64,
 
-    
+    /// Place of service code.
     pub place_of_service: String,
 
-    
+    /// NUBC revenue code of institutional lines.
     pub revenue_code: Option<String>,
 
-    
+    /// Procedure modifiers.
+    #[serde(default)]
     pub modifiers: Vec<String>,
-}
 
+    /// Last date of service, for services spanning several days.
+    pub service_end_date: Option<String>,
+
+    /// Line item control number (REF*6R).
+    #[serde(default)]
+    pub line_item_control_number: String,
+
+    /// Allowed amount: the charge less contractual adjustments.
+    #[serde(default)]
+    pub allowed_amount: u64,
+
+    /// Remittance advice remark codes.
+    #[serde(default)]
+    pub remark_codes: Vec<String>,
 
+    /// Procedure code as submitted, when the payer changed it.
+    pub submitted_procedure_code: Option<String>,
+}
+
+/// Summary payment status of a claim.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClaimStatus {
-    
+    /// Paid in full.
     Paid,
 
-    
+    /// Denied.
     Denied,
 
-    
+    /// Paid less than charged.
     Partial,
 
-    
+    /// Forwarded to another payer.
     Pending,
 }
 
+impl ClaimStatus {
+    /// `Partial` when the payment is below the charge, otherwise `Paid`.
+    pub fn from_totals(charge: u64, payment: u64) -> Self {
+        if payment < charge {
+            ClaimStatus::Partial
+        } else {
+            ClaimStatus::Paid
+        }
+    }
+
+    /// The status of a claim with `status_code`.
+    pub fn of(status_code: ClaimStatusCode, charge: u64, payment: u64) -> Self {
+        if status_code.is_denial() {
+            ClaimStatus::Denied
+        } else if status_code.is_forwarded() {
+            ClaimStatus::Pending
+        } else {
+            ClaimStatus::from_totals(charge, payment)
+        }
+    }
+}
+
+/// Claim status code reported in CLP02.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClaimStatusCode {
+    /// `1` processed as primary.
+    #[default]
+    ProcessedAsPrimary,
+
+    /// `2` processed as secondary.
+    ProcessedAsSecondary,
+
+    /// `3` processed as tertiary.
+    ProcessedAsTertiary,
+
+    /// `4` denied.
+    Denied,
+
+    /// `19` processed as primary, forwarded to an additional payer.
+    PrimaryForwarded,
+
+    /// `20` processed as secondary, forwarded to an additional payer.
+    SecondaryForwarded,
+
+    /// `21` processed as tertiary, forwarded to an additional payer.
+    TertiaryForwarded,
+
+    /// `22` reversal of a previous payment.
+    Reversal,
+
+    /// `23` not our claim, forwarded to an additional payer.
+    NotOurClaim,
+}
+
+impl ClaimStatusCode {
+    /// The status code of a claim processed as payer `sequence` (1 to 3).
+    pub fn processed(sequence: u8, forwarded: bool) -> Self {
+        match (sequence, forwarded) {
+            (2, false) => ClaimStatusCode::ProcessedAsSecondary,
+            (2, true) => ClaimStatusCode::SecondaryForwarded,
+            (3, false) => ClaimStatusCode::ProcessedAsTertiary,
+            (3, true) => ClaimStatusCode::TertiaryForwarded,
+            (_, false) => ClaimStatusCode::ProcessedAsPrimary,
+            (_, true) => ClaimStatusCode::PrimaryForwarded,
+        }
+    }
+
+    /// The CLP02 code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ClaimStatusCode::ProcessedAsPrimary => "1",
+            ClaimStatusCode::ProcessedAsSecondary => "2",
+            ClaimStatusCode::ProcessedAsTertiary => "3",
+            ClaimStatusCode::Denied => "4",
+            ClaimStatusCode::PrimaryForwarded => "19",
+            ClaimStatusCode::SecondaryForwarded => "20",
+            ClaimStatusCode::TertiaryForwarded => "21",
+            ClaimStatusCode::Reversal => "22",
+            ClaimStatusCode::NotOurClaim => "23",
+        }
+    }
+
+    /// Looks up a status by its CLP02 code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "1" => Some(ClaimStatusCode::ProcessedAsPrimary),
+            "2" => Some(ClaimStatusCode::ProcessedAsSecondary),
+            "3" => Some(ClaimStatusCode::ProcessedAsTertiary),
+            "4" => Some(ClaimStatusCode::Denied),
+            "19" => Some(ClaimStatusCode::PrimaryForwarded),
+            "20" => Some(ClaimStatusCode::SecondaryForwarded),
+            "21" => Some(ClaimStatusCode::TertiaryForwarded),
+            "22" => Some(ClaimStatusCode::Reversal),
+            "23" => Some(ClaimStatusCode::NotOurClaim),
+            _ => None,
+        }
+    }
+
+    /// The payer's position in the coordination of benefits (1 to 3).
+    pub fn payer_sequence(&self) -> u8 {
+        match self {
+            ClaimStatusCode::ProcessedAsSecondary | ClaimStatusCode::SecondaryForwarded => 2,
+            ClaimStatusCode::ProcessedAsTertiary | ClaimStatusCode::TertiaryForwarded => 3,
+            _ => 1,
+        }
+    }
+
+    /// Whether the payer processed the claim as primary.
+    pub fn is_primary(&self) -> bool {
+        matches!(
+            self,
+            ClaimStatusCode::ProcessedAsPrimary | ClaimStatusCode::PrimaryForwarded
+        )
+    }
+
+    /// Whether the claim was denied.
+    pub fn is_denial(&self) -> bool {
+        matches!(self, ClaimStatusCode::Denied | ClaimStatusCode::NotOurClaim)
+    }
+
+    /// Whether the claim was forwarded to another payer.
+    pub fn is_forwarded(&self) -> bool {
+        matches!(
+            self,
+            ClaimStatusCode::PrimaryForwarded
+                | ClaimStatusCode::SecondaryForwarded
+                | ClaimStatusCode::TertiaryForwarded
+                | ClaimStatusCode::NotOurClaim
+        )
+    }
+}
 
+/// Generates and adjudicates synthetic claims.
 pub struct ClaimGenerator {
     rng: rand_chacha::ChaCha8Rng,
     procedure_codes: Vec<ProcedureCode>,
     modifiers: Vec<String>,
     place_of_service_codes: HashMap<String, String>,
+    remark_codes: Vec<String>,
+    carc_codes: CarcTable,
+    fee_schedule: FeeSchedule,
+    clock: Clock,
 }
 
-
+/// A procedure code with its typical charge and units.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct ProcedureCode {
     code: String,
@@ -118,21 +449,11 @@ struct ProcedureCode {
 }
 
 impl ClaimGenerator {
-    
-    pub fn new(seed: Option<u64>) -> Self {
-        use rand_chacha::rand_core::SeedableRng;
-
-        let rng = if let Some(seed) = seed {
-            rand_chacha::ChaCha8Rng::seed_from_u64(seed)
-        } else {
-            rand_chacha::ChaCha8Rng::from_entropy()
-        };
-
-        
-        let data_dir = env::var("ZEDI_GEN_DATA_DIR").unwrap_or_else(|_| "data".to_string());
-        let data_path = Path::new(&data_dir);
+    /// Creates a generator, loading reference data from `data_path`.
+    pub fn new(seed: Option<u64>, data_path: &Path) -> Self {
+        let rng = stream_rng(seed, Stream::Claims);
 
-        
+        /// A row of `procedure_codes.csv`.
         #[derive(Debug, Deserialize)]
         struct CsvProcedureCode {
             code: String,
@@ -143,16 +464,14 @@ impl ClaimGenerator {
 
         let mut procedure_codes = Vec::new();
         if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("procedure_codes.csv")) {
-            for result in rdr.deserialize() {
-                if let Ok(rec) = result {
-                    let rec: CsvProcedureCode = rec;
-                    procedure_codes.push(ProcedureCode {
-                        code: rec.code,
-                        description: rec.description,
-                        typical_charge: rec.typical_charge,
-                        typical_units: rec.typical_units,
-                    });
-                }
+            for rec in rdr.deserialize().flatten() {
+                let rec: CsvProcedureCode = rec;
+                procedure_codes.push(ProcedureCode {
+                    code: rec.code,
+                    description: rec.description,
+                    typical_charge: rec.typical_charge,
+                    typical_units: rec.typical_units,
+                });
             }
         }
         if procedure_codes.is_empty() {
@@ -170,11 +489,9 @@ impl ClaimGenerator {
             .has_headers(false)
             .from_path(data_path.join("modifiers.csv"))
         {
-            for result in rdr.records() {
-                if let Ok(record) = result {
-                    if let Some(m) = record.get(0) {
-                        modifiers.push(m.to_string());
-                    }
+            for record in rdr.records().flatten() {
+                if let Some(m) = record.get(0) {
+                    modifiers.push(m.to_string());
                 }
             }
         }
@@ -187,7 +504,7 @@ impl ClaimGenerator {
             ];
         }
 
-        
+        /// A row of `pos_codes.csv`.
         #[derive(Debug, Deserialize)]
         struct CsvPosCode {
             pos: String,
@@ -196,11 +513,9 @@ impl ClaimGenerator {
 
         let mut place_of_service_codes = HashMap::new();
         if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("pos_codes.csv")) {
-            for result in rdr.deserialize() {
-                if let Ok(rec) = result {
-                    let rec: CsvPosCode = rec;
-                    place_of_service_codes.insert(rec.pos, rec.description);
-                }
+            for rec in rdr.deserialize().flatten() {
+                let rec: CsvPosCode = rec;
+                place_of_service_codes.insert(rec.pos, rec.description);
             }
         }
         if place_of_service_codes.is_empty() {
@@ -210,15 +525,49 @@ impl ClaimGenerator {
             place_of_service_codes.insert("23".to_string(), "Emergency Room".to_string());
         }
 
+        /// A row of `remark_codes.csv`.
+        #[derive(Debug, Deserialize)]
+        struct CsvRemarkCode {
+            code: String,
+        }
+
+        let mut remark_codes = Vec::new();
+        if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("remark_codes.csv")) {
+            for rec in rdr.deserialize::<CsvRemarkCode>().flatten() {
+                remark_codes.push(rec.code);
+            }
+        }
+        if remark_codes.is_empty() {
+            remark_codes = vec![
+                "N130".to_string(),
+                "M15".to_string(),
+                "N381".to_string(),
+                "MA130".to_string(),
+            ];
+        }
+
+        let carc_codes = CarcTable::load(data_path);
+        let fee_schedule = FeeSchedule::load(data_path);
+
         Self {
             rng,
             procedure_codes,
             modifiers,
             place_of_service_codes,
+            remark_codes,
+            carc_codes,
+            fee_schedule,
+            clock: Clock::system(),
         }
     }
 
-    
+    /// Uses `clock` for service and received dates.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Generates an adjudicated claim for `patient`.
     pub fn generate_claim(
         &mut self,
         patient: Person,
@@ -228,57 +577,193 @@ impl ClaimGenerator {
         let claim_id = format!("CLM{:08}", self.rng.gen_range(10000000..=99999999));
 
         
+        let mut pos_codes: Vec<&String> = self.place_of_service_codes.keys().collect();
+        pos_codes.sort();
+        let place_of_service = pos_codes
+            .choose(&mut self.rng)
+            .map(|pos| pos.to_string())
+            .unwrap_or_else(|| "11".to_string());
+
+        
+        let payment_date = self.clock.today();
+        let service_date = payment_date
+            - chrono::Duration::days(
+                self.rng
+                    .gen_range(MIN_SERVICE_LAG_DAYS..=SERVICE_DATE_WINDOW_DAYS),
+            );
+
+        
         let num_service_lines = self.rng.gen_range(1..=5);
         let mut service_lines = Vec::with_capacity(num_service_lines as usize);
 
-        let mut total_charge = 0;
-        let mut total_payment = 0;
-        let mut total_adjustment = 0;
-
         for i in 0..num_service_lines {
-            let procedure = self.procedure_codes.choose(&mut self.rng).unwrap();
-
+            let mut procedure = self.procedure_codes.choose(&mut self.rng).unwrap();
             let charge_amount = procedure.typical_charge;
-            let payment_amount = (charge_amount as f64 * self.rng.gen_range(0.5..1.0)) as u64;
-            let adjustment_amount = charge_amount - payment_amount;
 
-            total_charge += charge_amount;
-            total_payment += payment_amount;
-            total_adjustment += adjustment_amount;
+            
+            let mut submitted_procedure_code = None;
+            if self.rng.gen_bool(0.03) {
+                if let Some(adjudicated) = downcode(&self.procedure_codes, procedure) {
+                    submitted_procedure_code = Some(procedure.code.clone());
+                    procedure = adjudicated;
+                }
+            }
 
             
             let num_modifiers = self.rng.gen_range(0..=2);
-            let modifiers = self
+            let modifiers: Vec<String> = self
                 .modifiers
                 .choose_multiple(&mut self.rng, num_modifiers as usize)
                 .cloned()
                 .collect();
 
-            let service_line = ServiceLine {
+            
+            let allowed_amount = self
+                .fee_schedule
+                .allowed(
+                    &procedure.code,
+                    &modifiers,
+                    &billing_provider.address.state,
+                    procedure.typical_units,
+                )
+                .unwrap_or(charge_amount * UNLISTED_ALLOWED_PERCENT / 100)
+                .min(charge_amount);
+            let adjustments = Self::split_adjustment(
+                &mut self.rng,
+                &self.carc_codes,
+                charge_amount - allowed_amount,
+                allowed_amount,
+            );
+            let patient_share: u64 = adjustments
+                .iter()
+                .filter(|a| a.group == AdjustmentGroup::PatientResponsibility)
+                .map(|a| a.amount)
+                .sum();
+            let payment_amount = allowed_amount - patient_share;
+            let adjustment_amount = charge_amount - payment_amount;
+
+            let span_days = if place_of_service == "21" {
+                (procedure.typical_units.ceil() as i64).clamp(1, MIN_SERVICE_LAG_DAYS)
+            } else {
+                1
+            };
+            let service_end_date = (span_days > 1).then(|| {
+                (service_date + chrono::Duration::days(span_days - 1))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            });
+
+            let remark_codes = if adjustment_amount > 0 && self.rng.gen_bool(0.25) {
+                self.remark_codes
+                    .choose(&mut self.rng)
+                    .cloned()
+                    .into_iter()
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let mut service_line = ServiceLine {
                 line_number: i + 1,
                 procedure_code: procedure.code.clone(),
                 procedure_description: procedure.description.clone(),
-                service_date: Utc::now().format("%Y-%m-%d").to_string(),
+                service_date: service_date.format("%Y-%m-%d").to_string(),
                 charge_amount,
                 payment_amount,
                 paid_amount: payment_amount,
                 adjustment_amount,
+                adjustments,
                 units: procedure.typical_units,
-                place_of_service: "11".to_string(), 
-                revenue_code: None, 
+                place_of_service: place_of_service.clone(),
+                revenue_code: revenue_code(&place_of_service),
                 modifiers,
+                service_end_date,
+                line_item_control_number: format!("{}-{}", claim_id, i + 1),
+                allowed_amount,
+                remark_codes,
+                submitted_procedure_code,
             };
+            balancing::balance_line(&mut service_line);
 
             service_lines.push(service_line);
         }
 
+        let totals = ClaimTotals::of(&service_lines);
+        let total_charge = totals.charge.unsigned_cents();
+        let total_payment = totals.payment.unsigned_cents();
+        let total_adjustment = totals.adjustment.unsigned_cents();
+        let patient_responsibility = totals.patient_responsibility.unsigned_cents();
+
         
-        let status = if total_payment == 0 {
-            ClaimStatus::Denied
-        } else if total_payment < total_charge {
-            ClaimStatus::Partial
+        let service_days: std::collections::BTreeSet<&str> = service_lines
+            .iter()
+            .flat_map(|line| [Some(&line.service_date), line.service_end_date.as_ref()])
+            .flatten()
+            .map(|date| date.as_str())
+            .collect();
+        let parse = |date: &&str| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let first_day = service_days.iter().next().and_then(parse);
+        let last_day = service_days.iter().next_back().and_then(parse);
+        let covered_days = match (first_day, last_day) {
+            (Some(from), Some(to)) if place_of_service == "21" => (to - from).num_days() as u32 + 1,
+            _ => 0,
+        };
+        let received_date = service_days
+            .iter()
+            .next_back()
+            .map(|date| date.to_string())
+            .unwrap_or_default();
+
+        let medical_record_number = self
+            .rng
+            .gen_bool(0.4)
+            .then(|| format!("MR{:07}", self.rng.gen_range(0..10_000_000)));
+        let prior_authorization = self
+            .rng
+            .gen_bool(0.15)
+            .then(|| format!("PA{:08}", self.rng.gen_range(0..100_000_000)));
+        let original_reference = self
+            .rng
+            .gen_bool(0.05)
+            .then(|| format!("CLM{:08}", self.rng.gen_range(10000000..=99999999)));
+        let corrected_member_id = self
+            .rng
+            .gen_bool(0.02)
+            .then(|| format!("W{:09}", self.rng.gen_range(0..1_000_000_000u32)));
+        let crossover_carrier = self.rng.gen_bool(0.05).then(|| self.crossover_carrier());
+        let sequence = match self.rng.gen::<f64>() {
+            roll if roll < TERTIARY_CLAIM_RATE => 3,
+            roll if roll < TERTIARY_CLAIM_RATE + SECONDARY_CLAIM_RATE => 2,
+            _ => 1,
+        };
+        let status_code = ClaimStatusCode::processed(sequence, crossover_carrier.is_some());
+
+        
+        let claim_frequency_code = if original_reference.is_some() {
+            "7"
         } else {
-            ClaimStatus::Paid
+            "1"
+        };
+        let bill_type = match place_of_service.as_str() {
+            "21" => Some(format!("11{}", claim_frequency_code)),
+            "22" | "23" => Some(format!("13{}", claim_frequency_code)),
+            _ => None,
+        };
+
+        let interest_amount = if self.rng.gen_bool(0.03) {
+            self.rng.gen_range(1..=500)
+        } else {
+            0
+        };
+        let prompt_pay_discount = if self.rng.gen_bool(0.02) {
+            total_payment / 50
+        } else {
+            0
+        };
+        let patient_amount_paid = if self.rng.gen_bool(0.2) {
+            patient_responsibility / 2
+        } else {
+            0
         };
 
         Claim {
@@ -290,10 +775,336 @@ impl ClaimGenerator {
             total_charge,
             total_payment,
             total_adjustment,
-            patient_responsibility: (total_charge - total_payment) / 2, 
-            status,
+            patient_responsibility,
+            status: ClaimStatus::of(status_code, total_charge, total_payment),
+            subscriber: None,
+            corrected_member_id,
+            crossover_carrier,
+            medical_record_number,
+            prior_authorization,
+            original_reference,
+            received_date,
+            coverage_expiration: None,
+            interest_amount,
+            prompt_pay_discount,
+            patient_amount_paid,
+            covered_days,
+            bill_type,
+            claim_frequency_code: claim_frequency_code.to_string(),
+            status_code,
+            denial_reason: None,
+        }
+    }
+
+    /// Picks the carrier a claim is crossed over to.
+    fn crossover_carrier(&mut self) -> CrossoverCarrier {
+        let (name, payer_id) = CROSSOVER_CARRIERS
+            .choose(&mut self.rng)
+            .copied()
+            .unwrap_or(("MEDIGAP SUPPLEMENT PLAN", "60054"));
+        CrossoverCarrier {
+            name: name.to_string(),
+            payer_id: payer_id.to_string(),
+            claim_number: format!("{:013}", self.rng.gen_range(0..10_000_000_000_000u64)),
+        }
+    }
+
+    /// Picks a coverage end date shortly before the claim's first date of service.
+    fn coverage_expiration(&mut self, claim: &Claim) -> Option<String> {
+        let first_day = claim.service_lines.iter().map(|l| &l.service_date).min()?;
+        let first_day = chrono::NaiveDate::parse_from_str(first_day, "%Y-%m-%d").ok()?;
+        let expiration = first_day - chrono::Duration::days(self.rng.gen_range(1..=90));
+        Some(expiration.format("%Y-%m-%d").to_string())
+    }
+
+    /// Applies `payer`'s denial rates and fee schedule multiplier to `claim` and settles it.
+    pub fn adjudicate(&mut self, claim: &mut Claim, payer: &Payer) {
+        let claim_denial = self
+            .rng
+            .gen_bool(payer.denial_rate.clamp(0.0, 1.0))
+            .then(|| payer.denial_mix.choose(&mut self.rng, false))
+            .flatten();
+        if let Some(reason) = claim_denial {
+            for line in &mut claim.service_lines {
+                deny_line(line, reason);
+            }
+            claim.status_code = if reason == DenialReason::WrongPayer {
+                if claim.crossover_carrier.is_none() {
+                    claim.crossover_carrier = Some(self.crossover_carrier());
+                }
+                ClaimStatusCode::NotOurClaim
+            } else {
+                claim.crossover_carrier = None;
+                ClaimStatusCode::Denied
+            };
+            claim.coverage_expiration = if reason == DenialReason::Eligibility {
+                self.coverage_expiration(claim)
+            } else {
+                None
+            };
+            claim.denial_reason = Some(reason);
+            claim.interest_amount = 0;
+            claim.prompt_pay_discount = 0;
+        } else {
+            let multiplier = payer.fee_schedule_multiplier.max(0.0);
+            let sequence = claim.status_code.payer_sequence();
+            let multiline = claim.service_lines.len() > 1;
+            for line in &mut claim.service_lines {
+                if multiline && self.rng.gen_bool(payer.line_denial_rate.clamp(0.0, 1.0)) {
+                    if let Some(reason) = payer.denial_mix.choose(&mut self.rng, true) {
+                        deny_line(line, reason);
+                        continue;
+                    }
+                }
+
+                let allowed = (line.allowed_amount as f64 * multiplier).round() as u64;
+                let patient_share: u64 = line
+                    .adjustments
+                    .iter()
+                    .filter(|a| a.group == AdjustmentGroup::PatientResponsibility)
+                    .map(|a| a.amount)
+                    .sum();
+                line.paid_amount = allowed
+                    .min(line.charge_amount)
+                    .saturating_sub(patient_share);
+                if sequence > 1 {
+                    balancing::balance_line(line);
+                    coordinate_benefits(line, sequence);
+                }
+            }
         }
+
+        claim.settle();
     }
+
+    /// Generates provider level adjustments for a remittance with probability `rate`.
+    pub fn generate_provider_adjustments(
+        &mut self,
+        claims: &[Claim],
+        rate: f64,
+    ) -> Vec<ProviderAdjustment> {
+        if claims.is_empty() || !self.rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            return Vec::new();
+        }
+
+        let count = self.rng.gen_range(1..=3);
+        let reasons: Vec<ProviderAdjustmentReason> = PROVIDER_ADJUSTMENT_REASONS
+            .choose_multiple(&mut self.rng, count)
+            .copied()
+            .collect();
+
+        
+        let mut available: i64 = claims
+            .iter()
+            .map(|c| c.signed(c.total_payment).cents())
+            .sum();
+        let mut adjustments = Vec::with_capacity(reasons.len());
+        for reason in reasons {
+            let (reference_id, amount) = match reason {
+                ProviderAdjustmentReason::Interest => {
+                    let interest = self.rng.gen_range(100..=2500);
+                    available += interest;
+                    let claim = claims.choose(&mut self.rng).unwrap();
+                    (Some(claim.claim_id.clone()), -interest)
+                }
+                ProviderAdjustmentReason::ForwardingBalance => (
+                    Some(format!("FB{:08}", self.rng.gen_range(0..100_000_000))),
+                    available * self.rng.gen_range(5..=30) / 100,
+                ),
+                ProviderAdjustmentReason::OverpaymentRecovery => (
+                    Some(format!("CLM{:08}", self.rng.gen_range(10000000..=99999999))),
+                    available * self.rng.gen_range(5..=40) / 100,
+                ),
+                ProviderAdjustmentReason::Levy => (
+                    Some(format!("LV{:07}", self.rng.gen_range(0..10_000_000))),
+                    available * self.rng.gen_range(5..=15) / 100,
+                ),
+                ProviderAdjustmentReason::OriginationFee => {
+                    (None, self.rng.gen_range(100..=500).min(available))
+                }
+                ProviderAdjustmentReason::AuthorizedReturn => (
+                    Some(format!("CLM{:08}", self.rng.gen_range(10000000..=99999999))),
+                    available * self.rng.gen_range(5..=20) / 100,
+                ),
+            };
+            if amount == 0 {
+                continue;
+            }
+            if amount > 0 {
+                available -= amount;
+            }
+            adjustments.push(ProviderAdjustment {
+                reason,
+                reference_id,
+                amount,
+            });
+        }
+
+        adjustments
+    }
+
+    /// Splits a line's adjustments into CO pieces for `contractual` and a random PR share of
+    /// `allowed`.
+    fn split_adjustment(
+        rng: &mut rand_chacha::ChaCha8Rng,
+        carc_codes: &CarcTable,
+        contractual: u64,
+        allowed: u64,
+    ) -> Vec<Adjustment> {
+        let patient_share = if allowed > 0 && rng.gen_bool(0.6) {
+            (allowed as f64 * rng.gen_range(0.1..0.6)) as u64
+        } else {
+            0
+        };
+
+        let mut adjustments = Vec::new();
+        let secondary = if contractual > 0 && rng.gen_bool(0.1) {
+            SECONDARY_CONTRACTUAL_REASONS
+                .iter()
+                .filter(|code| carc_codes.contains(code))
+                .copied()
+                .collect::<Vec<_>>()
+                .choose(rng)
+                .map(|code| (*code, contractual * rng.gen_range(2..=15) / 100))
+        } else {
+            None
+        };
+        let secondary_amount = secondary.map_or(0, |(_, amount)| amount);
+        if contractual > secondary_amount {
+            adjustments.push(Adjustment::new(
+                AdjustmentGroup::Contractual,
+                "45",
+                contractual - secondary_amount,
+            ));
+        }
+        if let Some((code, amount)) = secondary.filter(|(_, amount)| *amount > 0) {
+            adjustments.push(Adjustment::new(AdjustmentGroup::Contractual, code, amount));
+        }
+
+        if patient_share > 0 {
+            match rng.gen_range(0..4) {
+                0 => {
+                    adjustments.push(Adjustment::new(
+                        AdjustmentGroup::PatientResponsibility,
+                        "1",
+                        patient_share,
+                    ));
+                }
+                1 => {
+                    adjustments.push(Adjustment::new(
+                        AdjustmentGroup::PatientResponsibility,
+                        "2",
+                        patient_share,
+                    ));
+                }
+                2 => {
+                    let deductible = patient_share * rng.gen_range(20..=80) / 100;
+                    if deductible > 0 {
+                        adjustments.push(Adjustment::new(
+                            AdjustmentGroup::PatientResponsibility,
+                            "1",
+                            deductible,
+                        ));
+                    }
+                    if patient_share > deductible {
+                        adjustments.push(Adjustment::new(
+                            AdjustmentGroup::PatientResponsibility,
+                            "2",
+                            patient_share - deductible,
+                        ));
+                    }
+                }
+                _ => {
+                    let copay = COPAY_AMOUNTS
+                        .choose(rng)
+                        .copied()
+                        .unwrap_or(2000)
+                        .min(patient_share);
+                    adjustments.push(Adjustment::new(
+                        AdjustmentGroup::PatientResponsibility,
+                        "3",
+                        copay,
+                    ));
+                    if patient_share > copay {
+                        adjustments.push(Adjustment::new(
+                            AdjustmentGroup::PatientResponsibility,
+                            "2",
+                            patient_share - copay,
+                        ));
+                    }
+                }
+            }
+        }
+
+        adjustments
+    }
+}
+
+/// Denies `line` in full with the reason's CARC and RARC.
+fn deny_line(line: &mut ServiceLine, reason: DenialReason) {
+    line.paid_amount = 0;
+    line.adjustments = vec![Adjustment::new(
+        AdjustmentGroup::Contractual,
+        reason.carc(),
+        line.charge_amount,
+    )];
+    line.remark_codes = vec![reason.rarc().to_string()];
+}
+
+/// Turns a primary adjudication of `line` into a secondary (`sequence` 2) or tertiary one.
+fn coordinate_benefits(line: &mut ServiceLine, sequence: u8) {
+    let patient_share = |reason: Option<&str>| -> u64 {
+        line.adjustments
+            .iter()
+            .filter(|a| a.group == AdjustmentGroup::PatientResponsibility)
+            .filter(|a| reason.map_or(true, |reason| a.reason_code == reason))
+            .map(|a| a.amount)
+            .sum()
+    };
+    let deductible = patient_share(Some("1"));
+    let cost_share = patient_share(None) - deductible;
+
+    let (prior_payment, payment) = if sequence == 2 {
+        (line.paid_amount, cost_share)
+    } else {
+        (line.paid_amount + cost_share, deductible)
+    };
+    line.adjustments.retain(|a| {
+        a.group != AdjustmentGroup::PatientResponsibility || (sequence == 2 && a.reason_code == "1")
+    });
+    if prior_payment > 0 {
+        line.adjustments.push(Adjustment::new(
+            AdjustmentGroup::Other,
+            PRIOR_PAYER_REASON,
+            prior_payment,
+        ));
+    }
+    line.paid_amount = payment;
+}
+
+/// The NUBC revenue code of an institutional line at `place_of_service`; `None` for places of
+/// service billed on professional claims.
+pub fn revenue_code(place_of_service: &str) -> Option<String> {
+    let code = match place_of_service {
+        "21" => "0120",
+        "22" => "0510",
+        "23" => "0450",
+        _ => return None,
+    };
+    Some(code.to_string())
+}
+
+/// The next lower level of `procedure`'s code family, e.g. 99213 for 99214.
+fn downcode<'a>(
+    procedures: &'a [ProcedureCode],
+    procedure: &ProcedureCode,
+) -> Option<&'a ProcedureCode> {
+    let code = procedure.code.as_str();
+    procedures
+        .iter()
+        .filter(|p| p.code.len() == code.len() && p.code.get(..4) == code.get(..4))
+        .filter(|p| p.code.as_str() < code)
+        .max_by(|a, b| a.code.cmp(&b.code))
 }
 
 #[cfg(test)]
@@ -304,8 +1115,8 @@ mod tests {
     fn test_claim_generation() {
         use crate::population::PopulationGenerator;
 
-        let mut claim_gen = ClaimGenerator::new(Some(42));
-        let mut pop_gen = PopulationGenerator::new(Some(42));
+        let mut claim_gen = ClaimGenerator::new(Some(42), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(42), Path::new("data"));
 
         let patient = pop_gen.generate_person();
         let provider = pop_gen.generate_provider();
@@ -317,4 +1128,291 @@ mod tests {
         assert!(claim.total_charge > 0);
         assert!(claim.patient_responsibility <= claim.total_charge - claim.total_payment);
     }
+
+    #[test]
+    fn test_adjustments_balance_service_lines() {
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(7), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(7), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+
+        for _ in 0..50 {
+            let claim = claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+
+            let mut patient_responsibility = 0;
+            for line in &claim.service_lines {
+                let adjusted: u64 = line.adjustments.iter().map(|a| a.amount).sum();
+                assert_eq!(adjusted, line.adjustment_amount);
+                assert!(line.adjustments.iter().all(|a| a.amount > 0));
+
+                let contractual: u64 = line
+                    .adjustments
+                    .iter()
+                    .filter(|a| a.group == AdjustmentGroup::Contractual)
+                    .map(|a| a.amount)
+                    .sum();
+                assert_eq!(line.allowed_amount, line.charge_amount - contractual);
+                patient_responsibility += adjusted - contractual;
+            }
+            assert_eq!(claim.patient_responsibility, patient_responsibility);
+        }
+    }
+
+    #[test]
+    fn test_carc_codes_follow_data_dir() {
+        use crate::population::PopulationGenerator;
+        use std::collections::HashSet;
+
+        let data_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            data_dir.path().join("carc_codes.csv"),
+            "code,description\n1,Deductible Amount\n2,Coinsurance Amount\n\
+             3,Co-payment Amount\n45,Charge exceeds fee schedule\n",
+        )
+        .unwrap();
+        let contractual_codes = |data_path: &Path| {
+            let mut claim_gen = ClaimGenerator::new(Some(5), data_path);
+            let mut pop_gen = PopulationGenerator::new(Some(5), Path::new("data"));
+            let provider = pop_gen.generate_provider();
+            let mut codes = HashSet::new();
+            for _ in 0..100 {
+                let claim =
+                    claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+                for line in &claim.service_lines {
+                    codes.extend(
+                        line.adjustments
+                            .iter()
+                            .filter(|a| a.group == AdjustmentGroup::Contractual)
+                            .map(|a| a.reason_code.clone()),
+                    );
+                }
+            }
+            codes
+        };
+
+        assert!(contractual_codes(Path::new("data")).len() > 1);
+        assert_eq!(
+            contractual_codes(data_dir.path()),
+            HashSet::from(["45".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_payment_follows_fee_schedule() {
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(9), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(9), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let schedule = FeeSchedule::load(Path::new("data"));
+
+        for _ in 0..50 {
+            let claim = claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+            for line in &claim.service_lines {
+                let allowed = schedule
+                    .allowed(
+                        &line.procedure_code,
+                        &line.modifiers,
+                        &provider.address.state,
+                        line.units,
+                    )
+                    .unwrap()
+                    .min(line.charge_amount);
+                let total = |group| -> u64 {
+                    line.adjustments
+                        .iter()
+                        .filter(|a| a.group == group)
+                        .map(|a| a.amount)
+                        .sum()
+                };
+
+                assert_eq!(line.allowed_amount, allowed);
+                assert_eq!(
+                    line.paid_amount,
+                    allowed - total(AdjustmentGroup::PatientResponsibility)
+                );
+                assert_eq!(
+                    total(AdjustmentGroup::Contractual),
+                    line.charge_amount - allowed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_recoded_lines_are_downcoded() {
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(14), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(14), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+
+        let mut recoded = 0;
+        for _ in 0..500 {
+            let claim = claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+            for line in &claim.service_lines {
+                if let Some(ref submitted) = line.submitted_procedure_code {
+                    assert_eq!(submitted[..4], line.procedure_code[..4]);
+                    assert!(line.procedure_code < *submitted);
+                    recoded += 1;
+                }
+            }
+        }
+        assert!(recoded > 0);
+    }
+
+    #[test]
+    fn test_provider_adjustments_never_exceed_payment() {
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(9), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(9), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+
+        for _ in 0..50 {
+            let claim = claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+            let claims = std::slice::from_ref(&claim);
+            let skipped = claim_gen.generate_provider_adjustments(claims, 0.0);
+            assert!(skipped.is_empty());
+
+            let adjustments = claim_gen.generate_provider_adjustments(claims, 1.0);
+            assert!(!adjustments.is_empty() || claim.total_payment == 0);
+            assert!(adjustments.len() <= 3);
+
+            let net: i64 = adjustments.iter().map(|a| a.amount).sum();
+            assert!(net <= claim.total_payment as i64);
+        }
+    }
+
+    #[test]
+    fn test_denials_set_claim_status_codes() {
+        use crate::balancing::check_claim;
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(25), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(25), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let payer = |mix: &str, denial_rate, line_denial_rate| Payer {
+            denial_rate,
+            line_denial_rate,
+            denial_mix: mix.parse().unwrap(),
+            ..Default::default()
+        };
+
+        for (mix, code, forwarded) in [("eligibility=1", "4", false), ("wrong_payer=1", "23", true)]
+        {
+            let mut claim =
+                claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+            claim_gen.adjudicate(&mut claim, &payer(mix, 1.0, 0.0));
+
+            let reason = claim.denial_reason.unwrap();
+            assert_eq!(claim.status_code.code(), code);
+            assert_eq!(claim.status, ClaimStatus::Denied);
+            assert_eq!(claim.crossover_carrier.is_some(), forwarded);
+            assert_eq!((claim.total_payment, claim.patient_responsibility), (0, 0));
+            for line in &claim.service_lines {
+                assert_eq!(line.adjustments[0].reason_code, reason.carc());
+                assert_eq!(line.remark_codes, [reason.rarc()]);
+            }
+            assert_eq!(check_claim(&claim), Vec::new());
+        }
+        for (status_code, status) in [
+            (ClaimStatusCode::ProcessedAsSecondary, ClaimStatus::Partial),
+            (ClaimStatusCode::SecondaryForwarded, ClaimStatus::Pending),
+            (ClaimStatusCode::NotOurClaim, ClaimStatus::Denied),
+        ] {
+            assert_eq!(ClaimStatus::of(status_code, 10000, 4000), status);
+        }
+
+        let line_payer = payer("prior_authorization=1", 0.0, 1.0);
+        for _ in 0..20 {
+            let mut claim =
+                claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+            let status_code = claim.status_code;
+            claim_gen.adjudicate(&mut claim, &line_payer);
+
+            assert_eq!(claim.status_code, status_code);
+            assert!(claim.denial_reason.is_none());
+            let denied = claim.service_lines.len() > 1;
+            for line in &claim.service_lines {
+                assert_eq!(
+                    line.paid_amount == 0 && line.remark_codes == ["M62"],
+                    denied
+                );
+            }
+            assert_eq!(check_claim(&claim), Vec::new());
+        }
+    }
+
+    #[test]
+    fn test_secondary_claims_report_prior_payment() {
+        use crate::balancing::check_claim;
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(26), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(26), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+
+        for sequence in [1, 2, 3] {
+            for _ in 0..20 {
+                let mut claim =
+                    claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+                claim.status_code = ClaimStatusCode::processed(sequence, false);
+                let primary: Vec<(u64, u64)> = claim
+                    .service_lines
+                    .iter()
+                    .map(|line| (line.allowed_amount, line.paid_amount))
+                    .collect();
+                claim_gen.adjudicate(&mut claim, &Payer::default());
+
+                for (line, (allowed, primary_paid)) in claim.service_lines.iter().zip(primary) {
+                    let prior: u64 = line
+                        .adjustments
+                        .iter()
+                        .filter(|a| a.group == AdjustmentGroup::Other && a.reason_code == "23")
+                        .map(|a| a.amount)
+                        .sum();
+                    let patient_share: u64 = line
+                        .adjustments
+                        .iter()
+                        .filter(|a| a.group == AdjustmentGroup::PatientResponsibility)
+                        .map(|a| a.amount)
+                        .sum();
+                    assert_eq!(line.allowed_amount, allowed);
+                    assert_eq!(prior + line.paid_amount + patient_share, allowed);
+                    match sequence {
+                        1 => assert_eq!((prior, line.paid_amount), (0, primary_paid)),
+                        2 => assert_eq!(prior, primary_paid),
+                        _ => assert_eq!(patient_share, 0),
+                    }
+                }
+                assert_eq!(check_claim(&claim), Vec::new());
+            }
+        }
+    }
+
+    #[test]
+    fn test_secondary_claims_balance_above_fee_schedule() {
+        use crate::balancing::check_claim;
+        use crate::population::PopulationGenerator;
+
+        let mut claim_gen = ClaimGenerator::new(Some(27), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(27), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let generous = Payer {
+            fee_schedule_multiplier: 2.0,
+            ..Default::default()
+        };
+
+        for sequence in [2, 3] {
+            for _ in 0..50 {
+                let mut claim =
+                    claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None);
+                claim.status_code = ClaimStatusCode::processed(sequence, false);
+                claim_gen.adjudicate(&mut claim, &generous);
+                assert_eq!(check_claim(&claim), Vec::new());
+            }
+        }
+    }
 }
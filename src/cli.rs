@@ -1,9 +1,13 @@
+//! Command line arguments.
 
-
+use crate::config::ClaimsPerRemittance;
+use crate::denials::DenialMix;
+use crate::payers::PayerMix;
+use chrono::NaiveDate;
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
-
+/// Command line arguments of `zedi-gen`.
 #[derive(Parser, Debug)]
 #[command(
     name = "zedi-gen",
@@ -13,66 +17,234 @@ use std::path::PathBuf;
     subcommand_required = true,
 )]
 pub struct Cli {
-    
+    /// The subcommand to run.
     #[command(subcommand)]
     pub command: Command,
 }
 
+/// The `zedi-gen` subcommands.
 #[derive(Subcommand, Debug)]
 pub enum Command {
-    
+    /// Generate synthetic X12 835 healthcare claim data.
     Generate(GenerateArgs),
-    
+    /// Score conformance of an X12 835 file against the spec.
     Conformance(ConformanceArgs),
+    /// Convert between X12 835 and the JSON claim model.
+    Convert(ConvertArgs),
+    /// Acknowledge an X12 interchange with a TA1 and a 999.
+    Ack(AckArgs),
 }
 
-
+/// Arguments of `zedi-gen generate`.
 #[derive(Args, Debug)]
 pub struct GenerateArgs {
-    
+    /// Number of claims to generate.
     #[arg(short, long, default_value_t = 1000)]
     pub count: usize,
 
-    
+    /// Anomaly injection rate (0.0 to 100.0).
     #[arg(short, long, default_value_t = 1.0)]
     pub anomaly_rate: f64,
 
-    
+    /// Output file (default: stdout).
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    
+    /// Random seed for reproducible output (combine with --as-of for byte-identical runs).
     #[arg(long)]
     pub seed: Option<u64>,
 
-    
+    /// Data directory for CSV files for realistic generation.
     #[arg(long, default_value = "data")]
     pub data_dir: PathBuf,
 
-    
+    /// Output format.
     #[arg(long, value_enum, default_value_t = OutputFormat::X12)]
     pub format: OutputFormat,
-}
 
+    /// Share of remittances (0.0 to 1.0) that carry provider level adjustments.
+    #[arg(long, default_value_t = 0.05)]
+    pub plb_rate: f64,
+
+    /// Pay several claims per remittance instead of one.
+    #[arg(long)]
+    pub batch: bool,
+
+    /// Claims per remittance with --batch: N, MIN-MAX or poisson:MEAN.
+    #[arg(long, default_value = "1-25")]
+    pub claims_per_remittance: ClaimsPerRemittance,
+
+    /// X12 element separator.
+    #[arg(long, default_value_t = '*')]
+    pub element_separator: char,
+
+    /// X12 component element separator.
+    #[arg(long, default_value_t = ':')]
+    pub component_separator: char,
+
+    /// X12 repetition separator.
+    #[arg(long, default_value_t = '^')]
+    pub repetition_separator: char,
+
+    /// X12 segment terminator.
+    #[arg(long, default_value_t = '~')]
+    pub segment_terminator: char,
+
+    /// Line ending written after each segment.
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    pub line_ending: LineEnding,
 
+    /// Date used as "today" for all generated dates (default: system clock).
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub as_of: Option<NaiveDate>,
+
+    /// Persist ISA/GS control numbers so consecutive runs never reuse them.
+    #[arg(long)]
+    pub control_number_file: Option<PathBuf>,
+
+    /// Validate X12 output and fail on conformance errors outside anomalous transactions.
+    #[arg(long)]
+    pub self_check: bool,
+
+    /// Also write the matching 837P/837I claims (X12 output only).
+    #[arg(long)]
+    pub claims_output: Option<PathBuf>,
+
+    /// Share of claims per payer ID from the payer registry (default: equal).
+    #[arg(long, value_name = "PAYER_ID=WEIGHT,...")]
+    pub payer_mix: Option<PayerMix>,
+
+    /// Denial reason weights for payers without their own mix (default: equal).
+    #[arg(long, value_name = "REASON=WEIGHT,...")]
+    pub denial_mix: Option<DenialMix>,
+}
+
+/// Arguments of `zedi-gen conformance`.
 #[derive(Args, Debug)]
 pub struct ConformanceArgs {
-    
+    /// Input X12 835 file to check for conformance (plain or gzip, - for stdin).
+    #[arg()]
+    pub input_path: PathBuf,
+
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub report_format: ReportFormat,
+
+    /// Report file (default: stdout).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Exit non-zero when the score (0-100) is below SCORE.
+    #[arg(long, value_name = "SCORE")]
+    pub fail_under: Option<f64>,
+
+    /// Exit non-zero when more than N errors are found [default: 0].
+    #[arg(long)]
+    pub max_errors: Option<usize>,
+
+    /// Exit zero despite errors unless --max-errors or --fail-under is exceeded.
+    #[arg(long)]
+    pub allow_errors: bool,
+
+    /// Directory holding carc_codes.csv and remark_codes.csv.
+    #[arg(long, default_value = "data")]
+    pub data_dir: PathBuf,
+}
+
+/// Arguments of `zedi-gen convert`.
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// X12 835 file, remittance JSON document, or JSON array of claims.
+    #[arg()]
+    pub input_path: PathBuf,
+
+    /// Output file (default: stdout).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Output format (default: json-pretty for X12 input, x12 for JSON input).
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Envelope and payment date when converting a bare claims array.
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub as_of: Option<NaiveDate>,
+}
+
+/// Arguments of `zedi-gen ack`.
+#[derive(Args, Debug)]
+pub struct AckArgs {
+    /// X12 835 file to acknowledge (plain or gzip, - for stdin).
     #[arg()]
     pub input_path: PathBuf,
+
+    /// Output file (default: stdout).
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Force every TA104/IK501/AK901 status.
+    #[arg(long, value_enum)]
+    pub outcome: Option<AckOutcome>,
+
+    /// Date used for the acknowledgment's ISA/GS timestamps.
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub as_of: Option<NaiveDate>,
+
+    /// Persist ISA/GS control numbers across runs.
+    #[arg(long)]
+    pub control_number_file: Option<PathBuf>,
+
+    /// Directory holding the CARC/RARC code lists.
+    #[arg(long, default_value = "data")]
+    pub data_dir: PathBuf,
 }
 
+/// Status forced onto an acknowledgment.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckOutcome {
+    /// `A`: accepted.
+    Accepted,
+    /// `E`: accepted with errors.
+    AcceptedWithErrors,
+    /// `R`: rejected.
+    Rejected,
+}
 
+/// Output format of generated or converted data.
 #[derive(ValueEnum, Clone, Debug)]
 pub enum OutputFormat {
-    
+    /// X12 835 text.
     X12,
-    
+    /// Compact JSON.
     Json,
-    
+    /// Indented JSON.
     JsonPretty,
 }
 
+/// Conformance report format.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum ReportFormat {
+    /// Human-readable findings and summary.
+    Text,
+    /// JSON document.
+    Json,
+    /// JUnit XML with a test case for segment presence and each SNIP level.
+    Junit,
+    /// SARIF 2.1.0 log.
+    Sarif,
+}
+
+/// Line ending written after each segment.
+#[derive(ValueEnum, Clone, Debug)]
+pub enum LineEnding {
+    /// No line breaks.
+    None,
+    /// `\n`.
+    Lf,
+    /// `\r\n`.
+    CrLf,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,6 +259,8 @@ mod tests {
             "100",
             "--anomaly-rate",
             "5.0",
+            "--as-of",
+            "2024-03-15",
         ]);
         match cli.command {
             Command::Generate(args) => {
@@ -94,6 +268,16 @@ mod tests {
                 assert_eq!(args.anomaly_rate, 5.0);
                 assert!(args.output.is_none());
                 assert_eq!(args.data_dir, PathBuf::from("data"));
+                assert_eq!(args.plb_rate, 0.05);
+                assert!(!args.batch);
+                assert_eq!(
+                    args.claims_per_remittance,
+                    ClaimsPerRemittance::Uniform { min: 1, max: 25 }
+                );
+                assert_eq!(args.element_separator, '*');
+                assert_eq!(args.segment_terminator, '~');
+                assert!(matches!(args.line_ending, LineEnding::Lf));
+                assert_eq!(args.as_of, NaiveDate::from_ymd_opt(2024, 3, 15));
             }
             _ => panic!("Expected Generate command"),
         }
@@ -105,8 +289,68 @@ mod tests {
         match cli.command {
             Command::Conformance(args) => {
                 assert_eq!(args.input_path, PathBuf::from("file.edi"));
+                assert!(matches!(args.report_format, ReportFormat::Text));
+                assert!(args.output.is_none());
+                assert!(args.fail_under.is_none());
+                assert!(args.max_errors.is_none());
+                assert!(!args.allow_errors);
+            }
+            _ => panic!("Expected Conformance command"),
+        }
+
+        let cli = Cli::parse_from([
+            "zedi-gen",
+            "conformance",
+            "file.edi",
+            "--report-format",
+            "sarif",
+            "--fail-under",
+            "95",
+            "--max-errors",
+            "0",
+            "--allow-errors",
+        ]);
+        match cli.command {
+            Command::Conformance(args) => {
+                assert!(matches!(args.report_format, ReportFormat::Sarif));
+                assert_eq!(args.fail_under, Some(95.0));
+                assert_eq!(args.max_errors, Some(0));
+                assert!(args.allow_errors);
             }
             _ => panic!("Expected Conformance command"),
         }
     }
+
+    #[test]
+    fn test_cli_convert_parsing() {
+        let cli = Cli::parse_from(["zedi-gen", "convert", "file.edi", "--format", "json"]);
+        match cli.command {
+            Command::Convert(args) => {
+                assert_eq!(args.input_path, PathBuf::from("file.edi"));
+                assert!(matches!(args.format, Some(OutputFormat::Json)));
+                assert!(args.output.is_none());
+            }
+            _ => panic!("Expected Convert command"),
+        }
+    }
+
+    #[test]
+    fn test_cli_ack_parsing() {
+        let cli = Cli::parse_from([
+            "zedi-gen",
+            "ack",
+            "file.edi",
+            "--outcome",
+            "accepted-with-errors",
+        ]);
+        match cli.command {
+            Command::Ack(args) => {
+                assert_eq!(args.input_path, PathBuf::from("file.edi"));
+                assert_eq!(args.outcome, Some(AckOutcome::AcceptedWithErrors));
+                assert!(args.output.is_none());
+                assert!(args.control_number_file.is_none());
+            }
+            _ => panic!("Expected Ack command"),
+        }
+    }
 }
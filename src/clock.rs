@@ -0,0 +1,69 @@
+//! The source of "today" for generated dates.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+/// The date and time that generated data treats as "now".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    now: NaiveDateTime,
+}
+
+impl Clock {
+    /// A clock reading the system time.
+    pub fn system() -> Self {
+        Self {
+            now: chrono::Local::now().naive_local(),
+        }
+    }
+
+    /// A clock fixed at midnight on `as_of`.
+    pub fn fixed(as_of: NaiveDate) -> Self {
+        Self {
+            now: as_of.and_hms_opt(0, 0, 0).unwrap_or_default(),
+        }
+    }
+
+    /// A fixed clock when `as_of` is given, otherwise the system clock.
+    pub fn from_as_of(as_of: Option<NaiveDate>) -> Self {
+        as_of.map_or_else(Self::system, Self::fixed)
+    }
+
+    /// The current date and time.
+    pub fn now(&self) -> NaiveDateTime {
+        self.now
+    }
+
+    /// The current date.
+    pub fn today(&self) -> NaiveDate {
+        self.now.date()
+    }
+
+    /// The last day of the current calendar year, used as the provider fiscal period end.
+    pub fn fiscal_period_end(&self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.now.year(), 12, 31).unwrap_or_else(|| self.today())
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let as_of = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        let clock = Clock::from_as_of(Some(as_of));
+
+        assert_eq!(clock.today(), as_of);
+        assert_eq!(clock.now().format("%H%M").to_string(), "0000");
+        assert_eq!(
+            clock.fiscal_period_end(),
+            NaiveDate::from_ymd_opt(2024, 12, 31).unwrap()
+        );
+    }
+}
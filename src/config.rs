@@ -1,49 +1,176 @@
+//! Generator configuration.
 
-
+use chrono::NaiveDate;
+use rand::Rng;
+use rand_distr::{Distribution, Poisson};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-
+/// Default directory of the CSV data files.
 const DEFAULT_DATA_DIR: &str = "data";
 
-use crate::errors::Error;
+/// Default share of remittances with provider level adjustments.
+const DEFAULT_PLB_RATE: f64 = 0.05;
 
+use crate::denials::DenialMix;
+use crate::errors::Error;
+use crate::payers::{Payer, PayerMix};
+use crate::x12::delimiters::{Delimiters, LineEnding};
 
+/// Generator configuration, built from the command line or a TOML file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(crate = "serde")]
 pub struct Config {
-    
+    /// Random seed for reproducible output.
     pub seed: Option<u64>,
 
-    
+    /// Number of claims to generate.
     pub claim_count: usize,
 
-    
+    /// Anomaly injection rate.
     pub anomaly_rate: f64,
 
-    
+    /// Output file; stdout when unset.
     pub output_path: Option<PathBuf>,
 
-    
+    /// Output format.
     pub output_format: OutputFormat,
-    
+    /// Directory holding the CSV reference data.
     pub data_dir: PathBuf,
+
+    /// Share of remittances (0.0 to 1.0) that carry provider level adjustments.
+    #[serde(default = "default_plb_rate")]
+    pub plb_rate: f64,
+
+    /// Whether remittances pay several claims.
+    #[serde(default)]
+    pub batch_remittances: bool,
+
+    /// Claims per remittance when batching.
+    #[serde(default)]
+    pub claims_per_remittance: ClaimsPerRemittance,
+
+    /// Delimiters of the X12 output.
+    #[serde(default)]
+    pub delimiters: Delimiters,
+
+    /// Date used as "today"; the system clock when unset.
+    #[serde(default)]
+    pub as_of: Option<NaiveDate>,
+
+    /// File persisting control numbers between runs.
+    #[serde(default)]
+    pub control_number_file: Option<PathBuf>,
+
+    /// Whether to validate the X12 output.
+    #[serde(default)]
+    pub self_check: bool,
+
+    /// File the matching 837 claims are written to.
+    #[serde(default)]
+    pub claims_output: Option<PathBuf>,
+
+    /// Share of claims per payer ID.
+    #[serde(default)]
+    pub payer_mix: PayerMix,
+
+    /// Payer profiles; loaded from the data directory when empty.
+    #[serde(default)]
+    pub payers: Vec<Payer>,
+
+    /// Denial reason weights for payers without their own mix.
+    #[serde(default)]
+    pub denial_mix: DenialMix,
 }
 
+/// The default PLB rate, for serde.
+fn default_plb_rate() -> f64 {
+    DEFAULT_PLB_RATE
+}
 
+/// Output format of generated data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OutputFormat {
-    
+    /// X12 835 text.
     X12,
 
-    
+    /// Compact JSON.
     Json,
 
-    
+    /// Indented JSON.
     JsonPretty,
 }
 
+/// Distribution of the number of claims per remittance.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ClaimsPerRemittance {
+    /// Always this many claims.
+    Fixed(usize),
+
+    /// Uniformly between `min` and `max`, inclusive.
+    Uniform {
+        /// Smallest count.
+        min: usize,
+        /// Largest count.
+        max: usize,
+    },
+
+    /// Poisson distributed around `mean`.
+    Poisson {
+        /// Mean count.
+        mean: f64,
+    },
+}
+
+impl Default for ClaimsPerRemittance {
+    fn default() -> Self {
+        ClaimsPerRemittance::Uniform { min: 1, max: 25 }
+    }
+}
+
+impl ClaimsPerRemittance {
+    /// Samples a claim count of at least one.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let count = match *self {
+            ClaimsPerRemittance::Fixed(count) => count,
+            ClaimsPerRemittance::Uniform { min, max } => rng.gen_range(min..=max.max(min)),
+            ClaimsPerRemittance::Poisson { mean } => Poisson::new(mean)
+                .map(|poisson| poisson.sample(rng) as usize)
+                .unwrap_or(1),
+        };
+        count.max(1)
+    }
+}
+
+impl FromStr for ClaimsPerRemittance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid claims-per-remittance distribution: {}", s);
+        if let Some(mean) = s.strip_prefix("poisson:") {
+            let mean: f64 = mean.parse().map_err(|_| invalid())?;
+            if mean <= 0.0 {
+                return Err(invalid());
+            }
+            return Ok(ClaimsPerRemittance::Poisson { mean });
+        }
+        if let Some((min, max)) = s.split_once('-') {
+            let min: usize = min.parse().map_err(|_| invalid())?;
+            let max: usize = max.parse().map_err(|_| invalid())?;
+            if min == 0 || max < min {
+                return Err(invalid());
+            }
+            return Ok(ClaimsPerRemittance::Uniform { min, max });
+        }
+        match s.parse() {
+            Ok(count) if count > 0 => Ok(ClaimsPerRemittance::Fixed(count)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -53,18 +180,34 @@ impl Default for Config {
             output_path: None,
             output_format: OutputFormat::X12,
             data_dir: PathBuf::from(DEFAULT_DATA_DIR),
+            plb_rate: DEFAULT_PLB_RATE,
+            batch_remittances: false,
+            claims_per_remittance: ClaimsPerRemittance::default(),
+            delimiters: Delimiters::default(),
+            as_of: None,
+            control_number_file: None,
+            self_check: false,
+            claims_output: None,
+            payer_mix: PayerMix::default(),
+            denial_mix: DenialMix::default(),
+            payers: Vec::new(),
         }
     }
 }
 
 impl Config {
-    
+    /// Builds a configuration from `generate` arguments.
     pub fn from_cli(args: &crate::cli::GenerateArgs) -> Self {
         let output_format = match args.format {
             crate::cli::OutputFormat::X12 => OutputFormat::X12,
             crate::cli::OutputFormat::Json => OutputFormat::Json,
             crate::cli::OutputFormat::JsonPretty => OutputFormat::JsonPretty,
         };
+        let line_ending = match args.line_ending {
+            crate::cli::LineEnding::None => LineEnding::None,
+            crate::cli::LineEnding::Lf => LineEnding::Lf,
+            crate::cli::LineEnding::CrLf => LineEnding::CrLf,
+        };
 
         Self {
             seed: args.seed,
@@ -73,16 +216,33 @@ impl Config {
             output_path: args.output.clone(),
             output_format,
             data_dir: args.data_dir.clone(),
+            plb_rate: args.plb_rate,
+            batch_remittances: args.batch,
+            claims_per_remittance: args.claims_per_remittance,
+            delimiters: Delimiters {
+                element: args.element_separator,
+                component: args.component_separator,
+                repetition: args.repetition_separator,
+                segment: args.segment_terminator,
+                line_ending,
+            },
+            as_of: args.as_of,
+            control_number_file: args.control_number_file.clone(),
+            self_check: args.self_check,
+            claims_output: args.claims_output.clone(),
+            payer_mix: args.payer_mix.clone().unwrap_or_default(),
+            denial_mix: args.denial_mix.clone().unwrap_or_default(),
+            payers: Vec::new(),
         }
     }
 
-    
+    /// Reads a configuration from a TOML file.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let content = fs::read_to_string(path).map_err(Error::Io)?;
         toml::from_str(&content).map_err(Error::TomlDe)
     }
 
-    
+    /// Writes the configuration to a TOML file.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
         let content = toml::to_string_pretty(self).map_err(Error::Toml)?;
         fs::write(path, content).map_err(Error::Io)
@@ -104,6 +264,21 @@ mod tests {
             output_path: Some("output.json".into()),
             output_format: OutputFormat::Json,
             data_dir: PathBuf::from(DEFAULT_DATA_DIR),
+            plb_rate: 0.25,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Uniform { min: 2, max: 8 },
+            delimiters: Delimiters {
+                element: '|',
+                line_ending: LineEnding::CrLf,
+                ..Default::default()
+            },
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            control_number_file: Some("control_numbers.toml".into()),
+            self_check: true,
+            claims_output: Some("claims_837.edi".into()),
+            payer_mix: "60101=3,47198=1".parse().unwrap(),
+            denial_mix: "eligibility=2,duplicate=1".parse().unwrap(),
+            payers: vec![Payer::default()],
         };
 
         let temp_file = NamedTempFile::new().unwrap();
@@ -124,5 +299,47 @@ mod tests {
         );
         assert_eq!(config.output_format, loaded.output_format);
         assert_eq!(config.data_dir, loaded.data_dir);
+        assert_eq!(config.plb_rate, loaded.plb_rate);
+        assert_eq!(config.batch_remittances, loaded.batch_remittances);
+        assert_eq!(config.claims_per_remittance, loaded.claims_per_remittance);
+        assert_eq!(config.delimiters, loaded.delimiters);
+        assert_eq!(config.as_of, loaded.as_of);
+        assert_eq!(config.control_number_file, loaded.control_number_file);
+        assert_eq!(config.self_check, loaded.self_check);
+        assert_eq!(config.claims_output, loaded.claims_output);
+        assert_eq!(config.payer_mix, loaded.payer_mix);
+        assert_eq!(config.denial_mix, loaded.denial_mix);
+        assert_eq!(config.payers[0].payer_id, loaded.payers[0].payer_id);
+    }
+
+    #[test]
+    fn test_config_without_plb_rate_uses_default() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(
+            temp_file.path(),
+            "claim_count = 10\nanomaly_rate = 0.0\noutput_format = \"X12\"\ndata_dir = \"data\"\n",
+        )
+        .unwrap();
+
+        let loaded = Config::from_file(temp_file.path()).unwrap();
+        assert_eq!(loaded.plb_rate, DEFAULT_PLB_RATE);
+        assert_eq!(loaded.delimiters, Delimiters::default());
+        assert!(loaded.as_of.is_none());
+    }
+
+    #[test]
+    fn test_claims_per_remittance_parsing() {
+        assert_eq!("5".parse(), Ok(ClaimsPerRemittance::Fixed(5)));
+        assert_eq!(
+            "2-10".parse(),
+            Ok(ClaimsPerRemittance::Uniform { min: 2, max: 10 })
+        );
+        assert_eq!(
+            "poisson:7.5".parse(),
+            Ok(ClaimsPerRemittance::Poisson { mean: 7.5 })
+        );
+        assert!("0".parse::<ClaimsPerRemittance>().is_err());
+        assert!("9-3".parse::<ClaimsPerRemittance>().is_err());
+        assert!("many".parse::<ClaimsPerRemittance>().is_err());
     }
 }
@@ -1,95 +1,2541 @@
+//! Validation of X12 835 files against the 005010X221A1 guide at WEDI SNIP levels 1-5.
 
-
-use crate::errors::Result;
-use std::collections::HashSet;
-use std::fs;
+use crate::adjustments::{CarcTable, RarcTable};
+use crate::cli::{ConformanceArgs, ReportFormat};
+use crate::errors::{Error, Result};
+use crate::money::Money;
+use crate::population::npi_check_digit;
+use crate::x12::envelope::X12Interchange;
+use crate::x12::parser::{RawSegment, SegmentReader};
+use crate::x12::schema::{self, DataType, ElementSpec, LoopId, SegmentSpec, SyntaxRule, Usage};
+use chrono::NaiveDate;
+use flate2::bufread::MultiGzDecoder;
+use log::info;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
+/// The implementation guide version expected in GS08 and ST03.
+const IMPLEMENTATION_GUIDE: &str = "005010X221A1";
 
-pub struct ConformanceResult {
-    
-    pub present: HashSet<String>,
-    
+/// CLP02 statuses that report a crossover to another payer.
+const CROSSOVER_STATUSES: &[&str] = &["19", "20", "21", "23"];
+
+/// State and territory codes accepted in N402.
+const US_STATES: &[&str] = &[
+    "AK", "AL", "AR", "AS", "AZ", "CA", "CO", "CT", "DC", "DE", "FL", "FM", "GA", "GU", "HI", "IA",
+    "ID", "IL", "IN", "KS", "KY", "LA", "MA", "MD", "ME", "MH", "MI", "MN", "MO", "MP", "MS", "MT",
+    "NC", "ND", "NE", "NH", "NJ", "NM", "NV", "NY", "OH", "OK", "OR", "PA", "PR", "PW", "RI", "SC",
+    "SD", "TN", "TX", "UT", "VA", "VI", "VT", "WA", "WI", "WV", "WY",
+];
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file does not conform; counts against the score.
+    Error,
+    /// Worth reviewing, but allowed by the implementation guide.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// WEDI SNIP validation level that a finding belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(into = "u8")]
+pub enum SnipLevel {
+    /// Level 1: EDI syntax, envelope counts and control numbers.
+    Integrity = 1,
+
+    /// Level 2: segment order, loops, element usage, lengths and codes.
+    Requirements = 2,
+
+    /// Level 3: service line, claim and payment balancing.
+    Balancing = 3,
+
+    /// Level 4: inter-segment situational rules.
+    Situational = 4,
+
+    /// Level 5: external code sets such as CARCs, RARCs and NPIs.
+    CodeSets = 5,
+}
+
+impl SnipLevel {
+    /// Every level in ascending order.
+    pub const ALL: [SnipLevel; 5] = [
+        SnipLevel::Integrity,
+        SnipLevel::Requirements,
+        SnipLevel::Balancing,
+        SnipLevel::Situational,
+        SnipLevel::CodeSets,
+    ];
+
+    /// The SNIP level number, 1 to 5.
+    pub fn number(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Short description of what the level checks.
+    pub fn description(&self) -> &'static str {
+        match self {
+            SnipLevel::Integrity => "EDI syntax integrity",
+            SnipLevel::Requirements => "implementation guide requirements",
+            SnipLevel::Balancing => "balancing",
+            SnipLevel::Situational => "situational rules",
+            SnipLevel::CodeSets => "external code sets",
+        }
+    }
+}
+
+impl From<SnipLevel> for u8 {
+    fn from(level: SnipLevel) -> u8 {
+        level.number()
+    }
+}
+
+impl fmt::Display for SnipLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SNIP {}", self.number())
+    }
+}
+
+/// A single conformance problem found in an input file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Whether the problem is an error or a warning.
+    pub severity: Severity,
+    /// SNIP level of the rule that was violated.
+    pub level: SnipLevel,
+
+    /// 1-based position of the segment in the interchange.
+    pub segment: usize,
+
+    /// 1-based line number the segment starts on.
+    pub line: usize,
+
+    /// Byte offset of the segment, when known.
+    pub offset: Option<usize>,
+
+    /// ST02 control number of the enclosing transaction set.
+    pub transaction: Option<String>,
+    /// Identifier of the loop containing the offending segment, e.g. "2100".
+    pub loop_id: Option<&'static str>,
+    /// Identifier of the offending segment.
+    pub segment_id: Option<String>,
+    /// 1-based position of the offending element.
+    pub element: Option<usize>,
+    /// 1-based position of the offending component within the element.
+    pub component: Option<usize>,
+
+    /// TA1 note or 999 syntax error code used in acknowledgments, when one applies.
+    pub code: Option<&'static str>,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl Finding {
+    /// Creates a finding located at `raw`.
+    pub fn new(
+        severity: Severity,
+        level: SnipLevel,
+        raw: &RawSegment,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            level,
+            segment: raw.position,
+            line: raw.line,
+            offset: Some(raw.offset),
+            transaction: None,
+            loop_id: None,
+            segment_id: Some(raw.id.clone()),
+            element: None,
+            component: None,
+            code: None,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an error finding located at `raw`.
+    pub fn error(level: SnipLevel, raw: &RawSegment, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, level, raw, message)
+    }
+
+    /// Creates a warning finding located at `raw`.
+    pub fn warning(level: SnipLevel, raw: &RawSegment, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, level, raw, message)
+    }
+
+    /// Points the finding at an element of its segment.
+    pub fn at(mut self, element: usize) -> Self {
+        self.element = Some(element);
+        self
+    }
+
+    /// Points the finding at a component of an element; component 0 means the whole element.
+    pub fn at_component(mut self, element: usize, component: usize) -> Self {
+        self.element = Some(element);
+        self.component = (component > 0).then_some(component);
+        self
+    }
+
+    /// Places the finding in `id`.
+    pub fn in_loop(mut self, id: LoopId) -> Self {
+        self.loop_id = id.code();
+        self
+    }
+
+    /// Attaches an acknowledgment error code.
+    pub fn code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Reference to the offending segment, element and component, e.g. `CLP04`.
+    pub fn location(&self) -> String {
+        let id = self.segment_id.as_deref().unwrap_or_default();
+        match (self.element, self.component) {
+            (Some(element), Some(component)) => element_ref(id, element, component),
+            (Some(element), None) => element_ref(id, element, 0),
+            _ => id.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] line {}, segment {}",
+            self.severity, self.level, self.line, self.segment
+        )?;
+        if self.segment_id.is_some() {
+            write!(f, " ({})", self.location())?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Counts and segment presence collected while checking a file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConformanceSummary {
+    /// Identifiers of all segments present in the input.
+    pub present: BTreeSet<String>,
+
+    /// Number of required segment identifiers that were found.
     pub found: usize,
-    
+
+    /// Number of required segment identifiers.
     pub total: usize,
+
+    /// Number of segments read.
+    pub segments: usize,
+
+    #[serde(skip)]
+    counts: BTreeMap<(Severity, SnipLevel), usize>,
+
+    #[serde(skip)]
+    error_segments: HashSet<usize>,
 }
 
+impl ConformanceSummary {
+    /// Adds a finding to the counts.
+    pub fn record(&mut self, finding: &Finding) {
+        if finding.severity == Severity::Error {
+            self.error_segments.insert(finding.segment);
+        }
+        *self
+            .counts
+            .entry((finding.severity, finding.level))
+            .or_default() += 1;
+    }
+
+    /// Number of findings with `severity`, optionally limited to one level.
+    pub fn count(&self, severity: Severity, level: Option<SnipLevel>) -> usize {
+        self.counts
+            .iter()
+            .filter(|((s, l), _)| *s == severity && level.map_or(true, |level| *l == level))
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Total number of error findings.
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error, None)
+    }
+
+    /// Total number of warning findings.
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning, None)
+    }
+
+    /// Percentage of segments without an error finding.
+    pub fn score(&self) -> f64 {
+        if self.segments == 0 {
+            return if self.errors() == 0 { 100.0 } else { 0.0 };
+        }
+        (self.clean_segments() as f64) / (self.segments as f64) * 100.0
+    }
+
+    /// Number of segments without an error finding.
+    pub fn clean_segments(&self) -> usize {
+        self.segments.saturating_sub(self.error_segments.len())
+    }
+
+    /// Required segment identifiers that were not found.
+    pub fn missing(&self) -> Vec<&'static str> {
+        REQUIRED_SEGMENTS
+            .iter()
+            .copied()
+            .filter(|id| !self.present.contains(*id))
+            .collect()
+    }
 
+    /// Describes each threshold the summary fails to meet.
+    pub fn threshold_failures(
+        &self,
+        fail_under: Option<f64>,
+        max_errors: Option<usize>,
+    ) -> Vec<String> {
+        let mut failures = Vec::new();
+        if let Some(minimum) = fail_under {
+            if self.score() < minimum {
+                failures.push(format!(
+                    "conformance score {:.1}% is below the required {:.1}%",
+                    self.score(),
+                    minimum
+                ));
+            }
+        }
+        if let Some(limit) = max_errors {
+            if self.errors() > limit {
+                failures.push(format!(
+                    "{} errors exceed the maximum of {}",
+                    self.errors(),
+                    limit
+                ));
+            }
+        }
+        failures
+    }
+}
+
+/// The summary of a conformance check together with every finding.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceResult {
+    /// Counts and segment presence.
+    #[serde(flatten)]
+    pub summary: ConformanceSummary,
+
+    /// Findings ordered by segment position.
+    pub findings: Vec<Finding>,
+}
+
+/// Bytes read between progress callbacks.
+const PROGRESS_INTERVAL: usize = 64 * 1024 * 1024;
+
+/// Capacity of the buffered input reader.
+const INPUT_BUFFER_SIZE: usize = 256 * 1024;
+
+/// The leading bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// How far a conformance check has read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes of (decompressed) input read.
+    pub bytes: usize,
+
+    /// Segments read.
+    pub segments: usize,
+}
+
+/// Segments whose presence the summary reports.
 const REQUIRED_SEGMENTS: &[&str] = &[
     "ISA", "GS", "ST", "BPR", "TRN", "DTM", "N1", "CLP", "SVC", "SE", "GE", "IEA",
 ];
 
+/// Checks an X12 835 held in memory.
+pub fn compute_conformance(content: &str, data_path: &Path) -> ConformanceResult {
+    compute_conformance_from_reader(content.as_bytes(), data_path)
+        .expect("in-memory reads cannot fail")
+}
 
-pub fn compute_conformance(content: &str) -> ConformanceResult {
-    
-    let mut present = HashSet::new();
-    for seg in content.split('~') {
-        let seg = seg.trim();
-        if seg.is_empty() {
-            continue;
+/// Checks an X12 835 read from `reader`.
+pub fn compute_conformance_from_reader<R: Read>(
+    reader: R,
+    data_path: &Path,
+) -> Result<ConformanceResult> {
+    compute_conformance_with_progress(BufReader::new(reader), data_path, |_| {})
+}
+
+/// Checks an interchange by validating its X12 text.
+pub fn compute_conformance_for_interchange(
+    interchange: &X12Interchange,
+    data_path: &Path,
+) -> ConformanceResult {
+    compute_conformance(&interchange.to_string(), data_path)
+}
+
+/// Checks an X12 835 read from `reader`, reporting progress as it goes.
+pub fn compute_conformance_with_progress<R: BufRead>(
+    reader: R,
+    data_path: &Path,
+    progress: impl FnMut(Progress),
+) -> Result<ConformanceResult> {
+    let mut findings = Vec::new();
+    let summary = check_conformance(reader, data_path, |f| findings.push(f), progress)?;
+    findings.sort_by_key(|f| (f.segment, f.level, f.element, f.component));
+    Ok(ConformanceResult { summary, findings })
+}
+
+/// Checks an X12 835 read from `reader`, passing each finding to `on_finding` as soon
+/// as it is found instead of collecting them.
+pub fn check_conformance<R: BufRead>(
+    reader: R,
+    data_path: &Path,
+    mut on_finding: impl FnMut(Finding),
+    mut progress: impl FnMut(Progress),
+) -> Result<ConformanceSummary> {
+    let mut summary = ConformanceSummary::default();
+    let mut sink = |finding: Finding| {
+        summary.record(&finding);
+        on_finding(finding);
+    };
+    let mut segments = SegmentReader::new(reader)?;
+    let mut validator = Validator::new(data_path, &mut sink);
+    let detected = match segments.delimiters() {
+        Ok(_) => true,
+        Err(e) => {
+            validator.push(Finding {
+                severity: Severity::Error,
+                level: SnipLevel::Integrity,
+                segment: e.segment,
+                line: 1,
+                offset: e.offset,
+                transaction: None,
+                loop_id: None,
+                segment_id: e.segment_id.clone(),
+                element: e.element,
+                component: None,
+                code: None,
+                message: e.message.clone(),
+            });
+            false
         }
-        if let Some(id) = seg.split('*').next() {
-            present.insert(id.to_string());
+    };
+
+    let mut present = BTreeSet::new();
+    let mut count = 0;
+    let mut next_report = PROGRESS_INTERVAL;
+    while let Some(raw) = segments.next() {
+        let raw = raw?;
+        count += 1;
+        if !present.contains(&raw.id) {
+            present.insert(raw.id.clone());
         }
+        if detected {
+            validator.feed(raw);
+        }
+        if segments.bytes_read() >= next_report {
+            next_report += PROGRESS_INTERVAL;
+            progress(Progress {
+                bytes: segments.bytes_read(),
+                segments: count,
+            });
+        }
+    }
+    if detected {
+        validator.finish();
     }
-    let total = REQUIRED_SEGMENTS.len();
-    let found = REQUIRED_SEGMENTS
+
+    summary.total = REQUIRED_SEGMENTS.len();
+    summary.found = REQUIRED_SEGMENTS
         .iter()
-        .filter(|id| present.contains(&id.to_string()))
+        .filter(|id| present.contains(**id))
         .count();
-    ConformanceResult {
-        present,
-        found,
-        total,
+    summary.present = present;
+    summary.segments = count;
+    Ok(summary)
+}
+
+/// Returns the findings for an X12 835 held in memory.
+pub fn validate(content: &str, data_path: &Path) -> Vec<Finding> {
+    compute_conformance(content, data_path).findings
+}
+
+/// The reference for an element or component, e.g. `CLP01` or `SVC01-2`.
+fn element_ref(id: &str, element: usize, component: usize) -> String {
+    if component > 0 {
+        format!("{}{:02}-{}", id, element, component)
+    } else {
+        format!("{}{:02}", id, element)
+    }
+}
+
+/// The value of the element at `position`, or an empty string.
+fn value(raw: &RawSegment, position: usize) -> &str {
+    raw.elements.get(position - 1).map_or("", |e| e.value())
+}
+
+/// The value of a component of the element at `position`, or an empty string.
+fn component(raw: &RawSegment, position: usize, component: usize) -> &str {
+    raw.elements
+        .get(position - 1)
+        .and_then(|e| e.components().get(component - 1))
+        .map_or("", String::as_str)
+}
+
+/// Whether the element at `position` has a value.
+fn is_present(raw: &RawSegment, position: usize) -> bool {
+    raw.elements
+        .get(position - 1)
+        .is_some_and(|e| !e.is_empty())
+}
+
+/// Parses a monetary amount.
+fn money(value: &str) -> Option<Money> {
+    value.parse().ok()
+}
+
+/// The message for a charge less payment that does not match the adjustments.
+fn imbalance(what: &str, charge: Money, paid: Money, adjustments: Money) -> String {
+    format!(
+        "{} does not balance: charge {} less payment {} is {} but adjustments total {}",
+        what,
+        charge,
+        paid,
+        charge - paid,
+        adjustments
+    )
+}
+
+/// Whether `id` looks like a segment identifier.
+fn is_valid_segment_id(id: &str) -> bool {
+    let mut chars = id.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_uppercase())
+        && (2..=3).contains(&id.len())
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Whether `value` is a decimal number.
+fn is_decimal(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+}
+
+/// Whether `value` is a CCYYMMDD or YYMMDD date.
+fn is_valid_date(value: &str) -> bool {
+    if !value.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    match value.len() {
+        8 => NaiveDate::parse_from_str(value, "%Y%m%d").is_ok(),
+        6 => NaiveDate::parse_from_str(value, "%y%m%d").is_ok(),
+        _ => false,
+    }
+}
+
+/// Whether `value` is an HHMM, HHMMSS or HHMMSSD(D) time.
+fn is_valid_time(value: &str) -> bool {
+    if !value.chars().all(|c| c.is_ascii_digit()) || ![4, 6, 7, 8].contains(&value.len()) {
+        return false;
+    }
+    let part = |range: std::ops::Range<usize>| value[range].parse::<u32>().unwrap_or(99);
+    part(0..2) < 24 && part(2..4) < 60 && (value.len() < 6 || part(4..6) < 60)
+}
+
+/// Whether `npi` is ten digits with a valid Luhn check digit.
+pub fn is_valid_npi(npi: &str) -> bool {
+    if npi.len() != 10 || !npi.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    npi[9..].parse() == Ok(npi_check_digit(&npi[..9]))
+}
+
+/// A description of the expected format when `code` is malformed for `qualifier`.
+fn procedure_code_format(qualifier: &str, code: &str) -> Option<&'static str> {
+    let digits = |n: usize| code.len() == n && code.chars().all(|c| c.is_ascii_digit());
+    let valid = match qualifier {
+        "HC" => {
+            code.len() == 5
+                && code
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        }
+        "NU" => digits(4),
+        "N4" => digits(11),
+        "AD" => {
+            code.len() == 5
+                && code.starts_with('D')
+                && code[1..].chars().all(|c| c.is_ascii_digit())
+        }
+        _ => true,
+    };
+    if valid {
+        return None;
+    }
+    Some(match qualifier {
+        "HC" => "a five character HCPCS/CPT code",
+        "NU" => "a four digit revenue code",
+        "N4" => "an eleven digit NDC",
+        _ => "a CDT code (D followed by four digits)",
+    })
+}
+
+/// An open loop and the segments used in it so far.
+struct LoopState {
+    id: LoopId,
+    start: RawSegment,
+    last: Option<&'static SegmentSpec>,
+    uses: Vec<(&'static SegmentSpec, usize)>,
+    children: Vec<(LoopId, usize)>,
+    last_child: Option<LoopId>,
+}
+
+impl LoopState {
+    fn new(id: LoopId, start: &RawSegment) -> Self {
+        Self {
+            id,
+            start: start.clone(),
+            last: None,
+            uses: Vec::new(),
+            children: Vec::new(),
+            last_child: None,
+        }
+    }
+
+    /// Counts a use of `spec`, returning the uses so far.
+    fn record(&mut self, spec: &'static SegmentSpec) -> usize {
+        match self.uses.iter_mut().find(|(s, _)| std::ptr::eq(*s, spec)) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.uses.push((spec, 1));
+                1
+            }
+        }
+    }
+
+    /// Counts an occurrence of child loop `id`, returning the occurrences so far.
+    fn add_child(&mut self, id: LoopId) -> usize {
+        self.last_child = Some(id);
+        match self.children.iter_mut().find(|(child, _)| *child == id) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                self.children.push((id, 1));
+                1
+            }
+        }
+    }
+
+    /// Whether child loop `id` has occurred in the loop.
+    fn has_child(&self, id: LoopId) -> bool {
+        self.children.iter().any(|(child, _)| *child == id)
+    }
+
+    /// Whether `spec` has been used in the loop.
+    fn used(&self, spec: &SegmentSpec) -> bool {
+        self.uses.iter().any(|(s, _)| std::ptr::eq(*s, spec))
+    }
+}
+
+/// Running totals for the current claim.
+struct ClaimBalance {
+    clp: RawSegment,
+    adjustments: Money,
+    line: Option<(RawSegment, Money)>,
+}
+
+/// Running totals for the balancing checks of a transaction.
+struct Balance {
+    bpr: Option<RawSegment>,
+    claim: Option<ClaimBalance>,
+    total_paid: Option<Money>,
+    provider_adjustments: Money,
+}
+
+/// What the situational checks have seen of the current claim.
+struct ClaimSituation {
+    clp: RawSegment,
+    crossover: Option<RawSegment>,
+    claim_dates: bool,
+    patient_responsibility: usize,
+    line: Option<(RawSegment, Vec<String>)>,
+}
+
+/// What the situational checks have seen of a transaction.
+#[derive(Default)]
+struct Situation {
+    bpr: Option<RawSegment>,
+    trn: Option<RawSegment>,
+    has_web_contact: bool,
+    claim: Option<ClaimSituation>,
+}
+
+/// An open transaction set.
+struct Transaction {
+    st: RawSegment,
+    segments: usize,
+    loops: Vec<LoopState>,
+    balance: Balance,
+    situation: Situation,
+}
+
+impl Transaction {
+    fn new(st: &RawSegment) -> Self {
+        Self {
+            st: st.clone(),
+            segments: 0,
+            loops: vec![LoopState::new(LoopId::Header, st)],
+            balance: Balance {
+                bpr: None,
+                claim: None,
+                total_paid: Some(Money::ZERO),
+                provider_adjustments: Money::ZERO,
+            },
+            situation: Situation::default(),
+        }
+    }
+}
+
+/// An open interchange or functional group.
+struct Envelope {
+    header: RawSegment,
+    children: usize,
+    control_numbers: HashSet<String>,
+}
+
+impl Envelope {
+    fn new(header: RawSegment) -> Self {
+        Self {
+            header,
+            children: 0,
+            control_numbers: HashSet::new(),
+        }
     }
 }
 
+/// Checks a stream of segments, passing findings to a sink.
+struct Validator<'s> {
+    carc: CarcTable,
+    rarc: RarcTable,
+    sink: &'s mut dyn FnMut(Finding),
+    interchange: Option<Envelope>,
+    group: Option<Envelope>,
+    transaction: Option<Transaction>,
+    control_number: Option<String>,
+    segment_loop: Option<(usize, LoopId)>,
+}
+
+impl<'s> Validator<'s> {
+    fn new(data_path: &Path, sink: &'s mut dyn FnMut(Finding)) -> Self {
+        Self {
+            carc: CarcTable::load(data_path),
+            rarc: RarcTable::load(data_path),
+            sink,
+            interchange: None,
+            group: None,
+            transaction: None,
+            control_number: None,
+            segment_loop: None,
+        }
+    }
+
+    /// Reports `finding` against the current transaction.
+    fn push(&mut self, mut finding: Finding) {
+        finding.transaction = self.control_number.clone();
+        if let Some((position, id)) = self.segment_loop {
+            if finding.loop_id.is_none() && finding.segment == position {
+                finding = finding.in_loop(id);
+            }
+        }
+        (self.sink)(finding);
+    }
+
+    /// Reports an integrity error at `raw`.
+    fn integrity(&mut self, raw: &RawSegment, element: Option<usize>, message: impl Into<String>) {
+        let finding = Finding::error(SnipLevel::Integrity, raw, message);
+        self.push(match element {
+            Some(element) => finding.at(element),
+            None => finding,
+        });
+    }
+
+    /// Checks one segment.
+    fn feed(&mut self, raw: RawSegment) {
+        if !is_valid_segment_id(&raw.id) {
+            let message = format!("'{}' is not a valid segment identifier", raw.id);
+            self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("1"));
+            self.step(&raw);
+            return;
+        }
+        let id = raw.id.as_str();
+        if matches!(id, "ISA" | "GS" | "ST" | "GE" | "IEA") && self.transaction.is_some() {
+            self.unterminated_transaction();
+        }
+        if raw.id != "ISA" && raw.elements.last().is_some_and(|e| e.is_empty()) {
+            let message = "segment ends with a trailing element separator";
+            self.push(Finding::warning(SnipLevel::Integrity, &raw, message));
+        }
+        if let Some(spec) = schema::envelope_spec(id) {
+            self.check_elements(&raw, spec);
+        }
+
+        match id {
+            "ISA" => {
+                let group = self.group.take();
+                self.unclosed(group);
+                let interchange = self.interchange.take();
+                self.unclosed(interchange);
+                if raw.elements.len() != 16 {
+                    let message = format!("ISA has {} elements instead of 16", raw.elements.len());
+                    self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("022"));
+                }
+                self.interchange = Some(Envelope::new(raw));
+            }
+            "GS" => {
+                let group = self.group.take();
+                self.unclosed(group);
+                let mut interchange = self.interchange.take();
+                self.enter(interchange.as_mut(), &raw, 6, "interchange");
+                self.interchange = interchange;
+                if is_present(&raw, 8) && value(&raw, 8) != IMPLEMENTATION_GUIDE {
+                    let message = format!(
+                        "version '{}' is not {}",
+                        value(&raw, 8),
+                        IMPLEMENTATION_GUIDE
+                    );
+                    self.push(Finding::error(SnipLevel::Requirements, &raw, message).at(8));
+                }
+                self.group = Some(Envelope::new(raw));
+            }
+            "ST" => {
+                self.transaction = Some(Transaction::new(&raw));
+                self.control_number = Some(value(&raw, 2).to_string());
+                let mut group = self.group.take();
+                self.enter(group.as_mut(), &raw, 2, "functional group");
+                self.group = group;
+                self.step(&raw);
+            }
+            "SE" => match self.transaction {
+                Some(_) => {
+                    self.step(&raw);
+                    if let Some(transaction) = self.transaction.take() {
+                        self.expect_count(&raw, transaction.segments, "the number of segments");
+                        self.expect_control(&raw, &transaction.st, 2);
+                        self.end_transaction(transaction);
+                    }
+                }
+                None => {
+                    let message = "SE segment has no matching ST segment";
+                    self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("024"));
+                }
+            },
+            "GE" => match self.group.take() {
+                Some(open) => {
+                    self.expect_count(&raw, open.children, "the number of transaction sets");
+                    self.expect_control(&raw, &open.header, 6);
+                }
+                None => {
+                    let message = "GE segment has no matching GS segment";
+                    self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("024"));
+                }
+            },
+            "IEA" => {
+                let group = self.group.take();
+                self.unclosed(group);
+                match self.interchange.take() {
+                    Some(open) => {
+                        self.expect_count(&raw, open.children, "the number of functional groups");
+                        self.expect_control(&raw, &open.header, 13);
+                    }
+                    None => {
+                        let message = "IEA segment has no matching ISA segment";
+                        self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("022"));
+                    }
+                }
+            }
+            _ => match self.transaction {
+                Some(_) => self.step(&raw),
+                None => {
+                    let message = format!("{} segment is outside of a transaction set", raw.id);
+                    self.push(Finding::error(SnipLevel::Integrity, &raw, message).code("024"));
+                }
+            },
+        }
+    }
+
+    /// Reports envelopes left open at the end of the input.
+    fn finish(&mut self) {
+        if self.transaction.is_some() {
+            self.unterminated_transaction();
+        }
+        let group = self.group.take();
+        self.unclosed(group);
+        let interchange = self.interchange.take();
+        self.unclosed(interchange);
+    }
+
+    /// Runs the transaction-level checks for `raw`.
+    fn step(&mut self, raw: &RawSegment) {
+        let Some(mut transaction) = self.transaction.take() else {
+            return;
+        };
+        transaction.segments += 1;
+        if is_valid_segment_id(&raw.id) {
+            self.check_loops(&mut transaction.loops, raw);
+        }
+        self.check_balancing(&mut transaction.balance, raw);
+        self.check_situational(&mut transaction.situation, raw);
+        self.check_code_sets(raw);
+        self.transaction = Some(transaction);
+    }
+
+    /// Closes the loops of a transaction and settles its checks.
+    fn end_transaction(&mut self, mut transaction: Transaction) {
+        while let Some(state) = transaction.loops.pop() {
+            self.close_loop(&state);
+        }
+        self.settle_balancing(&mut transaction.balance);
+        self.settle_situational(&mut transaction.situation);
+        self.control_number = None;
+    }
+
+    /// Counts a child envelope in `parent` and checks its control number is unique.
+    fn enter(
+        &mut self,
+        parent: Option<&mut Envelope>,
+        raw: &RawSegment,
+        control: usize,
+        name: &str,
+    ) {
+        let Some(parent) = parent else {
+            let message = format!("{} segment is outside of any {}", raw.id, name);
+            return self.push(Finding::error(SnipLevel::Integrity, raw, message).code("024"));
+        };
+        parent.children += 1;
+        if !parent
+            .control_numbers
+            .insert(value(raw, control).to_string())
+        {
+            let message = format!(
+                "control number '{}' is not unique within the {}",
+                value(raw, control),
+                name
+            );
+            self.integrity(raw, Some(control), message);
+        }
+    }
+
+    /// Checks a trailer's count against `count`.
+    fn expect_count(&mut self, trailer: &RawSegment, count: usize, description: &str) {
+        if value(trailer, 1).parse::<usize>().ok() != Some(count) {
+            let message = format!(
+                "{} is '{}' but {} is {}",
+                element_ref(&trailer.id, 1, 0),
+                value(trailer, 1),
+                description,
+                count
+            );
+            self.integrity(trailer, Some(1), message);
+        }
+    }
+
+    /// Checks a trailer's control number against its header.
+    fn expect_control(&mut self, trailer: &RawSegment, header: &RawSegment, control: usize) {
+        if value(trailer, 2) != value(header, control) {
+            let message = format!(
+                "{} '{}' does not match {} '{}'",
+                element_ref(&trailer.id, 2, 0),
+                value(trailer, 2),
+                element_ref(&header.id, control, 0),
+                value(header, control)
+            );
+            self.integrity(trailer, Some(2), message);
+        }
+    }
+
+    /// Reports an interchange or group missing its trailer.
+    fn unclosed(&mut self, envelope: Option<Envelope>) {
+        if let Some(open) = envelope {
+            let (trailer, control, code) = if open.header.id == "ISA" {
+                ("IEA", 13, "023")
+            } else {
+                ("GE", 6, "3")
+            };
+            let message = format!(
+                "{} {} is missing its {} trailer",
+                open.header.id,
+                value(&open.header, control),
+                trailer
+            );
+            self.push(Finding::error(SnipLevel::Integrity, &open.header, message).code(code));
+        }
+    }
+
+    /// Reports a transaction missing its SE trailer.
+    fn unterminated_transaction(&mut self) {
+        let Some(transaction) = self.transaction.take() else {
+            return;
+        };
+        let message = format!("ST {} is missing its SE trailer", value(&transaction.st, 2));
+        self.push(Finding::error(SnipLevel::Integrity, &transaction.st, message).code("2"));
+        self.end_transaction(transaction);
+    }
 
-pub fn run(input_path: &Path) -> Result<()> {
-    let content = fs::read_to_string(input_path)?;
-    let result = compute_conformance(&content);
-    
-    println!("Segment presence:");
+    /// Opens and closes loops as `raw` requires.
+    fn check_loops(&mut self, stack: &mut Vec<LoopState>, raw: &RawSegment) {
+        let in_summary = stack.last().is_some_and(|s| s.id == LoopId::Summary);
+        let target = match raw.id.as_str() {
+            "N1" if value(raw, 1) == "PR" => Some(LoopId::Payer),
+            "N1" if value(raw, 1) == "PE" => Some(LoopId::Payee),
+            "LX" => Some(LoopId::HeaderNumber),
+            "CLP" => Some(LoopId::ClaimPayment),
+            "SVC" => Some(LoopId::ServicePayment),
+            "PLB" | "SE" if !in_summary => Some(LoopId::Summary),
+            _ => None,
+        };
+        self.segment_loop = target
+            .or_else(|| stack.last().map(|s| s.id))
+            .map(|id| (raw.position, id));
+        if let Some(target) = target {
+            self.open_loop(stack, target, raw);
+        }
+        if let Some(state) = stack.last_mut() {
+            match Self::check_position(state, raw) {
+                Ok(spec) => self.check_elements(raw, spec),
+                Err(finding) => self.push(*finding),
+            }
+        }
+    }
+
+    /// Opens the `target` loop, closing loops that cannot contain it.
+    fn open_loop(&mut self, stack: &mut Vec<LoopState>, target: LoopId, raw: &RawSegment) {
+        let parent = match target {
+            LoopId::ServicePayment => LoopId::ClaimPayment,
+            LoopId::ClaimPayment => LoopId::HeaderNumber,
+            _ => LoopId::Header,
+        };
+        while stack.len() > 1 && stack.last().map(|s| s.id) != Some(parent) {
+            if let Some(state) = stack.pop() {
+                self.close_loop(&state);
+            }
+        }
+
+        let Some(state) = stack.last_mut() else {
+            return;
+        };
+        if state.id != parent {
+            self.push(
+                Finding::error(
+                    SnipLevel::Requirements,
+                    raw,
+                    format!(
+                        "{} segment starts {} but is not inside {}",
+                        raw.id,
+                        target.name(),
+                        parent.name()
+                    ),
+                )
+                .code("2"),
+            );
+        }
+        if let Some(previous) = state.last_child {
+            if target < previous {
+                self.push(
+                    Finding::error(
+                        SnipLevel::Integrity,
+                        raw,
+                        format!("{} cannot follow {}", target.name(), previous.name()),
+                    )
+                    .code("7"),
+                );
+            }
+        }
+        let count = state.add_child(target);
+        if let Some(max) = target.max_repeat() {
+            if count == max + 1 {
+                self.push(
+                    Finding::error(
+                        SnipLevel::Requirements,
+                        raw,
+                        format!("{} exceeds its maximum repeat of {}", target.name(), max),
+                    )
+                    .code("4"),
+                );
+            }
+        }
+        stack.push(LoopState::new(target, raw));
+    }
+
+    /// Reports required segments missing from a closed loop.
+    fn close_loop(&mut self, state: &LoopState) {
+        for spec in schema::loop_specs(state.id) {
+            if spec.usage == Usage::Required && spec.id != "SE" && !state.used(spec) {
+                self.push(
+                    Finding::error(
+                        SnipLevel::Requirements,
+                        &state.start,
+                        format!(
+                            "{} is missing required segment {} ({})",
+                            state.id.name(),
+                            spec.id,
+                            spec.name
+                        ),
+                    )
+                    .in_loop(state.id)
+                    .code("3"),
+                );
+            }
+        }
+        let required_children: &[LoopId] = match state.id {
+            LoopId::Header => &[LoopId::Payer, LoopId::Payee],
+            LoopId::HeaderNumber => &[LoopId::ClaimPayment],
+            _ => &[],
+        };
+        for child in required_children {
+            if !state.has_child(*child) {
+                self.push(
+                    Finding::error(
+                        SnipLevel::Requirements,
+                        &state.start,
+                        format!("{} is missing required {}", state.id.name(), child.name()),
+                    )
+                    .in_loop(state.id)
+                    .code("I7"),
+                );
+            }
+        }
+    }
+
+    /// Finds the spec for `raw` at its position in the loop.
+    fn check_position(
+        state: &mut LoopState,
+        raw: &RawSegment,
+    ) -> std::result::Result<&'static SegmentSpec, Box<Finding>> {
+        let specs: Vec<&'static SegmentSpec> = schema::segment_specs(state.id, &raw.id).collect();
+        if specs.is_empty() {
+            let known = schema::SEGMENTS.iter().any(|spec| spec.id == raw.id);
+            let (code, message) = if known {
+                let message = format!("{} segment is not expected in {}", raw.id, state.id.name());
+                ("2", message)
+            } else {
+                ("1", format!("{} is not a segment used in the 835", raw.id))
+            };
+            let finding = Finding::error(SnipLevel::Integrity, raw, message).code(code);
+            return Err(Box::new(finding));
+        }
+
+        let qualifier = value(raw, 1);
+        let spec = specs
+            .iter()
+            .find(|spec| spec.qualifiers().contains(&qualifier))
+            .or_else(|| specs.iter().find(|spec| spec.qualifiers().is_empty()))
+            .or_else(|| (specs.len() == 1).then(|| &specs[0]))
+            .copied();
+        let Some(spec) = spec else {
+            let message = format!(
+                "qualifier '{}' is not valid for {} in {}",
+                qualifier,
+                raw.id,
+                state.id.name()
+            );
+            let finding = Finding::error(SnipLevel::Requirements, raw, message)
+                .at(1)
+                .code("7");
+            return Err(Box::new(finding));
+        };
+
+        if let Some(last) = state.last {
+            if spec.position < last.position {
+                return Err(Box::new(
+                    Finding::error(
+                        SnipLevel::Integrity,
+                        raw,
+                        format!(
+                            "{} segment is out of order in {}: it must precede {}",
+                            raw.id,
+                            state.id.name(),
+                            last.id
+                        ),
+                    )
+                    .code("7"),
+                ));
+            }
+        }
+        state.last = Some(spec);
+
+        let count = state.record(spec);
+        if let Some(max) = spec.max_use {
+            if count == max + 1 {
+                return Err(Box::new(
+                    Finding::error(
+                        SnipLevel::Requirements,
+                        raw,
+                        format!(
+                            "{} ({}) exceeds its maximum use of {} in {}",
+                            spec.name,
+                            spec.id,
+                            max,
+                            state.id.name()
+                        ),
+                    )
+                    .code("5"),
+                ));
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Checks the elements of `raw` against `spec`.
+    fn check_elements(&mut self, raw: &RawSegment, spec: &SegmentSpec) {
+        for (index, element) in raw.elements.iter().enumerate() {
+            if spec.element(index + 1).is_none() && !element.is_empty() {
+                self.push(
+                    Finding::warning(
+                        SnipLevel::Requirements,
+                        raw,
+                        format!(
+                            "{} is not used in this implementation",
+                            element_ref(&raw.id, index + 1, 0)
+                        ),
+                    )
+                    .at(index + 1)
+                    .code("I10"),
+                );
+            }
+        }
+
+        for el in spec.elements.iter().filter(|e| e.component == 0) {
+            let element = match raw.elements.get(el.position - 1) {
+                Some(element) if !element.is_empty() => element,
+                _ => {
+                    if el.usage == Usage::Required {
+                        self.push(
+                            Finding::error(
+                                SnipLevel::Requirements,
+                                raw,
+                                format!(
+                                    "required element {} is missing",
+                                    element_ref(&raw.id, el.position, 0)
+                                ),
+                            )
+                            .at(el.position)
+                            .code("1"),
+                        );
+                    }
+                    continue;
+                }
+            };
+
+            if el.data_type != DataType::Composite {
+                if element.components().len() > 1 {
+                    self.push(
+                        Finding::error(
+                            SnipLevel::Integrity,
+                            raw,
+                            "element contains a component separator but is not a composite",
+                        )
+                        .at(el.position)
+                        .code("6"),
+                    );
+                } else {
+                    self.check_value(raw, el, element.value());
+                }
+                continue;
+            }
+
+            let components = element.components();
+            let mut defined = 0;
+            for c in spec.components(el.position) {
+                defined = defined.max(c.component);
+                match components.get(c.component - 1).map(String::as_str) {
+                    Some(value) if !value.is_empty() => self.check_value(raw, c, value),
+                    _ if c.usage == Usage::Required => self.push(
+                        Finding::error(
+                            SnipLevel::Requirements,
+                            raw,
+                            format!(
+                                "required component {} is missing",
+                                element_ref(&raw.id, el.position, c.component)
+                            ),
+                        )
+                        .at_component(el.position, c.component)
+                        .code("1"),
+                    ),
+                    _ => {}
+                }
+            }
+            if components.len() > defined {
+                self.push(
+                    Finding::warning(
+                        SnipLevel::Requirements,
+                        raw,
+                        format!(
+                            "{} is not used in this implementation",
+                            element_ref(&raw.id, el.position, defined + 1)
+                        ),
+                    )
+                    .at_component(el.position, defined + 1)
+                    .code("I10"),
+                );
+            }
+        }
+
+        for rule in spec.rules {
+            self.check_rule(raw, rule);
+        }
+    }
+
+    /// Checks an element value against its data type, length and code list.
+    fn check_value(&mut self, raw: &RawSegment, el: &ElementSpec, value: &str) {
+        let reference = element_ref(&raw.id, el.position, el.component);
+        let problem = match el.data_type {
+            DataType::Decimal if !is_decimal(value) => {
+                Some(("6", format!("'{}' is not a valid decimal number", value)))
+            }
+            DataType::Numeric if !is_decimal(value) || value.contains('.') => {
+                Some(("6", format!("'{}' is not a valid integer", value)))
+            }
+            DataType::Date
+                if !is_valid_date(value) || value.len() < el.min || value.len() > el.max =>
+            {
+                let format = if el.max == 6 { "YYMMDD" } else { "CCYYMMDD" };
+                Some(("8", format!("'{}' is not a valid {} date", value, format)))
+            }
+            DataType::Time
+                if !is_valid_time(value) || value.len() < el.min || value.len() > el.max =>
+            {
+                Some(("9", format!("'{}' is not a valid time", value)))
+            }
+            _ => None,
+        };
+        if let Some((code, message)) = problem {
+            self.push(
+                Finding::error(SnipLevel::Integrity, raw, message)
+                    .at_component(el.position, el.component)
+                    .code(code),
+            );
+            return;
+        }
+
+        let length = match el.data_type {
+            DataType::Decimal | DataType::Numeric => {
+                value.chars().filter(|c| c.is_ascii_digit()).count()
+            }
+            _ => value.chars().count(),
+        };
+        if el.max > 0 && (length < el.min || length > el.max) {
+            self.push(
+                Finding::error(
+                    SnipLevel::Integrity,
+                    raw,
+                    format!(
+                        "{} has length {} but must be {}-{} characters",
+                        reference, length, el.min, el.max
+                    ),
+                )
+                .at_component(el.position, el.component)
+                .code(if length < el.min { "4" } else { "5" }),
+            );
+        }
+        if !el.codes.is_empty() && !el.codes.contains(&value) {
+            self.push(
+                Finding::error(
+                    SnipLevel::Requirements,
+                    raw,
+                    format!("'{}' is not a valid code for {}", value, reference),
+                )
+                .at_component(el.position, el.component)
+                .code("7"),
+            );
+        }
+    }
+
+    /// Checks an element syntax rule.
+    fn check_rule(&mut self, raw: &RawSegment, rule: &SyntaxRule) {
+        let name = |position: usize| element_ref(&raw.id, position, 0);
+        let names = |positions: &[usize]| {
+            positions
+                .iter()
+                .map(|p| name(*p))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        };
+        let present = |position: usize| is_present(raw, position);
+        let finding = match *rule {
+            SyntaxRule::Paired(a, b) if present(a) != present(b) => {
+                let missing = if present(a) { b } else { a };
+                Some((
+                    missing,
+                    "2",
+                    format!("{} and {} must be used together", name(a), name(b)),
+                ))
+            }
+            SyntaxRule::Conditional(a, b) if present(a) && !present(b) => Some((
+                b,
+                "2",
+                format!("{} is required when {} is present", name(b), name(a)),
+            )),
+            SyntaxRule::ListConditional(a, list)
+                if present(a) && !list.iter().any(|p| present(*p)) =>
+            {
+                Some((
+                    list[0],
+                    "2",
+                    format!("{} is required when {} is present", names(list), name(a)),
+                ))
+            }
+            SyntaxRule::Exclusion(a, b) if present(a) && present(b) => Some((
+                b,
+                "10",
+                format!("only one of {} or {} may be present", name(a), name(b)),
+            )),
+            SyntaxRule::AtLeastOne(list) if !list.iter().any(|p| present(*p)) => Some((
+                list[0],
+                "2",
+                format!("at least one of {} is required", names(list)),
+            )),
+            _ => None,
+        };
+        if let Some((element, code, message)) = finding {
+            let finding = Finding::error(SnipLevel::Integrity, raw, message).at(element);
+            self.push(finding.code(code));
+        }
+    }
+
+    /// Adds `raw` to the balancing totals.
+    fn check_balancing(&mut self, balance: &mut Balance, raw: &RawSegment) {
+        match raw.id.as_str() {
+            "BPR" => balance.bpr = Some(raw.clone()),
+            "CLP" => {
+                self.settle_claim_balance(balance);
+                balance.claim = Some(ClaimBalance {
+                    clp: raw.clone(),
+                    adjustments: Money::ZERO,
+                    line: None,
+                });
+            }
+            "SVC" => {
+                if let Some(claim) = balance.claim.as_mut() {
+                    self.settle_line_balance(claim);
+                    claim.line = Some((raw.clone(), Money::ZERO));
+                }
+            }
+            "CAS" => {
+                if let Some(claim) = balance.claim.as_mut() {
+                    let amount: Money = (0..6)
+                        .filter_map(|trio| money(value(raw, 3 + trio * 3)))
+                        .sum();
+                    match claim.line.as_mut() {
+                        Some((_, line_adjustments)) => *line_adjustments += amount,
+                        None => claim.adjustments += amount,
+                    }
+                }
+            }
+            "LX" | "SE" => self.settle_claim_balance(balance),
+            "PLB" => {
+                self.settle_claim_balance(balance);
+                balance.provider_adjustments += (0..6)
+                    .filter_map(|pair| money(value(raw, 4 + pair * 2)))
+                    .sum::<Money>();
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks the open service line balances.
+    fn settle_line_balance(&mut self, claim: &mut ClaimBalance) {
+        let Some((svc, line_adjustments)) = claim.line.take() else {
+            return;
+        };
+        claim.adjustments += line_adjustments;
+        if let (Some(charge), Some(paid)) = (money(value(&svc, 2)), money(value(&svc, 3))) {
+            if charge - paid != line_adjustments {
+                let message = imbalance("service line", charge, paid, line_adjustments);
+                self.push(
+                    Finding::error(SnipLevel::Balancing, &svc, message)
+                        .in_loop(LoopId::ServicePayment)
+                        .at(3),
+                );
+            }
+        }
+    }
+
+    /// Checks the open claim balances.
+    fn settle_claim_balance(&mut self, balance: &mut Balance) {
+        let Some(mut claim) = balance.claim.take() else {
+            return;
+        };
+        self.settle_line_balance(&mut claim);
+        let paid = money(value(&claim.clp, 4));
+        balance.total_paid = balance
+            .total_paid
+            .zip(paid)
+            .map(|(total, paid)| total + paid);
+        if let (Some(charge), Some(paid)) = (money(value(&claim.clp, 3)), paid) {
+            if charge - paid != claim.adjustments {
+                let message = imbalance("claim", charge, paid, claim.adjustments);
+                self.push(
+                    Finding::error(SnipLevel::Balancing, &claim.clp, message)
+                        .in_loop(LoopId::ClaimPayment)
+                        .at(4),
+                );
+            }
+        }
+    }
+
+    /// Checks the transaction balances.
+    fn settle_balancing(&mut self, balance: &mut Balance) {
+        self.settle_claim_balance(balance);
+        let Some(bpr) = balance.bpr.as_ref() else {
+            return;
+        };
+        if let (Some(amount), Some(total_paid)) = (money(value(bpr, 2)), balance.total_paid) {
+            if amount != total_paid - balance.provider_adjustments {
+                let message = format!(
+                    "total payment {} != claim payments {} less adjustments {}",
+                    amount, total_paid, balance.provider_adjustments
+                );
+                self.push(Finding::error(SnipLevel::Balancing, bpr, message).at(2));
+            }
+        }
+    }
+
+    /// Records what `raw` tells the situational checks.
+    fn check_situational(&mut self, situation: &mut Situation, raw: &RawSegment) {
+        if matches!(raw.id.as_str(), "CLP" | "LX" | "PLB" | "SE") {
+            self.settle_claim_situations(situation);
+        }
+        match raw.id.as_str() {
+            "BPR" => situation.bpr = Some(raw.clone()),
+            "TRN" => situation.trn = Some(raw.clone()),
+            "PER" if value(raw, 1) == "IC" => situation.has_web_contact = true,
+            "CLP" => {
+                situation.claim = Some(ClaimSituation {
+                    clp: raw.clone(),
+                    crossover: None,
+                    claim_dates: false,
+                    patient_responsibility: 0,
+                    line: None,
+                })
+            }
+            "REF" if value(raw, 1) == "0K" && !situation.has_web_contact => self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    raw,
+                    "REF*0K requires a payer web site contact (PER*IC)",
+                )
+                .at(1),
+            ),
+            _ => {}
+        }
+
+        let Some(claim) = situation.claim.as_mut() else {
+            return;
+        };
+        match raw.id.as_str() {
+            "NM1" if value(raw, 1) == "TT" => claim.crossover = Some(raw.clone()),
+            "DTM" => match claim.line.as_mut() {
+                Some((_, dates)) => dates.push(value(raw, 1).to_string()),
+                None => claim.claim_dates |= matches!(value(raw, 1), "232" | "233"),
+            },
+            "SVC" => {
+                if let Some(line) = claim.line.replace((raw.clone(), Vec::new())) {
+                    self.check_line_situations(claim.claim_dates, &line);
+                }
+            }
+            "CAS" if value(raw, 1) == "PR" => claim.patient_responsibility += 1,
+            _ => {}
+        }
+    }
+
+    /// Settles the transaction-level situational checks.
+    fn settle_situational(&mut self, situation: &mut Situation) {
+        self.settle_claim_situations(situation);
+        let Some(bpr) = situation.bpr.as_ref() else {
+            return;
+        };
+        let handling = value(bpr, 1);
+        let method = value(bpr, 4);
+        if let Some(amount) = money(value(bpr, 2)) {
+            if (handling == "H") != amount.is_zero() {
+                let message = if amount.is_zero() {
+                    "a zero total payment must use BPR01 'H' (notification only)"
+                } else {
+                    "BPR01 'H' (notification only) requires a zero total payment"
+                };
+                self.push(Finding::error(SnipLevel::Situational, bpr, message).at(1));
+            }
+        }
+        if (handling == "H") != (method == "NON") {
+            self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    bpr,
+                    format!(
+                        "BPR04 '{}' is inconsistent with BPR01 '{}'",
+                        method, handling
+                    ),
+                )
+                .at(4),
+            );
+        }
+        if let Some(trn) = situation.trn.as_ref() {
+            if is_present(bpr, 11)
+                && is_present(trn, 4)
+                && value(bpr, 11).trim_end() != value(trn, 4).trim_end()
+            {
+                self.push(
+                    Finding::error(
+                        SnipLevel::Situational,
+                        trn,
+                        format!(
+                            "TRN04 '{}' must match BPR11 '{}'",
+                            value(trn, 4),
+                            value(bpr, 11)
+                        ),
+                    )
+                    .at(4),
+                );
+            }
+        }
+    }
+
+    /// Settles the situational checks for the open claim.
+    fn settle_claim_situations(&mut self, situation: &mut Situation) {
+        let Some(claim) = situation.claim.take() else {
+            return;
+        };
+        let clp = &claim.clp;
+        let status = value(clp, 2);
+        match claim.crossover {
+            None if CROSSOVER_STATUSES.contains(&status) => self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    clp,
+                    format!(
+                        "a crossover carrier (NM1*TT) is required when the claim status is {}",
+                        status
+                    ),
+                )
+                .in_loop(LoopId::ClaimPayment)
+                .at(2),
+            ),
+            Some(ref nm1) if !CROSSOVER_STATUSES.contains(&status) => self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    nm1,
+                    format!(
+                        "a crossover carrier must not be sent when the claim status is {}",
+                        status
+                    ),
+                )
+                .in_loop(LoopId::ClaimPayment)
+                .code("I9"),
+            ),
+            _ => {}
+        }
+
+        if money(value(clp, 5)).is_some_and(|amount| !amount.is_zero())
+            && claim.patient_responsibility == 0
+        {
+            self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    clp,
+                    "patient responsibility must be supported by a CAS segment with group code PR",
+                )
+                .in_loop(LoopId::ClaimPayment)
+                .at(5),
+            );
+        }
+
+        if let Some(ref line) = claim.line {
+            self.check_line_situations(claim.claim_dates, line);
+        }
+    }
+
+    /// Checks the situational dates of a service line.
+    fn check_line_situations(
+        &mut self,
+        claim_dates: bool,
+        (svc, dates): &(RawSegment, Vec<String>),
+    ) {
+        let has = |qualifier: &str| dates.iter().any(|date| date == qualifier);
+        if !claim_dates && dates.is_empty() {
+            self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    svc,
+                    "a service date is required when the claim has no statement dates",
+                )
+                .in_loop(LoopId::ServicePayment)
+                .code("I6"),
+            );
+        }
+        if has("150") != has("151") {
+            self.push(
+                Finding::error(
+                    SnipLevel::Situational,
+                    svc,
+                    "a service period must have both a start (DTM*150) and an end (DTM*151) date",
+                )
+                .in_loop(LoopId::ServicePayment)
+                .code("I6"),
+            );
+        }
+    }
+
+    /// Checks codes in `raw` against the external code sets.
+    fn check_code_sets(&mut self, raw: &RawSegment) {
+        match raw.id.as_str() {
+            "CAS" => {
+                for position in (2..=17).step_by(3) {
+                    let code = value(raw, position);
+                    if !code.is_empty() && !self.carc.contains(code) {
+                        self.push(
+                            Finding::error(
+                                SnipLevel::CodeSets,
+                                raw,
+                                format!("'{}' is not a known claim adjustment reason code", code),
+                            )
+                            .at(position)
+                            .code("7"),
+                        );
+                    }
+                }
+            }
+            "LQ" if value(raw, 1) == "HE" && !self.rarc.contains(value(raw, 2)) => {
+                self.push(
+                    Finding::warning(
+                        SnipLevel::CodeSets,
+                        raw,
+                        format!(
+                            "'{}' is not a known remittance advice remark code",
+                            value(raw, 2)
+                        ),
+                    )
+                    .at(2)
+                    .code("7"),
+                );
+            }
+            "N1" if value(raw, 3) == "XX" => self.check_npi(raw, 4),
+            "NM1" if value(raw, 8) == "XX" => self.check_npi(raw, 9),
+            "N4" => {
+                let domestic = matches!(value(raw, 4), "" | "US");
+                let state = value(raw, 2);
+                if domestic && !state.is_empty() && !US_STATES.contains(&state) {
+                    self.push(
+                        Finding::error(
+                            SnipLevel::CodeSets,
+                            raw,
+                            format!("'{}' is not a valid state code", state),
+                        )
+                        .at(2)
+                        .code("7"),
+                    );
+                }
+                let zip = value(raw, 3);
+                let zip_valid =
+                    matches!(zip.len(), 5 | 9) && zip.chars().all(|c| c.is_ascii_digit());
+                if domestic && !zip.is_empty() && !zip_valid {
+                    self.push(
+                        Finding::error(
+                            SnipLevel::CodeSets,
+                            raw,
+                            format!("'{}' is not a valid ZIP code", zip),
+                        )
+                        .at(3),
+                    );
+                }
+            }
+            "SVC" => {
+                for position in [1, 6] {
+                    self.check_procedure(raw, position);
+                }
+            }
+            "CLP" if is_present(raw, 8) => {
+                let facility = value(raw, 8);
+                if facility.len() != 2 || !facility.chars().all(|c| c.is_ascii_digit()) {
+                    self.push(
+                        Finding::error(
+                            SnipLevel::CodeSets,
+                            raw,
+                            format!(
+                                "'{}' is not a valid facility type or place of service code",
+                                facility
+                            ),
+                        )
+                        .at(8)
+                        .code("7"),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks the NPI at `position`.
+    fn check_npi(&mut self, raw: &RawSegment, position: usize) {
+        let npi = value(raw, position);
+        if !npi.is_empty() && !is_valid_npi(npi) {
+            self.push(
+                Finding::error(
+                    SnipLevel::CodeSets,
+                    raw,
+                    format!("'{}' is not a valid NPI", npi),
+                )
+                .at(position),
+            );
+        }
+    }
+
+    /// Checks the procedure code at `position`.
+    fn check_procedure(&mut self, raw: &RawSegment, position: usize) {
+        let qualifier = component(raw, position, 1);
+        let code = component(raw, position, 2);
+        if code.is_empty() {
+            return;
+        }
+        if let Some(expected) = procedure_code_format(qualifier, code) {
+            self.push(
+                Finding::error(
+                    SnipLevel::CodeSets,
+                    raw,
+                    format!("'{}' is not {}", code, expected),
+                )
+                .at_component(position, 2),
+            );
+        }
+        for modifier in 3..=6 {
+            let value = component(raw, position, modifier);
+            if !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+                self.push(
+                    Finding::error(
+                        SnipLevel::CodeSets,
+                        raw,
+                        format!("'{}' is not a valid procedure modifier", value),
+                    )
+                    .at_component(position, modifier),
+                );
+            }
+        }
+    }
+}
+
+/// Runs the `conformance` subcommand.
+pub fn run(args: &ConformanceArgs) -> Result<()> {
+    let input = open_input(&args.input_path)?;
+    let mut output: Box<dyn Write> = match args.output {
+        Some(ref path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let max_errors = match args.max_errors {
+        None if args.allow_errors => None,
+        limit => Some(limit.unwrap_or(0)),
+    };
+    let failures = match args.report_format {
+        ReportFormat::Text => {
+            let mut written = Ok(());
+            let summary = check_conformance(
+                input,
+                &args.data_dir,
+                |finding| {
+                    if written.is_ok() {
+                        written = writeln!(output, "{}", finding);
+                    }
+                },
+                log_progress,
+            )?;
+            written?;
+            let failures = summary.threshold_failures(args.fail_under, max_errors);
+            if summary.errors() + summary.warnings() > 0 {
+                writeln!(output)?;
+            }
+            output.write_all(text_summary(&summary, &failures).as_bytes())?;
+            failures
+        }
+        ref format => {
+            let result = compute_conformance_with_progress(input, &args.data_dir, log_progress)?;
+            let failures = result
+                .summary
+                .threshold_failures(args.fail_under, max_errors);
+            let report = render(&result, &failures, &args.input_path, format)?;
+            output.write_all(report.as_bytes())?;
+            failures
+        }
+    };
+    output.flush()?;
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Validation(failures.join("; ")))
+    }
+}
+
+/// Logs conformance progress.
+fn log_progress(progress: Progress) {
+    info!(
+        "Validated {} MiB ({} segments)...",
+        progress.bytes / (1024 * 1024),
+        progress.segments
+    );
+}
+
+/// Opens a plain or gzip-compressed input file, or stdin for `-`.
+pub fn open_input(path: &Path) -> Result<Box<dyn BufRead>> {
+    let source: Box<dyn Read> = if path == Path::new("-") {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(path)?)
+    };
+    let mut reader = BufReader::with_capacity(INPUT_BUFFER_SIZE, source);
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        let decoder = BufReader::with_capacity(INPUT_BUFFER_SIZE, MultiGzDecoder::new(reader));
+        Ok(Box::new(decoder))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Renders `result` in `format`.
+pub fn render(
+    result: &ConformanceResult,
+    failures: &[String],
+    input_path: &Path,
+    format: &ReportFormat,
+) -> Result<String> {
+    Ok(match format {
+        ReportFormat::Text => text_report(result, failures),
+        ReportFormat::Json => json_report(result, failures, input_path)?,
+        ReportFormat::Junit => junit_report(result, failures, input_path),
+        ReportFormat::Sarif => sarif_report(result, input_path)?,
+    })
+}
+
+/// The text report.
+fn text_report(result: &ConformanceResult, failures: &[String]) -> String {
+    let mut out = String::new();
+    for finding in &result.findings {
+        let _ = writeln!(out, "{}", finding);
+    }
+    if !result.findings.is_empty() {
+        let _ = writeln!(out);
+    }
+    out.push_str(&text_summary(&result.summary, failures));
+    out
+}
+
+/// The text segment presence summary.
+fn text_summary(summary: &ConformanceSummary, failures: &[String]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Segment presence:");
     for id in REQUIRED_SEGMENTS {
-        let status = if result.present.contains(&id.to_string()) {
+        let status = if summary.present.contains(*id) {
             "OK"
         } else {
             "MISSING"
         };
-        println!("  {:<3} - {}", id, status);
+        let _ = writeln!(out, "  {:<3} - {}", id, status);
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Score: {}/{} segments without errors ({:.1}%)",
+        summary.clean_segments(),
+        summary.segments,
+        summary.score()
+    );
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Validation:");
+    for level in SnipLevel::ALL {
+        let _ = writeln!(
+            out,
+            "  {} {:<34} {} errors, {} warnings",
+            level,
+            format!("({})", level.description()),
+            summary.count(Severity::Error, Some(level)),
+            summary.count(Severity::Warning, Some(level))
+        );
     }
-    println!();
-    let pct = (result.found as f64) / (result.total as f64) * 100.0;
-    println!(
-        "Score: {}/{} segments present ({:.1}%)",
-        result.found, result.total, pct
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "Result: {} ({} errors, {} warnings)",
+        if summary.errors() == 0 {
+            "PASS"
+        } else {
+            "FAIL"
+        },
+        summary.errors(),
+        summary.warnings()
     );
-    Ok(())
+    for failure in failures {
+        let _ = writeln!(out, "Threshold not met: {}", failure);
+    }
+    out
+}
+
+/// The JSON report.
+fn json_report(
+    result: &ConformanceResult,
+    failures: &[String],
+    input_path: &Path,
+) -> Result<String> {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        input: String,
+        score: f64,
+        errors: usize,
+        warnings: usize,
+        threshold_failures: &'a [String],
+        #[serde(flatten)]
+        result: &'a ConformanceResult,
+    }
+
+    let report = Report {
+        input: input_path.display().to_string(),
+        score: result.summary.score(),
+        errors: result.summary.errors(),
+        warnings: result.summary.warnings(),
+        threshold_failures: failures,
+        result,
+    };
+    let mut out = serde_json::to_string_pretty(&report)?;
+    out.push('\n');
+    Ok(out)
+}
+
+/// The JUnit XML report.
+fn junit_report(result: &ConformanceResult, failures: &[String], input_path: &Path) -> String {
+    let mut cases = Vec::new();
+
+    let missing = result.summary.missing();
+    let failure = (!missing.is_empty()).then(|| {
+        (
+            format!("{} required segments missing", missing.len()),
+            missing.join(", "),
+        )
+    });
+    cases.push(("Segment presence".to_string(), failure, String::new()));
+
+    for level in SnipLevel::ALL {
+        let lines = |severity: Severity| {
+            result
+                .findings
+                .iter()
+                .filter(|f| f.level == level && f.severity == severity)
+                .map(|f| f.to_string())
+                .collect::<Vec<_>>()
+        };
+        let errors = lines(Severity::Error);
+        let failure =
+            (!errors.is_empty()).then(|| (format!("{} errors", errors.len()), errors.join("\n")));
+        let name = format!("{} ({})", level, level.description());
+        cases.push((name, failure, lines(Severity::Warning).join("\n")));
+    }
+
+    if !failures.is_empty() {
+        let failure = Some(("thresholds not met".to_string(), failures.join("\n")));
+        cases.push(("Thresholds".to_string(), failure, String::new()));
+    }
+
+    let failed = cases.iter().filter(|(_, f, _)| f.is_some()).count();
+    let input = xml_escape(&input_path.display().to_string());
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        out,
+        r#"<testsuites name="zedi-gen conformance" tests="{}" failures="{}">"#,
+        cases.len(),
+        failed
+    );
+    let _ = writeln!(
+        out,
+        r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0">"#,
+        input,
+        cases.len(),
+        failed
+    );
+    for (name, failure, output) in &cases {
+        let _ = write!(
+            out,
+            r#"    <testcase classname="conformance" name="{}">"#,
+            xml_escape(name)
+        );
+        if failure.is_none() && output.is_empty() {
+            out.truncate(out.len() - 1);
+            let _ = writeln!(out, "/>");
+            continue;
+        }
+        let _ = writeln!(out);
+        if let Some((message, body)) = failure {
+            let _ = writeln!(
+                out,
+                r#"      <failure message="{}">{}</failure>"#,
+                xml_escape(message),
+                xml_escape(body)
+            );
+        }
+        if !output.is_empty() {
+            let _ = writeln!(out, "      <system-out>{}</system-out>", xml_escape(output));
+        }
+        let _ = writeln!(out, "    </testcase>");
+    }
+    let _ = writeln!(out, "  </testsuite>");
+    let _ = writeln!(out, "</testsuites>");
+    out
+}
+
+/// The SARIF report.
+fn sarif_report(result: &ConformanceResult, input_path: &Path) -> Result<String> {
+    let uri = input_path.display().to_string().replace('\\', "/");
+    let rules: Vec<_> = SnipLevel::ALL
+        .iter()
+        .map(|level| {
+            json!({
+                "id": sarif_rule_id(*level),
+                "name": format!("{:?}", level),
+                "shortDescription": { "text": format!("{}: {}", level, level.description()) },
+            })
+        })
+        .collect();
+    let results: Vec<_> = result
+        .findings
+        .iter()
+        .map(|finding| {
+            let mut location = json!({
+                "physicalLocation": { "artifactLocation": { "uri": uri } },
+            });
+            if finding.line > 0 {
+                location["physicalLocation"]["region"] = json!({ "startLine": finding.line });
+                if let Some(offset) = finding.offset {
+                    location["physicalLocation"]["region"]["byteOffset"] = json!(offset);
+                }
+            }
+            if finding.segment_id.is_some() {
+                location["logicalLocations"] = json!([{ "name": finding.location() }]);
+            }
+            json!({
+                "ruleId": sarif_rule_id(finding.level),
+                "ruleIndex": finding.level.number() - 1,
+                "level": finding.severity.to_string(),
+                "message": { "text": finding.message },
+                "locations": [location],
+            })
+        })
+        .collect();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "zedi-gen",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "artifacts": [{ "location": { "uri": uri } }],
+            "results": results,
+        }]
+    });
+    let mut out = serde_json::to_string_pretty(&sarif)?;
+    out.push('\n');
+    Ok(out)
+}
+
+/// The SARIF rule ID for `level`.
+fn sarif_rule_id(level: SnipLevel) -> String {
+    format!("SNIP{}", level.number())
+}
+
+/// Escapes `text` for XML.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+    use crate::generator::generate_to_string;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::NamedTempFile;
 
     const SAMPLE: &str = "ISA*00*          *00*          *ZZ*AAA          *ZZ*BBB          *200101*1253*^*00501*000000905*0*P*~\
 GS*HP*AAA*BBB*200101*1253*1*X*005010X221A1~\
 ST*835*0001~BPR*C*100.00*C*ACH***CTX*01*999999999*DA*999999999*200101~TRN*1*12345*98765~DTM*405*200101~\
 N1*PR*PAYER~N1*PE*PAYEE~CLP*1234*1*200*100**11*PAYER123~SVC*HC:200*150*100*0450*1~SE*12*0001~GE*1*1~IEA*1*000000905~";
 
+    const VALID: &str = "ISA*00*          *00*          *ZZ*SENDER001      *ZZ*RECEIVER01     *240601*1200*^*00501*000000001*0*P*:~
+GS*HP*SENDER001*RECEIVER01*20240601*1200*1*X*005010X221A1~
+ST*835*0001*005010X221A1~
+BPR*I*130.00*C*CHK************20240601~
+TRN*1*12345*1512345678~
+DTM*405*20240601~
+N1*PR*ACME HEALTH~
+N3*100 MAIN ST~
+N4*HARTFORD*CT*06103~
+PER*BL*EDI SUPPORT*TE*8005551313~
+N1*PE*CLINIC*XX*1234567893~
+LX*1~
+CLP*PCN1*1*200.00*130.00*20.00*12*PAYERCLM1~
+NM1*QC*1*DOE*JANE****MI*W123456789~
+DTM*232*20240415~
+SVC*HC:99213*200.00*130.00**1~
+CAS*CO*45*50.00~
+CAS*PR*1*20.00~
+SE*17*0001~
+GE*1*1~
+IEA*1*000000001~
+";
+
+    fn errors_at(content: &str, level: SnipLevel) -> Vec<(usize, String)> {
+        validate(content, Path::new("data"))
+            .into_iter()
+            .filter(|f| f.severity == Severity::Error && f.level == level)
+            .map(|f| (f.line, f.location()))
+            .collect()
+    }
+
     #[test]
     fn test_compute_conformance_full() {
-        let res = compute_conformance(SAMPLE);
-        assert_eq!(res.total, 12);
-        assert_eq!(res.found, 12);
+        let res = compute_conformance(SAMPLE, Path::new("data"));
+        assert_eq!(res.summary.total, 12);
+        assert_eq!(res.summary.found, 12);
     }
 
     #[test]
     fn test_compute_conformance_partial() {
-        
         let sample = SAMPLE.replace("GE*1*1~IEA*1*000000905~", "");
-        let res = compute_conformance(&sample);
-        assert_eq!(res.total, 12);
-        assert_eq!(res.found, 10);
+        let res = compute_conformance(&sample, Path::new("data"));
+        assert_eq!(res.summary.total, 12);
+        assert_eq!(res.summary.found, 10);
+    }
+
+    #[test]
+    fn test_valid_remittance_has_no_findings() {
+        let findings = validate(VALID, Path::new("data"));
+        assert!(findings.is_empty(), "{:#?}", findings);
+    }
+
+    #[test]
+    fn test_envelope_counts_and_control_numbers() {
+        let content = VALID
+            .replace("SE*17*0001", "SE*16*0002")
+            .replace("GE*1*1", "GE*2*7")
+            .replace("IEA*1*000000001", "IEA*1*000000002");
+        let errors = errors_at(&content, SnipLevel::Integrity);
+        for expected in ["SE01", "SE02", "GE01", "GE02", "IEA02"] {
+            assert!(
+                errors.iter().any(|(_, loc)| loc == expected),
+                "{:?}",
+                errors
+            );
+        }
+        assert!(errors.iter().all(|(line, _)| (19..=21).contains(line)));
+
+        let content = VALID.replace("SE*17*0001~\n", "");
+        let findings = validate(&content, Path::new("data"));
+        assert!(findings
+            .iter()
+            .any(|f| f.line == 3 && f.message.contains("missing its SE trailer")));
+    }
+
+    #[test]
+    fn test_elements_codes_and_data_types() {
+        let content = VALID
+            .replace("CLP*PCN1*1*", "CLP**7*")
+            .replace("DTM*232*20240415", "DTM*232*20241345");
+        assert_eq!(
+            errors_at(&content, SnipLevel::Requirements),
+            vec![(13, "CLP01".to_string()), (13, "CLP02".to_string())]
+        );
+        assert_eq!(
+            errors_at(&content, SnipLevel::Integrity),
+            vec![(15, "DTM02".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_segment_order_and_loop_structure() {
+        let content = VALID.replace(
+            "NM1*QC*1*DOE*JANE****MI*W123456789~\nDTM*232*20240415~",
+            "DTM*232*20240415~\nNM1*QC*1*DOE*JANE****MI*W123456789~",
+        );
+        let findings = validate(&content, Path::new("data"));
+        assert!(findings.iter().any(|f| f.line == 15
+            && f.level == SnipLevel::Integrity
+            && f.message.contains("out of order")));
+
+        let content = VALID.replace("LX*1~\n", "");
+        let findings = validate(&content, Path::new("data"));
+        assert!(findings
+            .iter()
+            .any(|f| f.segment_id.as_deref() == Some("CLP") && f.message.contains("loop 2000")));
+
+        let content = VALID.replace("LX*1~", "N1*PR*ACME HEALTH~\nLX*1~");
+        let messages: Vec<String> = validate(&content, Path::new("data"))
+            .into_iter()
+            .filter(|f| f.line == 12)
+            .map(|f| f.message)
+            .collect();
+        assert!(messages.contains(&"loop 1000A cannot follow loop 1000B".to_string()));
+        assert!(messages.contains(&"loop 1000A exceeds its maximum repeat of 1".to_string()));
+    }
+
+    #[test]
+    fn test_balancing() {
+        let content = VALID
+            .replace("SVC*HC:99213*200.00*130.00", "SVC*HC:99213*200.00*120.00")
+            .replace("BPR*I*130.00", "BPR*I*135.00");
+        assert_eq!(
+            errors_at(&content, SnipLevel::Balancing),
+            vec![(4, "BPR02".to_string()), (16, "SVC03".to_string())]
+        );
+
+        let content = VALID.replace("CAS*CO*45*50.00", "CAS*CO*45*40.00");
+        assert_eq!(
+            errors_at(&content, SnipLevel::Balancing),
+            vec![(13, "CLP04".to_string()), (16, "SVC03".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_situational_rules() {
+        let content = VALID
+            .replace("CLP*PCN1*1*", "CLP*PCN1*19*")
+            .replace("DTM*232*20240415~\n", "");
+        assert_eq!(
+            errors_at(&content, SnipLevel::Situational),
+            vec![(13, "CLP02".to_string()), (15, "SVC".to_string())]
+        );
+
+        let unpaid = VALID.replace("BPR*I*130.00*", "BPR*I*0.00*");
+        assert_eq!(
+            errors_at(&unpaid, SnipLevel::Situational),
+            vec![(4, "BPR01".to_string())]
+        );
+        let notification = VALID.replace("BPR*I*130.00*C*CHK*", "BPR*H*0.00*C*NON*");
+        assert!(errors_at(&notification, SnipLevel::Situational).is_empty());
+    }
+
+    #[test]
+    fn test_code_sets() {
+        let content = VALID
+            .replace("CAS*CO*45*", "CAS*CO*ZZ9*")
+            .replace("XX*1234567893", "XX*1234567890")
+            .replace("HC:99213", "HC:9921");
+        assert_eq!(
+            errors_at(&content, SnipLevel::CodeSets),
+            vec![
+                (11, "N104".to_string()),
+                (16, "SVC01-2".to_string()),
+                (17, "CAS02".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_npi_check_digit() {
+        assert!(is_valid_npi("1234567893"));
+        assert!(!is_valid_npi("1234567890"));
+        assert!(!is_valid_npi("123456789"));
+    }
+
+    #[test]
+    fn test_generated_remittances_conform() {
+        let content = generate_to_string(Config {
+            claim_count: 60,
+            seed: Some(11),
+            anomaly_rate: 0.0,
+            plb_rate: 0.5,
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            ..Default::default()
+        });
+
+        let findings = validate(&content, Path::new("data"));
+        assert!(findings.is_empty(), "{:#?}", findings);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let res = compute_conformance(VALID, Path::new("data"));
+        assert_eq!(res.summary.score(), 100.0);
+        assert!(res
+            .summary
+            .threshold_failures(Some(100.0), Some(0))
+            .is_empty());
+
+        let content = VALID
+            .replace("CLP*PCN1*1*", "CLP*PCN1*99*")
+            .replace("GE*1*1~\nIEA*1*000000001~\n", "");
+        let res = compute_conformance(&content, Path::new("data"));
+        assert_eq!(res.summary.segments, 19);
+        assert_eq!(res.summary.clean_segments(), 16);
+        assert!(res.summary.threshold_failures(None, None).is_empty());
+        assert!(res.summary.threshold_failures(Some(80.0), None).is_empty());
+        let failures = res.summary.threshold_failures(Some(90.0), Some(0));
+        assert_eq!(failures.len(), 2, "{:?}", failures);
+        assert!(failures[0].contains("84.2%"));
+        assert!(failures[1].starts_with("3 errors"));
+        assert_eq!(res.summary.missing(), vec!["GE", "IEA"]);
+    }
+
+    #[test]
+    fn test_report_formats() {
+        let content = VALID.replace("CLP*PCN1*1*", "CLP*PCN1*99*");
+        let res = compute_conformance(&content, Path::new("data"));
+        assert!(res.summary.errors() > 0);
+        let path = Path::new("claims.edi");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&res, &[], path, &ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["input"], "claims.edi");
+        assert_eq!(json["errors"], res.summary.errors());
+        assert_eq!(json["findings"][0]["severity"], "error");
+        assert_eq!(json["findings"][0]["level"], 2);
+        assert_eq!(json["findings"][0]["segment_id"], "CLP");
+
+        let junit = render(&res, &[], path, &ReportFormat::Junit).unwrap();
+        assert!(junit.starts_with("<?xml"));
+        assert!(junit.contains(r#"<testsuite name="claims.edi" tests="6" failures="1""#));
+        assert!(junit.contains("<failure message=\"1 errors\">error [SNIP 2]"));
+        assert!(junit.trim_end().ends_with("</testsuites>"));
+
+        let sarif: serde_json::Value =
+            serde_json::from_str(&render(&res, &[], path, &ReportFormat::Sarif).unwrap()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), res.findings.len());
+        assert_eq!(results[0]["ruleId"], "SNIP2");
+        assert_eq!(results[0]["level"], "error");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "claims.edi");
+        assert!(location["region"]["startLine"].as_u64().unwrap() > 1);
+        let logical = &results[0]["locations"][0]["logicalLocations"][0];
+        assert_eq!(logical["name"], "CLP02");
+
+        let failures = vec!["1 errors exceed the maximum of 0".to_string()];
+        let text = render(&res, &failures, path, &ReportFormat::Text).unwrap();
+        assert!(text.contains("Result: FAIL (1 errors, 0 warnings)"));
+        assert!(text.ends_with("Threshold not met: 1 errors exceed the maximum of 0\n"));
+    }
+
+    #[test]
+    fn test_library_entry_points() {
+        let from_reader =
+            compute_conformance_from_reader(VALID.as_bytes(), Path::new("data")).unwrap();
+        assert_eq!(from_reader.summary.found, from_reader.summary.total);
+        assert!(from_reader.findings.is_empty());
+
+        let interchange = crate::x12::parser::parse(VALID).unwrap();
+        let result = compute_conformance_for_interchange(&interchange, Path::new("data"));
+        assert!(result.findings.is_empty(), "{:#?}", result.findings);
+
+        let content = VALID.replace("CLP*PCN1*1*", "CLP*PCN1*99*");
+        let findings = compute_conformance_from_reader(content.as_bytes(), Path::new("data"))
+            .unwrap()
+            .findings;
+        assert_eq!(findings[0].transaction.as_deref(), Some("0001"));
+        let envelope = VALID.replace("IEA*1*", "IEA*2*");
+        assert_eq!(validate(&envelope, Path::new("data"))[0].transaction, None);
+
+        let denied = VALID.replace("CAS*CO*45*", "CAS*CO*197*");
+        assert!(validate(&denied, Path::new("does-not-exist")).is_empty());
+    }
+
+    #[test]
+    fn test_findings_are_streamed_to_the_sink() {
+        let content = VALID
+            .replace("CLP*PCN1*1*", "CLP*PCN1*99*")
+            .replace("SVC*HC:99213*200.00*130.00", "SVC*HC:99213*200.00*120.00");
+        let mut streamed = Vec::new();
+        let summary = check_conformance(
+            content.as_bytes(),
+            Path::new("data"),
+            |finding| streamed.push(finding),
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(summary.errors(), streamed.len());
+        assert_eq!(
+            summary.count(Severity::Error, Some(SnipLevel::Balancing)),
+            1
+        );
+        assert!(streamed
+            .iter()
+            .all(|f| f.transaction.as_deref() == Some("0001")));
+
+        let collected = compute_conformance(&content, Path::new("data"));
+        streamed.sort_by_key(|f| (f.segment, f.level, f.element, f.component));
+        assert_eq!(streamed, collected.findings);
+    }
+
+    #[test]
+    fn test_streamed_gzip_input() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut encoder = GzEncoder::new(temp_file.reopen().unwrap(), Compression::default());
+        encoder
+            .write_all(VALID.replace("CLP*PCN1*1*", "CLP*PCN1*99*").as_bytes())
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let input = open_input(temp_file.path()).unwrap();
+        let result = compute_conformance_with_progress(input, Path::new("data"), |_| {
+            panic!("progress is reported every {} bytes", PROGRESS_INTERVAL)
+        })
+        .unwrap();
+        assert_eq!(result.summary.found, result.summary.total);
+        assert_eq!(result.summary.errors(), 1);
+        assert_eq!(result.findings[0].line, 13);
+        assert_eq!(result.findings[0].location(), "CLP02");
+
+        let plain = open_input(Path::new("data/carc_codes.csv")).unwrap();
+        let result = compute_conformance_with_progress(plain, Path::new("data"), |_| {}).unwrap();
+        assert_eq!(result.findings.len(), 1);
+        let message = &result.findings[0].message;
+        assert!(message.contains("must start with an ISA"), "{}", message);
     }
 }
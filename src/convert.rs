@@ -0,0 +1,853 @@
+//! Conversion between X12 835 interchanges and the JSON remittance model.
+
+use crate::adjustments::{
+    Adjustment, AdjustmentGroup, ProviderAdjustment, ProviderAdjustmentReason,
+};
+use crate::balancing;
+use crate::claims::{Claim, ClaimStatus, ClaimStatusCode, CrossoverCarrier, ServiceLine};
+use crate::cli::{ConvertArgs, OutputFormat};
+use crate::clock::Clock;
+use crate::denials::DenialReason;
+use crate::errors::{Error, Result};
+use crate::generator::{Generator, RECEIVER_ID, SENDER_ID};
+use crate::money::Money;
+use crate::payers::{Contact, Payer, PaymentMethod, RemittanceDelivery};
+use crate::population::{Address, Person, Provider};
+use crate::remittance::{group_claims, Remittance};
+use crate::x12::control_numbers::ControlNumbers;
+use crate::x12::delimiters::Delimiters;
+use crate::x12::envelope::{FunctionalGroup, X12Interchange};
+use crate::x12::loops::{ClaimPaymentLoop, PartyLoop, RemittanceAdvice, ServicePaymentLoop};
+use crate::x12::parser;
+use crate::x12::segments::{Nm1Segment, PerSegment, Segment};
+use chrono::NaiveDate;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+
+/// A remittance interchange in JSON form.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemittanceDocument {
+    /// Interchange envelope.
+    pub interchange: InterchangeHeader,
+
+    /// Functional groups in the interchange.
+    pub functional_groups: Vec<RemittanceGroup>,
+}
+
+/// The ISA envelope of a remittance document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterchangeHeader {
+    /// ISA06 sender ID.
+    pub sender_id: String,
+
+    /// ISA08 receiver ID.
+    pub receiver_id: String,
+
+    /// Interchange control number.
+    pub control_number: String,
+
+    /// Interchange date (YYMMDD).
+    pub date: String,
+
+    /// Interchange time (HHMM).
+    pub time: String,
+
+    /// Usage indicator (`P` or `T`).
+    pub usage_indicator: char,
+
+    /// Delimiters used when writing X12.
+    #[serde(default)]
+    pub delimiters: Delimiters,
+}
+
+/// A GS functional group of a remittance document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemittanceGroup {
+    /// GS02 sender code.
+    pub sender_id: String,
+
+    /// GS03 receiver code.
+    pub receiver_id: String,
+
+    /// Group control number.
+    pub control_number: String,
+
+    /// Group date (CCYYMMDD).
+    pub date: String,
+
+    /// Group time (HHMM).
+    pub time: String,
+
+    /// One transaction per remittance.
+    pub transactions: Vec<RemittanceTransaction>,
+}
+
+/// An 835 transaction set of a remittance document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemittanceTransaction {
+    /// Transaction set control number.
+    pub control_number: String,
+
+    /// Provider fiscal period end reported in TS3 and PLB (CCYYMMDD).
+    pub fiscal_period_date: String,
+
+    /// The remittance carried by the transaction.
+    #[serde(flatten)]
+    pub remittance: Remittance,
+}
+
+/// JSON input: a remittance document or a list of claims.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonInput {
+    Document(RemittanceDocument),
+    Claims(Vec<ClaimRecord>),
+}
+
+/// A claim, either bare or wrapped as in the generator's JSON output.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClaimRecord {
+    Generated { claim: Claim },
+    Claim(Claim),
+}
+
+impl RemittanceDocument {
+    /// Parses an X12 835 interchange.
+    pub fn from_x12(input: &str) -> Result<Self> {
+        Self::from_interchange(&parser::parse(input)?)
+    }
+
+    /// Converts a parsed interchange.
+    pub fn from_interchange(interchange: &X12Interchange) -> Result<Self> {
+        let isa = &interchange.isa;
+        let functional_groups = interchange
+            .functional_groups
+            .iter()
+            .map(|group| {
+                let transactions = group
+                    .transaction_sets
+                    .iter()
+                    .map(|transaction| {
+                        let control_number = &transaction.st.st02_control_number;
+                        let advice = RemittanceAdvice::from_transaction(transaction)?;
+                        let remittance = remittance_from(&advice).map_err(|message| {
+                            Error::Validation(format!(
+                                "transaction {}: {}",
+                                control_number, message
+                            ))
+                        })?;
+                        Ok(RemittanceTransaction {
+                            control_number: control_number.clone(),
+                            fiscal_period_date: fiscal_period_date(&advice),
+                            remittance,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(RemittanceGroup {
+                    sender_id: group.gs.gs02_sender_id.clone(),
+                    receiver_id: group.gs.gs03_receiver_id.clone(),
+                    control_number: group.gs.gs06_group_control_number.clone(),
+                    date: group.gs.gs04_date.clone(),
+                    time: group.gs.gs05_time.clone(),
+                    transactions,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            interchange: InterchangeHeader {
+                sender_id: isa.isa06_sender_id.clone(),
+                receiver_id: isa.isa08_receiver_id.clone(),
+                control_number: isa.isa12_control_number.clone(),
+                date: isa.isa09_date.clone(),
+                time: isa.isa10_time.clone(),
+                usage_indicator: isa.isa13_usage_indicator,
+                delimiters: interchange.delimiters,
+            },
+            functional_groups,
+        })
+    }
+
+    /// Reads a remittance document, or a JSON array of claims that is grouped into
+    /// remittances dated by `clock`.
+    pub fn from_json(input: &str, clock: Clock) -> Result<Self> {
+        match serde_json::from_str(input)? {
+            JsonInput::Document(document) => Ok(document),
+            JsonInput::Claims(records) => {
+                let claims = records
+                    .into_iter()
+                    .map(|record| match record {
+                        ClaimRecord::Generated { claim } | ClaimRecord::Claim(claim) => claim,
+                    })
+                    .collect();
+                Ok(Self::from_claims(claims, clock))
+            }
+        }
+    }
+
+    /// Groups claims into one remittance per billing provider.
+    pub fn from_claims(claims: Vec<Claim>, clock: Clock) -> Self {
+        let mut control_numbers = ControlNumbers::default();
+        let now = clock.now();
+        let payment_date = clock.today().format("%Y%m%d").to_string();
+        let fiscal_period_date = clock.fiscal_period_end().format("%Y%m%d").to_string();
+
+        let interchange = InterchangeHeader {
+            sender_id: SENDER_ID.to_string(),
+            receiver_id: RECEIVER_ID.to_string(),
+            control_number: control_numbers.next_interchange(),
+            date: now.format("%y%m%d").to_string(),
+            time: now.format("%H%M").to_string(),
+            usage_indicator: 'P',
+            delimiters: Delimiters::default(),
+        };
+        let mut group = RemittanceGroup {
+            sender_id: SENDER_ID.to_string(),
+            receiver_id: RECEIVER_ID.to_string(),
+            control_number: control_numbers.next_group(),
+            date: now.format("%Y%m%d").to_string(),
+            time: interchange.time.clone(),
+            transactions: Vec::new(),
+        };
+
+        let remittances = group_claims(&Payer::default(), &payment_date, claims);
+        for (i, mut remittance) in remittances.into_iter().enumerate() {
+            remittance.trace_number = format!("{:010}", i + 1);
+            group.transactions.push(RemittanceTransaction {
+                control_number: control_numbers.next_transaction(),
+                fiscal_period_date: fiscal_period_date.clone(),
+                remittance,
+            });
+        }
+
+        Self {
+            interchange,
+            functional_groups: vec![group],
+        }
+    }
+
+    /// Builds the X12 interchange.
+    pub fn to_interchange(&self) -> X12Interchange {
+        let header = &self.interchange;
+        let mut interchange = X12Interchange::new(
+            &header.sender_id,
+            &header.receiver_id,
+            &header.control_number,
+            &header.date,
+            &header.time,
+        );
+        interchange.isa.isa13_usage_indicator = header.usage_indicator;
+        interchange.delimiters = header.delimiters;
+
+        for group in &self.functional_groups {
+            let mut functional_group = FunctionalGroup::new(
+                &group.sender_id,
+                &group.receiver_id,
+                &group.control_number,
+                &group.date,
+                &group.time,
+            );
+            for transaction in &group.transactions {
+                functional_group.add_transaction_set(Generator::build_835_transaction(
+                    &transaction.remittance,
+                    &transaction.control_number,
+                    &transaction.fiscal_period_date,
+                ));
+            }
+            interchange.add_functional_group(functional_group);
+        }
+
+        interchange
+    }
+
+    /// Every claim in the document.
+    pub fn claims(&self) -> impl Iterator<Item = &Claim> {
+        self.functional_groups
+            .iter()
+            .flat_map(|group| &group.transactions)
+            .flat_map(|transaction| &transaction.remittance.claims)
+    }
+}
+
+/// Runs the `convert` subcommand.
+pub fn run(args: &ConvertArgs) -> Result<()> {
+    let content = fs::read_to_string(&args.input_path)?;
+    let is_x12 = content.trim_start().starts_with("ISA");
+    let document = if is_x12 {
+        RemittanceDocument::from_x12(&content)?
+    } else {
+        RemittanceDocument::from_json(&content, Clock::from_as_of(args.as_of))?
+    };
+
+    let format = args.format.clone().unwrap_or(if is_x12 {
+        OutputFormat::JsonPretty
+    } else {
+        OutputFormat::X12
+    });
+    if !is_x12 && matches!(format, OutputFormat::X12) {
+        for imbalance in document.claims().flat_map(balancing::check_claim) {
+            warn!("{}", imbalance);
+        }
+    }
+    let output = match format {
+        OutputFormat::X12 => document.to_interchange().to_string(),
+        OutputFormat::Json => serde_json::to_string(&document)?,
+        OutputFormat::JsonPretty => serde_json::to_string_pretty(&document)?,
+    };
+
+    match args.output {
+        Some(ref path) => fs::write(path, output)?,
+        None => io::stdout().lock().write_all(output.as_bytes())?,
+    }
+    Ok(())
+}
+
+/// The fiscal period date from TS3 or PLB, defaulting to year end.
+fn fiscal_period_date(advice: &RemittanceAdvice) -> String {
+    let summary = advice
+        .header_numbers
+        .iter()
+        .filter_map(|number| number.ts3.as_ref())
+        .map(|ts3| &ts3.ts303_fiscal_period_date);
+    let adjustments = advice
+        .provider_adjustments
+        .iter()
+        .map(|plb| &plb.plb02_fiscal_period_date);
+    match summary.chain(adjustments).next() {
+        Some(date) => date.clone(),
+        None => {
+            let payment_date = &advice.bpr.bpr16_payment_date;
+            format!("{}1231", &payment_date[..payment_date.len().min(4)])
+        }
+    }
+}
+
+/// Converts one 835 transaction to a remittance.
+fn remittance_from(advice: &RemittanceAdvice) -> std::result::Result<Remittance, String> {
+    let trace = advice.header.iter().find_map(|segment| match segment {
+        Segment::Trn(trn) => Some(trn),
+        _ => None,
+    });
+
+    let mut payer = payer_from(&advice.payer);
+    payer.originating_company_id = trace
+        .map(|trn| trn.trn03_orig_company_id.clone())
+        .unwrap_or_default();
+    payer.payment_method =
+        PaymentMethod::from_code(&advice.bpr.bpr04_payment_method).unwrap_or_default();
+    let payee = Provider {
+        npi: advice.payee.n1.n104_id.clone(),
+        tax_id: reference(&advice.payee.segments, "TJ").unwrap_or_default(),
+        name: advice.payee.n1.n102_name.clone(),
+        address: address(&advice.payee.segments),
+        ..Default::default()
+    };
+    payer.remittance_delivery = advice
+        .payee
+        .segments
+        .iter()
+        .find_map(|segment| match segment {
+            Segment::Rdm(rdm) => Some(RemittanceDelivery {
+                method: rdm.rdm01_transmission_code.clone(),
+                name: rdm.rdm02_name.clone(),
+                communication_number: rdm.rdm03_communication_number.clone(),
+            }),
+            _ => None,
+        });
+
+    let mut claims = Vec::new();
+    for number in &advice.header_numbers {
+        for claim in &number.claims {
+            claims.push(claim_from(claim, &payee, number.ts3.is_some())?);
+        }
+    }
+
+    let mut provider_adjustments = Vec::new();
+    for adjustment in advice
+        .provider_adjustments
+        .iter()
+        .flat_map(|plb| &plb.adjustments)
+    {
+        let reason =
+            ProviderAdjustmentReason::from_code(&adjustment.reason_code).ok_or_else(|| {
+                format!(
+                    "unsupported PLB adjustment reason {:?}",
+                    adjustment.reason_code
+                )
+            })?;
+        provider_adjustments.push(ProviderAdjustment {
+            reason,
+            reference_id: adjustment.reference_id.clone(),
+            amount: adjustment.amount.cents(),
+        });
+    }
+
+    Ok(Remittance {
+        payer,
+        payee,
+        payment_date: advice.bpr.bpr16_payment_date.clone(),
+        trace_number: trace
+            .map(|trn| trn.trn02_reference_id.clone())
+            .unwrap_or_default(),
+        claims,
+        provider_adjustments,
+    })
+}
+
+/// The payer described by loop 1000A.
+fn payer_from(party: &PartyLoop) -> Payer {
+    let mut payer = Payer {
+        name: party.n1.n102_name.clone(),
+        payer_id: party.n1.n104_id.clone(),
+        originating_company_id: String::new(),
+        address: address(&party.segments),
+        additional_ids: Vec::new(),
+        business_contact: None,
+        technical_contact: Contact {
+            name: None,
+            communications: Vec::new(),
+        },
+        website: None,
+        remittance_delivery: None,
+        ..Default::default()
+    };
+    for segment in &party.segments {
+        match segment {
+            Segment::Ref(reference) => payer.additional_ids.push((
+                reference.ref01_qualifier.clone(),
+                reference.ref02_identifier.clone(),
+            )),
+            Segment::Per(per) => match per.per01_function_code.as_str() {
+                "CX" => payer.business_contact = Some(contact(per)),
+                "BL" => payer.technical_contact = contact(per),
+                "IC" => {
+                    payer.website = per
+                        .communications
+                        .iter()
+                        .find(|(qualifier, _)| qualifier == "UR")
+                        .map(|(_, website)| website.clone())
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    payer
+}
+
+/// Converts a 2100 claim payment loop.
+fn claim_from(
+    claim_loop: &ClaimPaymentLoop,
+    payee: &Provider,
+    institutional: bool,
+) -> std::result::Result<Claim, String> {
+    let clp = &claim_loop.clp;
+    let mut claim = Claim {
+        claim_id: clp.clp01_claim_id.clone(),
+        patient: Person::default(),
+        billing_provider: payee.clone(),
+        rendering_provider: None,
+        service_lines: Vec::new(),
+        total_charge: cents(clp.clp03_charge_amount),
+        total_payment: cents(clp.clp04_paid_amount),
+        total_adjustment: 0,
+        patient_responsibility: cents(clp.clp05_patient_responsibility),
+        status: ClaimStatus::Paid,
+        subscriber: None,
+        corrected_member_id: None,
+        crossover_carrier: None,
+        medical_record_number: None,
+        prior_authorization: None,
+        original_reference: None,
+        received_date: String::new(),
+        coverage_expiration: None,
+        interest_amount: 0,
+        prompt_pay_discount: 0,
+        patient_amount_paid: 0,
+        covered_days: 0,
+        bill_type: institutional
+            .then(|| format!("{}{}", clp.clp08_facility_type, clp.clp09_frequency_code)),
+        claim_frequency_code: clp.clp09_frequency_code.clone(),
+        status_code: ClaimStatusCode::from_code(&clp.clp02_claim_status).unwrap_or_default(),
+        denial_reason: None,
+    };
+
+    let mut statement_from = None;
+    for segment in &claim_loop.segments {
+        match segment {
+            Segment::Nm1(nm1) => match nm1.nm101_entity_id.as_str() {
+                "QC" => claim.patient = person(nm1),
+                "IL" => claim.subscriber = Some(person(nm1)),
+                "74" => claim.corrected_member_id = nm1.nm109_id.clone(),
+                "82" => {
+                    claim.rendering_provider = Some(Provider {
+                        npi: nm1.nm109_id.clone().unwrap_or_default(),
+                        name: nm1.nm103_last_name.clone(),
+                        ..Default::default()
+                    })
+                }
+                "TT" => {
+                    claim.crossover_carrier = Some(CrossoverCarrier {
+                        name: nm1.nm103_last_name.clone(),
+                        payer_id: nm1.nm109_id.clone().unwrap_or_default(),
+                        claim_number: String::new(),
+                    })
+                }
+                _ => {}
+            },
+            Segment::Ref(reference) => {
+                let value = Some(reference.ref02_identifier.clone());
+                match reference.ref01_qualifier.as_str() {
+                    "1K" => {
+                        if let Some(ref mut carrier) = claim.crossover_carrier {
+                            carrier.claim_number = reference.ref02_identifier.clone();
+                        }
+                    }
+                    "EA" => claim.medical_record_number = value,
+                    "F8" => claim.original_reference = value,
+                    "G1" => claim.prior_authorization = value,
+                    _ => {}
+                }
+            }
+            Segment::Dtm(dtm) => match dtm.dtm01_qualifier.as_str() {
+                "232" => statement_from = Some(model_date(&dtm.dtm02_date)),
+                "036" => claim.coverage_expiration = Some(model_date(&dtm.dtm02_date)),
+                "050" => claim.received_date = model_date(&dtm.dtm02_date),
+                _ => {}
+            },
+            Segment::Amt(amt) => match amt.amt01_qualifier.as_str() {
+                "AU" => {
+                    claim.patient_responsibility =
+                        cents(amt.amt02_amount).saturating_sub(claim.total_payment)
+                }
+                "D8" => claim.prompt_pay_discount = cents(amt.amt02_amount),
+                "F5" => claim.patient_amount_paid = cents(amt.amt02_amount),
+                "I" => claim.interest_amount = cents(amt.amt02_amount),
+                _ => {}
+            },
+            Segment::Qty(qty) if qty.qty01_qualifier == "CA" => {
+                claim.covered_days = qty.qty02_quantity.round() as u32
+            }
+            _ => {}
+        }
+    }
+
+    for (i, line_loop) in claim_loop.service_lines.iter().enumerate() {
+        let line = service_line_from(line_loop, i as u32 + 1, statement_from.as_deref())?;
+        claim.total_adjustment += line.adjustment_amount;
+        claim.service_lines.push(line);
+    }
+
+    if claim.status_code.is_denial() {
+        claim.denial_reason = claim
+            .service_lines
+            .iter()
+            .flat_map(|line| &line.adjustments)
+            .find_map(|adjustment| DenialReason::from_carc(&adjustment.reason_code));
+    }
+    claim.status = ClaimStatus::of(claim.status_code, claim.total_charge, claim.total_payment);
+
+    Ok(claim)
+}
+
+/// Converts a 2110 service payment loop.
+fn service_line_from(
+    line_loop: &ServicePaymentLoop,
+    line_number: u32,
+    statement_from: Option<&str>,
+) -> std::result::Result<ServiceLine, String> {
+    let svc = &line_loop.svc;
+    let mut line = ServiceLine {
+        line_number,
+        procedure_code: svc.svc01_procedure.code.clone(),
+        procedure_description: svc.svc01_procedure.description.clone().unwrap_or_default(),
+        service_date: statement_from.unwrap_or_default().to_string(),
+        charge_amount: cents(svc.svc02_charge_amount),
+        payment_amount: cents(svc.svc03_paid_amount),
+        paid_amount: cents(svc.svc03_paid_amount),
+        adjustment_amount: 0,
+        adjustments: Vec::new(),
+        units: svc.svc05_units,
+        place_of_service: "11".to_string(),
+        revenue_code: svc.svc04_revenue_code.clone(),
+        modifiers: svc.svc01_procedure.modifiers.clone(),
+        service_end_date: None,
+        line_item_control_number: String::new(),
+        allowed_amount: 0,
+        remark_codes: Vec::new(),
+        submitted_procedure_code: svc
+            .svc06_original_procedure
+            .as_ref()
+            .map(|procedure| procedure.code.clone()),
+    };
+
+    for segment in &line_loop.segments {
+        match segment {
+            Segment::Dtm(dtm) => match dtm.dtm01_qualifier.as_str() {
+                "150" | "472" => line.service_date = model_date(&dtm.dtm02_date),
+                "151" => line.service_end_date = Some(model_date(&dtm.dtm02_date)),
+                _ => {}
+            },
+            Segment::Cas(cas) => {
+                let group = AdjustmentGroup::from_code(&cas.cas01_group_code).ok_or_else(|| {
+                    format!("unsupported CAS group code {:?}", cas.cas01_group_code)
+                })?;
+                line.adjustments
+                    .extend(cas.adjustments.iter().map(|adjustment| Adjustment {
+                        group,
+                        reason_code: adjustment.reason_code.clone(),
+                        amount: cents(adjustment.amount),
+                        quantity: adjustment.quantity,
+                    }));
+            }
+            Segment::Ref(reference) => match reference.ref01_qualifier.as_str() {
+                "LU" => line.place_of_service = reference.ref02_identifier.clone(),
+                "6R" => line.line_item_control_number = reference.ref02_identifier.clone(),
+                _ => {}
+            },
+            Segment::Amt(amt) if amt.amt01_qualifier == "B6" => {
+                line.allowed_amount = cents(amt.amt02_amount)
+            }
+            Segment::Lq(lq) if lq.lq01_code_list_qualifier == "HE" => {
+                line.remark_codes.push(lq.lq02_remark_code.clone())
+            }
+            _ => {}
+        }
+    }
+    line.adjustment_amount = line.adjustments.iter().map(|a| a.amount).sum();
+
+    Ok(line)
+}
+
+/// The person named by `nm1`.
+fn person(nm1: &Nm1Segment) -> Person {
+    Person {
+        member_id: nm1.nm109_id.clone().unwrap_or_default(),
+        first_name: nm1.nm104_first_name.clone().unwrap_or_default(),
+        last_name: nm1.nm103_last_name.clone(),
+        ..Default::default()
+    }
+}
+
+/// The contact described by `per`.
+fn contact(per: &PerSegment) -> Contact {
+    Contact {
+        name: per.per02_name.clone(),
+        communications: per.communications.clone(),
+    }
+}
+
+/// The address from N3/N4 segments.
+fn address(segments: &[Segment]) -> Address {
+    let mut address = Address::default();
+    for segment in segments {
+        match segment {
+            Segment::N3(n3) => {
+                address.line1 = n3.n301_address_line1.clone();
+                address.line2 = n3.n302_address_line2.clone();
+            }
+            Segment::N4(n4) => {
+                address.city = n4.n401_city.clone();
+                address.state = n4.n402_state.clone();
+                address.zip_code = n4.n403_postal_code.clone();
+            }
+            _ => {}
+        }
+    }
+    address
+}
+
+/// The REF02 of the first REF with `qualifier`.
+fn reference(segments: &[Segment], qualifier: &str) -> Option<String> {
+    segments.iter().find_map(|segment| match segment {
+        Segment::Ref(reference) if reference.ref01_qualifier == qualifier => {
+            Some(reference.ref02_identifier.clone())
+        }
+        _ => None,
+    })
+}
+
+/// The magnitude of `amount` in cents.
+fn cents(amount: Money) -> u64 {
+    amount.cents().unsigned_abs()
+}
+
+/// Converts a CCYYMMDD date to ISO 8601, passing anything else through.
+fn model_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ClaimsPerRemittance, Config, OutputFormat as ConfigFormat};
+    use crate::generator::generate_to_string;
+    use crate::x12::delimiters::LineEnding;
+
+    fn generate(config: Config) -> String {
+        generate_to_string(Config {
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            batch_remittances: true,
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_x12_json_round_trip_is_byte_identical() {
+        for seed in [3, 21, 58] {
+            let input = generate(Config {
+                claim_count: 40,
+                seed: Some(seed),
+                anomaly_rate: 0.3,
+                plb_rate: 0.5,
+                claims_per_remittance: ClaimsPerRemittance::Uniform { min: 1, max: 8 },
+                ..Default::default()
+            });
+
+            let document = RemittanceDocument::from_x12(&input).unwrap();
+            let reversals = document
+                .claims()
+                .filter(|claim| claim.status_code == ClaimStatusCode::Reversal)
+                .count();
+            assert_eq!(document.claims().count() - reversals, 40);
+
+            let json = serde_json::to_string_pretty(&document).unwrap();
+            let restored = RemittanceDocument::from_json(&json, Clock::system()).unwrap();
+            assert_eq!(restored.to_interchange().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_delimiters() {
+        let input = generate(Config {
+            claim_count: 5,
+            seed: Some(8),
+            delimiters: Delimiters {
+                element: '|',
+                component: '>',
+                repetition: '!',
+                segment: '\'',
+                line_ending: LineEnding::CrLf,
+            },
+            ..Default::default()
+        });
+
+        let document = RemittanceDocument::from_x12(&input).unwrap();
+        assert_eq!(document.interchange.delimiters.element, '|');
+        assert_eq!(document.to_interchange().to_string(), input);
+    }
+
+    #[test]
+    fn test_claims_fixture_converts_to_x12() {
+        let fixture = generate(Config {
+            claim_count: 6,
+            seed: Some(13),
+            anomaly_rate: 0.0,
+            output_format: ConfigFormat::Json,
+            ..Default::default()
+        });
+        let clock = Clock::fixed(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+
+        let document = RemittanceDocument::from_json(&fixture, clock).unwrap();
+        let x12 = document.to_interchange().to_string();
+        assert!(x12.contains("*240601*0000*^*00501*000000001*0*P*:~"));
+
+        let parsed = RemittanceDocument::from_x12(&x12).unwrap();
+        let expected: Vec<&Claim> = document.claims().collect();
+        let claims: Vec<&Claim> = parsed.claims().collect();
+        assert_eq!(claims.len(), 6);
+        for claim in claims {
+            let original = expected
+                .iter()
+                .find(|c| c.claim_id == claim.claim_id)
+                .unwrap();
+            assert_eq!(claim.total_charge, original.total_charge);
+            assert_eq!(claim.total_payment, original.total_payment);
+            assert_eq!(claim.patient.member_id, original.patient.member_id);
+            assert_eq!(claim.service_lines.len(), original.service_lines.len());
+        }
+        assert_eq!(parsed.to_interchange().to_string(), x12);
+    }
+
+    #[test]
+    fn test_claims_fixture_without_newer_fields_converts() {
+        let fixture = generate(Config {
+            claim_count: 3,
+            seed: Some(2),
+            anomaly_rate: 0.0,
+            output_format: ConfigFormat::Json,
+            ..Default::default()
+        });
+        let mut records: serde_json::Value = serde_json::from_str(&fixture).unwrap();
+        for record in records.as_array_mut().unwrap() {
+            let claim = record.get_mut("claim").unwrap().as_object_mut().unwrap();
+            for field in [
+                "received_date",
+                "interest_amount",
+                "prompt_pay_discount",
+                "patient_amount_paid",
+                "covered_days",
+                "claim_frequency_code",
+            ] {
+                claim.remove(field).unwrap();
+            }
+            for line in claim["service_lines"].as_array_mut().unwrap() {
+                let line = line.as_object_mut().unwrap();
+                for field in [
+                    "line_item_control_number",
+                    "allowed_amount",
+                    "modifiers",
+                    "remark_codes",
+                ] {
+                    line.remove(field).unwrap();
+                }
+            }
+        }
+        let clock = Clock::fixed(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+
+        let document = RemittanceDocument::from_json(&records.to_string(), clock).unwrap();
+        assert_eq!(document.claims().count(), 3);
+        assert!(document
+            .claims()
+            .all(|claim| claim.claim_frequency_code == "1"));
+        assert!(document.to_interchange().to_string().contains("CLP*"));
+    }
+
+    #[test]
+    fn test_claims_fixture_without_member_and_tax_ids_converts() {
+        let fixture = generate(Config {
+            claim_count: 3,
+            seed: Some(3),
+            anomaly_rate: 0.0,
+            output_format: ConfigFormat::Json,
+            ..Default::default()
+        });
+        let mut records: serde_json::Value = serde_json::from_str(&fixture).unwrap();
+        for record in records.as_array_mut().unwrap() {
+            let claim = record.get_mut("claim").unwrap().as_object_mut().unwrap();
+            claim["patient"]
+                .as_object_mut()
+                .unwrap()
+                .remove("member_id")
+                .unwrap();
+            claim["billing_provider"]
+                .as_object_mut()
+                .unwrap()
+                .remove("tax_id")
+                .unwrap();
+        }
+        let clock = Clock::fixed(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap());
+
+        let document = RemittanceDocument::from_json(&records.to_string(), clock).unwrap();
+        assert_eq!(document.claims().count(), 3);
+        assert!(document
+            .claims()
+            .all(|claim| claim.patient.member_id.is_empty()));
+        assert!(document.to_interchange().to_string().contains("CLP*"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Claim and service line denial reasons.
+
+use rand::Rng;
+use rand_distr::{Distribution, WeightedIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Every denial reason.
+pub const ALL_REASONS: &[DenialReason] = &[
+    DenialReason::Eligibility,
+    DenialReason::TimelyFiling,
+    DenialReason::NonCovered,
+    DenialReason::PriorAuthorization,
+    DenialReason::Duplicate,
+    DenialReason::WrongPayer,
+];
+
+/// Why a payer denied a claim or service line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenialReason {
+    /// The patient was not eligible on the date of service.
+    Eligibility,
+
+    /// The claim was filed after the payer's deadline.
+    TimelyFiling,
+
+    /// The service is not covered by the plan.
+    NonCovered,
+
+    /// A required prior authorization was missing.
+    PriorAuthorization,
+
+    /// The claim or service was already submitted.
+    Duplicate,
+
+    /// The claim was sent to the wrong payer.
+    WrongPayer,
+}
+
+impl DenialReason {
+    /// The name used in denial mixes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DenialReason::Eligibility => "eligibility",
+            DenialReason::TimelyFiling => "timely_filing",
+            DenialReason::NonCovered => "non_covered",
+            DenialReason::PriorAuthorization => "prior_authorization",
+            DenialReason::Duplicate => "duplicate",
+            DenialReason::WrongPayer => "wrong_payer",
+        }
+    }
+
+    /// Looks up a reason by its denial mix name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL_REASONS
+            .iter()
+            .copied()
+            .find(|reason| reason.name() == name)
+    }
+
+    /// Looks up a reason by its claim adjustment reason code.
+    pub fn from_carc(carc: &str) -> Option<Self> {
+        ALL_REASONS
+            .iter()
+            .copied()
+            .find(|reason| reason.carc() == carc)
+    }
+
+    /// The claim adjustment reason code reported for the denial.
+    pub fn carc(&self) -> &'static str {
+        match self {
+            DenialReason::Eligibility => "27",
+            DenialReason::TimelyFiling => "29",
+            DenialReason::NonCovered => "96",
+            DenialReason::PriorAuthorization => "197",
+            DenialReason::Duplicate => "18",
+            DenialReason::WrongPayer => "109",
+        }
+    }
+
+    /// The remark code reported for the denial.
+    pub fn rarc(&self) -> &'static str {
+        match self {
+            DenialReason::Eligibility => "N30",
+            DenialReason::TimelyFiling => "N211",
+            DenialReason::NonCovered => "N130",
+            DenialReason::PriorAuthorization => "M62",
+            DenialReason::Duplicate => "M86",
+            DenialReason::WrongPayer => "N418",
+        }
+    }
+
+    /// Whether the reason can deny a single service line.
+    pub fn applies_to_lines(&self) -> bool {
+        matches!(
+            self,
+            DenialReason::NonCovered | DenialReason::PriorAuthorization | DenialReason::Duplicate
+        )
+    }
+}
+
+/// Relative weights of denial reasons, parsed from `REASON=WEIGHT,...`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DenialMix(pub BTreeMap<DenialReason, f64>);
+
+impl DenialMix {
+    /// Whether no weights are set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Picks a weighted reason; an empty mix weights every reason equally.
+    pub fn choose<R: Rng>(&self, rng: &mut R, line_level: bool) -> Option<DenialReason> {
+        let reasons: Vec<DenialReason> = ALL_REASONS
+            .iter()
+            .copied()
+            .filter(|reason| !line_level || reason.applies_to_lines())
+            .collect();
+        let weights = reasons.iter().map(|reason| {
+            if self.0.is_empty() {
+                1.0
+            } else {
+                self.0.get(reason).copied().unwrap_or(0.0)
+            }
+        });
+        let index = WeightedIndex::new(weights).ok()?;
+        Some(reasons[index.sample(rng)])
+    }
+}
+
+impl FromStr for DenialMix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid denial mix: {}", s);
+        let mut weights = BTreeMap::new();
+        for entry in s.split(',') {
+            let (name, weight) = entry.split_once('=').ok_or_else(invalid)?;
+            let reason = DenialReason::from_name(name.trim())
+                .ok_or_else(|| format!("unknown denial reason {:?}", name.trim()))?;
+            let weight: f64 = weight.trim().parse().map_err(|_| invalid())?;
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(invalid());
+            }
+            weights.insert(reason, weight);
+        }
+        Ok(DenialMix(weights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeding::{stream_rng, Stream};
+
+    #[test]
+    fn test_denial_mix() {
+        let mix: DenialMix = "timely_filing=3, duplicate=1".parse().unwrap();
+        assert_eq!(mix.0.len(), 2);
+        assert!("late=1".parse::<DenialMix>().is_err());
+        assert!("duplicate=-1".parse::<DenialMix>().is_err());
+
+        let mut rng = stream_rng(Some(25), Stream::Claims);
+        for _ in 0..50 {
+            let reason = mix.choose(&mut rng, false).unwrap();
+            assert!(matches!(
+                reason,
+                DenialReason::TimelyFiling | DenialReason::Duplicate
+            ));
+            assert_eq!(mix.choose(&mut rng, true), Some(DenialReason::Duplicate));
+        }
+        let eligibility_only: DenialMix = "eligibility=1".parse().unwrap();
+        assert_eq!(eligibility_only.choose(&mut rng, true), None);
+        assert!(DenialMix::default()
+            .choose(&mut rng, true)
+            .is_some_and(|reason| reason.applies_to_lines()));
+    }
+}
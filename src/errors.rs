@@ -1,32 +1,35 @@
-
+//! The crate error type.
 
 use std::fmt;
 
-
+/// Result type using the crate [`Error`].
 pub type Result<T> = std::result::Result<T, Error>;
 
-
+/// Errors returned by the crate.
 #[derive(Debug)]
 pub enum Error {
-    
+    /// Reading or writing a file failed.
     Io(std::io::Error),
 
-    
+    /// JSON (de)serialization failed.
     Json(serde_json::Error),
 
-    
+    /// TOML serialization failed.
     Toml(toml::ser::Error),
 
-    
+    /// TOML deserialization failed.
     TomlDe(toml::de::Error),
 
-    
+    /// The configuration is invalid.
     Config(String),
 
-    
+    /// Input or output failed validation.
     Validation(String),
 
-    
+    /// X12 input could not be parsed.
+    Parse(crate::x12::parser::ParseError),
+
+    /// Data could not be generated.
     Generation(String),
 }
 
@@ -39,6 +42,7 @@ impl fmt::Display for Error {
             Error::TomlDe(e) => write!(f, "TOML deserialization error: {}", e),
             Error::Config(msg) => write!(f, "Configuration error: {}", msg),
             Error::Validation(msg) => write!(f, "Validation error: {}", msg),
+            Error::Parse(e) => write!(f, "X12 parse error: {}", e),
             Error::Generation(msg) => write!(f, "Generation error: {}", msg),
         }
     }
@@ -51,6 +55,7 @@ impl std::error::Error for Error {
             Error::Json(ref e) => Some(e),
             Error::Toml(ref e) => Some(e),
             Error::TomlDe(ref e) => Some(e),
+            Error::Parse(ref e) => Some(e),
             _ => None,
         }
     }
@@ -80,9 +85,15 @@ impl From<toml::de::Error> for Error {
     }
 }
 
+impl From<crate::x12::parser::ParseError> for Error {
+    fn from(err: crate::x12::parser::ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
 
+/// Adds context to errors from other crates.
 pub trait Context<T, E> {
-    
+    /// Converts the error into a configuration error prefixed with `context`.
     fn context<C>(self, context: C) -> Result<T>
     where
         C: fmt::Display + Send + Sync + 'static;
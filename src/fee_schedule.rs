@@ -0,0 +1,138 @@
+//! Fee schedules that price service lines.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Medicare physician fee schedule conversion factor, in dollars per RVU.
+pub const CONVERSION_FACTOR: f64 = 32.3465;
+
+/// A fee schedule rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fee {
+    /// A flat allowed amount in cents.
+    Amount(u64),
+
+    /// Relative value units, priced with [`CONVERSION_FACTOR`].
+    Rvu(f64),
+}
+
+impl Fee {
+    /// The allowed amount for one unit, in cents.
+    pub fn cents(&self) -> u64 {
+        match *self {
+            Fee::Amount(cents) => cents,
+            Fee::Rvu(rvu) => (rvu * CONVERSION_FACTOR * 100.0).round().max(0.0) as u64,
+        }
+    }
+}
+
+/// Allowed amounts by procedure code, modifier and locality.
+#[derive(Debug, Clone)]
+pub struct FeeSchedule {
+    fees: HashMap<(String, String, String), Fee>,
+}
+
+impl FeeSchedule {
+    /// Loads `fee_schedule.csv` from `data_path`, falling back to a few office visit rates.
+    pub fn load(data_path: &Path) -> Self {
+        #[derive(Debug, Deserialize)]
+        struct CsvFee {
+            procedure_code: String,
+            modifier: Option<String>,
+            locality: Option<String>,
+            allowed_amount: Option<u64>,
+            rvu: Option<f64>,
+        }
+
+        let mut fees = HashMap::new();
+        if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("fee_schedule.csv")) {
+            for rec in rdr.deserialize::<CsvFee>().flatten() {
+                let fee = match (rec.allowed_amount, rec.rvu) {
+                    (Some(cents), _) => Fee::Amount(cents),
+                    (None, Some(rvu)) => Fee::Rvu(rvu),
+                    (None, None) => continue,
+                };
+                let key = (
+                    rec.procedure_code,
+                    rec.modifier.unwrap_or_default(),
+                    rec.locality.unwrap_or_default(),
+                );
+                fees.insert(key, fee);
+            }
+        }
+        if fees.is_empty() {
+            for (code, rvu) in [
+                ("99211", 0.67),
+                ("99212", 1.65),
+                ("99213", 2.65),
+                ("99214", 3.75),
+                ("99215", 5.27),
+            ] {
+                fees.insert(
+                    (code.to_string(), String::new(), String::new()),
+                    Fee::Rvu(rvu),
+                );
+            }
+        }
+
+        Self { fees }
+    }
+
+    /// The rate for `code`, preferring a modifier-specific rate and then the locality rate
+    /// over the national one.
+    pub fn fee(&self, code: &str, modifiers: &[String], locality: &str) -> Option<Fee> {
+        let lookup = |modifier: &str, locality: &str| {
+            self.fees
+                .get(&(code.to_string(), modifier.to_string(), locality.to_string()))
+                .copied()
+        };
+        modifiers
+            .iter()
+            .find_map(|modifier| lookup(modifier, locality).or_else(|| lookup(modifier, "")))
+            .or_else(|| lookup("", locality))
+            .or_else(|| lookup("", ""))
+    }
+
+    /// The allowed amount in cents for `units` of `code`.
+    pub fn allowed(
+        &self,
+        code: &str,
+        modifiers: &[String],
+        locality: &str,
+        units: f64,
+    ) -> Option<u64> {
+        self.fee(code, modifiers, locality)
+            .map(|fee| (fee.cents() as f64 * units.max(1.0)).round() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_lookup_falls_back_to_national_rate() {
+        let schedule = FeeSchedule::load(Path::new("data"));
+        let national = schedule.allowed("71046", &[], "TX", 1.0).unwrap();
+        let local = schedule.allowed("71046", &[], "NY", 1.0).unwrap();
+        let professional = schedule
+            .allowed("71046", &["26".to_string()], "NY", 1.0)
+            .unwrap();
+
+        assert!(local > national);
+        assert!(professional < national);
+        assert_eq!(schedule.allowed("36415", &[], "CA", 1.0), Some(300));
+        assert_eq!(schedule.allowed("36415", &[], "CA", 2.0), Some(600));
+        assert!(schedule.allowed("00000", &[], "CA", 1.0).is_none());
+    }
+
+    #[test]
+    fn test_rvu_fees_use_conversion_factor() {
+        let schedule = FeeSchedule::load(Path::new("does-not-exist"));
+
+        assert_eq!(Fee::Rvu(1.0).cents(), 3235);
+        assert_eq!(schedule.fee("99213", &[], "CA"), Some(Fee::Rvu(2.65)));
+        assert_eq!(schedule.allowed("99213", &[], "CA", 1.0), Some(8572));
+    }
+}
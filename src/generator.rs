@@ -1,40 +1,82 @@
+//! Generation of complete X12 835 interchanges.
 
-
-use crate::anomalies::{Anomaly, AnomalyConfig, AnomalyInjectionResult, AnomalyInjector};
-use crate::claims::{Claim, ClaimGenerator};
+use crate::adjustments::{Adjustment, AdjustmentGroup, ProviderAdjustment};
+use crate::anomalies::{Anomaly, AnomalyConfig, AnomalyInjector};
+use crate::benefits;
+use crate::claims::{Claim, ClaimGenerator, ClaimStatus};
+use crate::clock::Clock;
 use crate::config::{Config, OutputFormat};
-use crate::population::PopulationGenerator;
+use crate::conformance::{self, Finding, Severity};
+use crate::money::Money;
+use crate::payers::{Payer, PayerRegistry};
+use crate::population::{Person, PopulationGenerator, Provider};
+use crate::remittance::{group_claims, Remittance};
+use crate::seeding::{stream_rng, Stream};
+use crate::submission;
 
 
+use crate::x12::control_numbers::ControlNumbers;
 use crate::x12::envelope::{FunctionalGroup, TransactionSet, X12Interchange};
+use crate::x12::parser::{detect_delimiters, tokenize};
 use crate::x12::segments::{
-    BprSegment, CasSegment, ClpSegment, DtmSegment, N1Segment, SvcSegment, TrnSegment,
+    AmtSegment, BprSegment, CasAdjustment, CasSegment, ClpSegment, DtmSegment, LqSegment,
+    LxSegment, MedicalProcedure, N1Segment, N3Segment, N4Segment, Nm1Segment, PerSegment,
+    PlbAdjustment, PlbSegment, ProductServiceQualifier, QtySegment, RdmSegment, RefSegment,
+    SvcSegment, TrnSegment, Ts2Segment, Ts3Segment,
 };
-use chrono::Utc;
+use chrono::{Duration, NaiveDate};
 use rand::Rng;
+use rand_distr::{Distribution, WeightedIndex};
 use serde::Serialize;
 use std::{
-    fs::File,
+    collections::{BTreeMap, HashSet},
+    fs::{self, File},
     io::{self, Write},
     path::Path,
-    time::{SystemTime, UNIX_EPOCH},
 };
 
+/// Interchange sender ID (ISA06).
+pub const SENDER_ID: &str = "SENDER001";
+
+/// Interchange receiver ID (ISA08) and GS03.
+pub const RECEIVER_ID: &str = "RECEIVER01";
+
+/// Chance that a claim is for a member seen before, so accumulators carry over.
+const RETURNING_MEMBER_RATE: f64 = 0.5;
+
+/// Most members kept for reuse.
+const MEMBER_POOL_SIZE: usize = 5_000;
+
+/// Chance that a paid claim is preceded by a reversal (CLP02 22).
+const REVERSAL_RATE: f64 = 0.02;
+
+/// Not otherwise classified codes, which carry a description in SVC01-7.
+const NOT_OTHERWISE_CLASSIFIED: &[&str] = &[
+    "A9999", "E1399", "J3490", "J3590", "J9999", "17999", "64999", "99499",
+];
 
+/// Generates claims and writes them as X12 835 or JSON.
 pub struct Generator {
     config: Config,
     pop_generator: PopulationGenerator,
     claim_generator: ClaimGenerator,
     anomaly_injector: AnomalyInjector,
+    payers: Vec<Payer>,
+    payer_mix: WeightedIndex<f64>,
+    members: Vec<(Person, Option<Person>)>,
+    rng: rand_chacha::ChaCha8Rng,
+    clock: Clock,
+    control_numbers: ControlNumbers,
 }
 
 impl Generator {
-    
+    /// Creates a generator from `config`.
     pub fn new(config: Config) -> Self {
         let seed = config.seed;
+        let clock = Clock::from_as_of(config.as_of);
 
-        let pop_generator = PopulationGenerator::new(seed);
-        let claim_generator = ClaimGenerator::new(seed);
+        let pop_generator = PopulationGenerator::new(seed, &config.data_dir).with_clock(clock);
+        let claim_generator = ClaimGenerator::new(seed, &config.data_dir).with_clock(clock);
 
         
         let anomaly_config = AnomalyConfig {
@@ -44,15 +86,40 @@ impl Generator {
 
         let anomaly_injector = AnomalyInjector::new(anomaly_config, seed);
 
+        let rng = stream_rng(seed, Stream::Remittances);
+
+        
+        let registry = PayerRegistry::from_config(&config).unwrap_or_else(|e| {
+            log::warn!("{}; using the default payer", e);
+            PayerRegistry::new(Vec::new())
+        });
+        let payer_mix = registry.mix(&config.payer_mix).unwrap_or_else(|e| {
+            log::warn!("{}; assigning claims to payers uniformly", e);
+            registry.mix(&Default::default()).unwrap()
+        });
+        let mut payers = registry.payers().to_vec();
+        for payer in payers
+            .iter_mut()
+            .filter(|payer| payer.denial_mix.is_empty())
+        {
+            payer.denial_mix = config.denial_mix.clone();
+        }
+
         Self {
             config,
             pop_generator,
             claim_generator,
             anomaly_injector,
+            payers,
+            payer_mix,
+            members: Vec::new(),
+            rng,
+            clock,
+            control_numbers: ControlNumbers::default(),
         }
     }
 
-    
+    /// Generates to the configured output path, or stdout.
     pub fn generate(&mut self) -> io::Result<()> {
         
         let output_path = self.config.output_path.clone();
@@ -62,21 +129,21 @@ impl Generator {
         }
     }
 
-    
+    /// Generates to the file at `path`.
     pub fn generate_to_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
         let file = File::create(path)?;
         self.generate_and_serialize(Box::new(file))
     }
 
-    
+    /// Generates to stdout.
     pub fn generate_to_stdout(&mut self) -> io::Result<()> {
         let stdout = io::stdout();
         let handle = stdout.lock();
         self.generate_and_serialize(Box::new(handle))
     }
 
-    
-    fn generate_and_serialize(&mut self, writer: Box<dyn Write>) -> io::Result<()> {
+    /// Writes the configured output format to `writer`.
+    fn generate_and_serialize(&mut self, writer: Box<dyn Write + '_>) -> io::Result<()> {
         match self.config.output_format {
             OutputFormat::X12 => self.generate_x12(writer),
             OutputFormat::Json => self.generate_json(writer, false),
@@ -84,147 +151,615 @@ impl Generator {
         }
     }
 
-    
-    pub fn generate_to_writer(&mut self, writer: Box<dyn Write>) -> io::Result<()> {
+    /// Generates to `writer`.
+    pub fn generate_to_writer(&mut self, writer: Box<dyn Write + '_>) -> io::Result<()> {
         self.generate_and_serialize(writer)
     }
 
-    
-    fn generate_x12(&mut self, mut writer: Box<dyn Write>) -> io::Result<()> {
+    /// Writes an X12 interchange with one functional group per payer.
+    fn generate_x12(&mut self, mut writer: Box<dyn Write + '_>) -> io::Result<()> {
         
-        let control_number = Self::generate_control_number();
-        let mut interchange = X12Interchange::new("SENDER001", "RECEIVER01", &control_number);
+        if let Some(ref path) = self.config.control_number_file {
+            self.control_numbers =
+                ControlNumbers::load(path).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+        let interchange_control_number = self.control_numbers.next_interchange();
+        let now = self.clock.now();
+        let time = now.format("%H%M").to_string();
+        let mut interchange = X12Interchange::new(
+            SENDER_ID,
+            RECEIVER_ID,
+            &interchange_control_number,
+            &now.format("%y%m%d").to_string(),
+            &time,
+        );
+        interchange.delimiters = self.config.delimiters;
 
         
-        let mut group = FunctionalGroup::new(
-            "SENDER001",
-            "RECEIVER01",
-            &control_number,
-            &Utc::now().format("%Y%m%d").to_string(),
-            &Utc::now().format("%H%M").to_string(),
-        );
+        let mut remittances = Vec::new();
+        let mut remaining = self.config.claim_count;
+        while remaining > 0 {
+            for remittance in self.generate_remittances(remaining) {
+                remaining -= remittance.claims.len();
+                remittances.push(remittance);
+            }
+        }
+
+        
+        benefits::apply_benefits(remittances.iter_mut().flat_map(|r| r.claims.iter_mut()));
 
         
-        for _ in 0..self.config.claim_count {
-            let claim_result = self.generate_single_claim();
-            let transaction = self.create_835_transaction(&claim_result.claim);
-            group.add_transaction_set(transaction);
+        let mut batches: Vec<(String, Vec<(Remittance, bool)>)> = Vec::new();
+        for mut remittance in remittances {
+            let anomalous = self.finish_remittance(&mut remittance);
+            let payer_id = remittance.payer.payer_id.clone();
+            match batches.iter_mut().find(|(id, _)| *id == payer_id) {
+                Some((_, batch)) => batch.push((remittance, anomalous)),
+                None => batches.push((payer_id, vec![(remittance, anomalous)])),
+            }
         }
 
         
-        interchange.add_functional_group(group);
+        let mut anomalous_transactions = HashSet::new();
+        let mut submitted = Vec::new();
+        for (payer_id, batch) in batches {
+            let group_control_number = self.control_numbers.next_group();
+            let mut group = FunctionalGroup::new(
+                &payer_id,
+                RECEIVER_ID,
+                &group_control_number,
+                &now.format("%Y%m%d").to_string(),
+                &time,
+            );
+            for (remittance, anomalous) in batch {
+                let transaction = self.create_835_transaction(&remittance);
+                if anomalous {
+                    anomalous_transactions.insert((
+                        group_control_number.clone(),
+                        transaction.st.st02_control_number.clone(),
+                    ));
+                }
+                group.add_transaction_set(transaction);
+                if self.config.claims_output.is_some() {
+                    submitted.push(remittance);
+                }
+            }
+            interchange.add_functional_group(group);
+        }
 
         
-        write!(&mut *writer, "{}", interchange)?;
+        let output = interchange.to_string();
+        if self.config.self_check {
+            self_check(&output, &self.config.data_dir, &anomalous_transactions)?;
+        }
+        writer.write_all(output.as_bytes())?;
+
+        
+        if let Some(ref path) = self.config.claims_output {
+            let mut claims =
+                submission::build_interchange(&submitted, self.clock, &mut self.control_numbers);
+            claims.delimiters = self.config.delimiters;
+            fs::write(path, claims.to_string())?;
+        }
+        if let Some(ref path) = self.config.control_number_file {
+            self.control_numbers
+                .save(path)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
         Ok(())
     }
 
-    
-    fn create_835_transaction(&self, claim: &Claim) -> TransactionSet {
-        let control_number = Self::generate_control_number();
-        let mut transaction = TransactionSet::new(&control_number);
+    /// Builds the 835 for `remittance` with the next ST control number.
+    fn create_835_transaction(&mut self, remittance: &Remittance) -> TransactionSet {
+        let control_number = self.control_numbers.next_transaction();
+        let fiscal_period_date = self.clock.fiscal_period_end().format("%Y%m%d").to_string();
+        Self::build_835_transaction(remittance, &control_number, &fiscal_period_date)
+    }
+
+    /// Builds the 835 transaction set for `remittance`.
+    pub fn build_835_transaction(
+        remittance: &Remittance,
+        control_number: &str,
+        fiscal_period_date: &str,
+    ) -> TransactionSet {
+        let mut transaction = TransactionSet::new(control_number);
 
         
+        let payment_amount = Money::from(remittance.payment_amount());
+        let payment_method = if payment_amount.is_zero() {
+            "NON"
+        } else {
+            remittance.payer.payment_method.code()
+        };
         let bpr = BprSegment {
-            bpr02_payment_amount: (claim.total_payment as f64) / 100.0,
+            bpr02_payment_amount: payment_amount,
             bpr03_credit_debit: 'C',
-            bpr04_payment_method: "ACH".to_string(),
-            bpr16_payment_date: Utc::now().format("%Y%m%d").to_string(),
+            bpr04_payment_method: payment_method.to_string(),
+            bpr10_originating_company_id: remittance.payer.originating_company_id.clone(),
+            bpr16_payment_date: remittance.payment_date.clone(),
         };
         transaction.add_segment(bpr);
 
         
         let trn = TrnSegment {
-            trn02_reference_id: claim.claim_id.clone(),
-            trn03_orig_company_id: "PAYER123".to_string(),
+            trn02_reference_id: remittance.trace_number.clone(),
+            trn03_orig_company_id: remittance.payer.originating_company_id.clone(),
         };
         transaction.add_segment(trn);
 
         
         let dtm = DtmSegment {
             dtm01_qualifier: "405".to_string(),
-            dtm02_date: Utc::now().format("%Y%m%d").to_string(),
+            dtm02_date: remittance.payment_date.clone(),
         };
         transaction.add_segment(dtm);
 
         
-        let n1_payer = N1Segment {
-            n101_entity_id: "PR".to_string(),
-            n102_name: "PAYER NAME".to_string(),
-            n103_id_qual: "PI".to_string(),
-            n104_id: "1234567890".to_string(),
-        };
-        transaction.add_segment(n1_payer);
+        Self::add_payer_loop(&mut transaction, &remittance.payer);
 
         
-        let n1_payee = N1Segment {
-            n101_entity_id: "PE".to_string(),
-            n102_name: claim.billing_provider.name.clone(),
-            n103_id_qual: "XX".to_string(),
-            n104_id: claim.billing_provider.npi.clone(),
+        Self::add_payee_loop(&mut transaction, &remittance.payer, &remittance.payee);
+
+        
+        
+        let mut header_groups: Vec<((bool, &str), Vec<&Claim>)> = Vec::new();
+        for claim in &remittance.claims {
+            let key = (claim.is_institutional(), claim.facility_type_code());
+            match header_groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, claims)) => claims.push(claim),
+                None => header_groups.push((key, vec![claim])),
+            }
+        }
+        for (i, ((_, facility_type), claims)) in header_groups.iter().enumerate() {
+            transaction.add_segment(LxSegment {
+                lx01_assigned_number: i as u32 + 1,
+            });
+            Self::add_provider_summary(
+                &mut transaction,
+                &remittance.payee,
+                fiscal_period_date,
+                facility_type,
+                claims,
+            );
+            for claim in claims {
+                Self::add_claim(&mut transaction, &remittance.payer, claim);
+            }
+        }
+
+        
+        Self::add_provider_adjustments(
+            &mut transaction,
+            &remittance.payee,
+            fiscal_period_date,
+            &remittance.provider_adjustments,
+        );
+
+        transaction
+    }
+
+    /// Adds TS3, and TS2 for inpatient claims, when every claim is institutional.
+    fn add_provider_summary(
+        transaction: &mut TransactionSet,
+        payee: &Provider,
+        fiscal_period_date: &str,
+        facility_type: &str,
+        claims: &[&Claim],
+    ) {
+        if !claims.iter().all(|claim| claim.is_institutional()) {
+            return;
+        }
+
+        let lines = || {
+            claims
+                .iter()
+                .flat_map(|claim| claim.service_lines.iter().map(move |line| (*claim, line)))
+        };
+        let total_charge: Money = claims
+            .iter()
+            .map(|claim| claim.signed(claim.total_charge))
+            .sum();
+        let non_lab_charges: Money = lines()
+            .filter(|(_, line)| !is_lab_procedure(&line.procedure_code))
+            .map(|(claim, line)| claim.signed(line.charge_amount))
+            .sum();
+        let hcpcs_lines = || {
+            lines().filter(|(_, line)| {
+                medical_procedure(&line.procedure_code, &line.modifiers, "").qualifier
+                    == ProductServiceQualifier::Hcpcs
+            })
         };
-        transaction.add_segment(n1_payee);
+        let hcpcs_charges: Money = hcpcs_lines()
+            .map(|(claim, line)| claim.signed(line.charge_amount))
+            .sum();
+        let hcpcs_payable: Money = hcpcs_lines()
+            .map(|(claim, line)| claim.signed(line.paid_amount))
+            .sum();
+
+        transaction.add_segment(Ts3Segment {
+            ts301_provider_id: payee.npi.clone(),
+            ts302_facility_type: facility_type.to_string(),
+            ts303_fiscal_period_date: fiscal_period_date.to_string(),
+            ts304_claim_count: claims.len() as u32,
+            ts305_total_charge: total_charge,
+            ts315_non_lab_charges: Some(non_lab_charges),
+            ts317_hcpcs_reported_charges: Some(hcpcs_charges),
+            ts318_hcpcs_payable: Some(hcpcs_payable),
+            ..Default::default()
+        });
 
         
+        if facility_type == "11" {
+            let drg_amount: Money = lines()
+                .map(|(claim, line)| claim.signed(line.allowed_amount))
+                .sum();
+            let covered_days: u32 = claims.iter().map(|claim| claim.covered_days).sum();
+            let discharges = claims.len() as u32;
+            let average_length_of_stay =
+                (covered_days as f64 / discharges as f64 * 10.0).round() / 10.0;
+            transaction.add_segment(Ts2Segment {
+                ts201_drg_amount: Some(drg_amount),
+                ts210_average_length_of_stay: Some(average_length_of_stay),
+                ts211_discharges: Some(discharges),
+                ts212_cost_report_days: Some(covered_days),
+                ts213_covered_days: Some(covered_days),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Adds loop 2100 and its service lines for `claim`.
+    fn add_claim(transaction: &mut TransactionSet, payer: &Payer, claim: &Claim) {
         let clp = ClpSegment {
             clp01_claim_id: claim.claim_id.clone(),
-            clp02_claim_status: "1".to_string(), 
-            clp03_charge_amount: (claim.total_charge as f64) / 100.0,
-            clp04_paid_amount: (claim.total_payment as f64) / 100.0,
-            clp05_patient_responsibility: (claim.patient_responsibility as f64) / 100.0,
-            clp06_claim_type: "11".to_string(), 
+            clp02_claim_status: claim.status_code.code().to_string(),
+            clp03_charge_amount: claim.signed(claim.total_charge),
+            clp04_paid_amount: claim.signed(claim.total_payment),
+            clp05_patient_responsibility: claim.signed(claim.patient_responsibility),
+            clp06_claim_type: "12".to_string(), 
             clp07_payer_claim_number: format!("CLM{}", claim.claim_id),
+            clp08_facility_type: claim.facility_type_code().to_string(),
+            clp09_frequency_code: claim.claim_frequency_code.clone(),
         };
         transaction.add_segment(clp);
 
         
-        for (_, service_line) in claim.service_lines.iter().enumerate() {
+        Self::add_claim_details(transaction, payer, claim);
+
+        
+        let (statement_from, statement_to) = statement_period(claim);
+        for service_line in &claim.service_lines {
             let svc = SvcSegment {
-                svc01_procedure_code: service_line.procedure_code.clone(),
-                svc02_charge_amount: (service_line.charge_amount as f64) / 100.0,
-                svc03_paid_amount: (service_line.paid_amount as f64) / 100.0,
+                svc01_procedure: medical_procedure(
+                    &service_line.procedure_code,
+                    &service_line.modifiers,
+                    &service_line.procedure_description,
+                ),
+                svc02_charge_amount: claim.signed(service_line.charge_amount),
+                svc03_paid_amount: claim.signed(service_line.paid_amount),
                 svc04_revenue_code: service_line.revenue_code.clone(),
-                svc05_units: service_line.units as f64,
+                svc05_units: service_line.units,
+                svc06_original_procedure: service_line
+                    .submitted_procedure_code
+                    .as_ref()
+                    .map(|code| medical_procedure(code, &service_line.modifiers, "")),
+                svc07_original_units: None,
             };
             transaction.add_segment(svc);
 
             
-            if service_line.adjustment_amount > 0 {
-                let cas = CasSegment {
-                    cas01_group_code: "CO".to_string(),
-                    cas02_reason_code: "45".to_string(),
-                    cas03_amount: (service_line.adjustment_amount as f64) / 100.0,
-                    cas04_units: service_line.units,
-                };
-                transaction.add_segment(cas);
+            match service_line.service_end_date {
+                Some(ref end) if *end != service_line.service_date => {
+                    transaction.add_segment(DtmSegment {
+                        dtm01_qualifier: "150".to_string(),
+                        dtm02_date: x12_date(&service_line.service_date),
+                    });
+                    transaction.add_segment(DtmSegment {
+                        dtm01_qualifier: "151".to_string(),
+                        dtm02_date: x12_date(end),
+                    });
+                }
+                _ if statement_from != statement_to
+                    || statement_from != Some(&service_line.service_date) =>
+                {
+                    transaction.add_segment(DtmSegment {
+                        dtm01_qualifier: "472".to_string(),
+                        dtm02_date: x12_date(&service_line.service_date),
+                    });
+                }
+                _ => {}
+            }
+
+            
+            Self::add_adjustments(transaction, claim, &service_line.adjustments);
+
+            
+            if service_line.place_of_service != "11" {
+                transaction.add_segment(RefSegment {
+                    ref01_qualifier: "LU".to_string(),
+                    ref02_identifier: service_line.place_of_service.clone(),
+                });
+            }
+            transaction.add_segment(RefSegment {
+                ref01_qualifier: "6R".to_string(),
+                ref02_identifier: service_line.line_item_control_number.clone(),
+            });
+
+            
+            if service_line.allowed_amount > 0 {
+                transaction.add_segment(AmtSegment {
+                    amt01_qualifier: "B6".to_string(),
+                    amt02_amount: claim.signed(service_line.allowed_amount),
+                });
+            }
+            for remark in &service_line.remark_codes {
+                transaction.add_segment(LqSegment {
+                    lq01_code_list_qualifier: "HE".to_string(),
+                    lq02_remark_code: remark.clone(),
+                });
             }
         }
+    }
 
-        transaction
+    /// Adds loop 1000A for `payer`.
+    fn add_payer_loop(transaction: &mut TransactionSet, payer: &Payer) {
+        transaction.add_segment(N1Segment {
+            n101_entity_id: "PR".to_string(),
+            n102_name: payer.name.clone(),
+            n103_id_qual: "XV".to_string(),
+            n104_id: payer.payer_id.clone(),
+        });
+        transaction.add_segment(N3Segment {
+            n301_address_line1: payer.address.line1.clone(),
+            n302_address_line2: payer.address.line2.clone(),
+        });
+        transaction.add_segment(N4Segment {
+            n401_city: payer.address.city.clone(),
+            n402_state: payer.address.state.clone(),
+            n403_postal_code: payer.address.zip_code.clone(),
+        });
+        for (qualifier, id) in payer.additional_ids.iter().take(4) {
+            transaction.add_segment(RefSegment {
+                ref01_qualifier: qualifier.clone(),
+                ref02_identifier: id.clone(),
+            });
+        }
+        if let Some(ref contact) = payer.business_contact {
+            transaction.add_segment(PerSegment {
+                per01_function_code: "CX".to_string(),
+                per02_name: contact.name.clone(),
+                communications: contact.communications.clone(),
+            });
+        }
+        transaction.add_segment(PerSegment {
+            per01_function_code: "BL".to_string(),
+            per02_name: payer.technical_contact.name.clone(),
+            communications: payer.technical_contact.communications.clone(),
+        });
+        if let Some(ref website) = payer.website {
+            transaction.add_segment(PerSegment {
+                per01_function_code: "IC".to_string(),
+                per02_name: None,
+                communications: vec![("UR".to_string(), website.clone())],
+            });
+        }
     }
 
-    
-    fn generate_control_number() -> String {
-        let start = SystemTime::now();
-        let since_epoch = start
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let random: u32 = rand::thread_rng().gen();
-        format!("{:08}{:08}", since_epoch % 100000000, random % 10000)
+    /// Adds loop 1000B for `payee`.
+    fn add_payee_loop(transaction: &mut TransactionSet, payer: &Payer, payee: &Provider) {
+        transaction.add_segment(N1Segment {
+            n101_entity_id: "PE".to_string(),
+            n102_name: payee.name.clone(),
+            n103_id_qual: "XX".to_string(),
+            n104_id: payee.npi.clone(),
+        });
+        transaction.add_segment(N3Segment {
+            n301_address_line1: payee.address.line1.clone(),
+            n302_address_line2: payee.address.line2.clone(),
+        });
+        transaction.add_segment(N4Segment {
+            n401_city: payee.address.city.clone(),
+            n402_state: payee.address.state.clone(),
+            n403_postal_code: payee.address.zip_code.clone(),
+        });
+        transaction.add_segment(RefSegment {
+            ref01_qualifier: "TJ".to_string(),
+            ref02_identifier: payee.tax_id.clone(),
+        });
+        transaction.add_segment(RefSegment {
+            ref01_qualifier: "PQ".to_string(),
+            ref02_identifier: payer.payee_id(payee),
+        });
+        if let Some(ref delivery) = payer.remittance_delivery {
+            transaction.add_segment(RdmSegment {
+                rdm01_transmission_code: delivery.method.clone(),
+                rdm02_name: delivery.name.clone(),
+                rdm03_communication_number: delivery.communication_number.clone(),
+            });
+        }
+    }
+
+    /// Adds the loop 2100 segments between CLP and the claim CAS.
+    fn add_claim_details(transaction: &mut TransactionSet, payer: &Payer, claim: &Claim) {
+        transaction.add_segment(person_name("QC", &claim.patient, &claim.patient.member_id));
+        if let Some(ref subscriber) = claim.subscriber {
+            transaction.add_segment(person_name("IL", subscriber, &subscriber.member_id));
+        }
+        if let Some(ref corrected_id) = claim.corrected_member_id {
+            let insured = claim.subscriber.as_ref().unwrap_or(&claim.patient);
+            let mut nm1 = person_name("74", insured, corrected_id);
+            nm1.nm108_id_qualifier = Some("C".to_string());
+            transaction.add_segment(nm1);
+        }
+        if let Some(ref rendering) = claim.rendering_provider {
+            if rendering.npi != claim.billing_provider.npi {
+                transaction.add_segment(Nm1Segment {
+                    nm101_entity_id: "82".to_string(),
+                    nm102_entity_type: "2".to_string(),
+                    nm103_last_name: rendering.name.clone(),
+                    nm104_first_name: None,
+                    nm105_middle_name: None,
+                    nm107_suffix: None,
+                    nm108_id_qualifier: Some("XX".to_string()),
+                    nm109_id: Some(rendering.npi.clone()),
+                });
+            }
+        }
+        if let Some(ref carrier) = claim.crossover_carrier {
+            transaction.add_segment(Nm1Segment {
+                nm101_entity_id: "TT".to_string(),
+                nm102_entity_type: "2".to_string(),
+                nm103_last_name: carrier.name.clone(),
+                nm104_first_name: None,
+                nm105_middle_name: None,
+                nm107_suffix: None,
+                nm108_id_qualifier: Some("PI".to_string()),
+                nm109_id: Some(carrier.payer_id.clone()),
+            });
+        }
+
+        
+        let crossover_claim_number = claim.crossover_carrier.as_ref().map(|c| &c.claim_number);
+        let references = [
+            ("1K", crossover_claim_number),
+            ("EA", claim.medical_record_number.as_ref()),
+            ("F8", claim.original_reference.as_ref()),
+            ("G1", claim.prior_authorization.as_ref()),
+        ];
+        for (qualifier, value) in references {
+            if let Some(value) = value {
+                transaction.add_segment(RefSegment {
+                    ref01_qualifier: qualifier.to_string(),
+                    ref02_identifier: value.clone(),
+                });
+            }
+        }
+
+        
+        if let (Some(from), Some(to)) = statement_period(claim) {
+            transaction.add_segment(DtmSegment {
+                dtm01_qualifier: "232".to_string(),
+                dtm02_date: x12_date(from),
+            });
+            transaction.add_segment(DtmSegment {
+                dtm01_qualifier: "233".to_string(),
+                dtm02_date: x12_date(to),
+            });
+        }
+        if let Some(ref expiration) = claim.coverage_expiration {
+            transaction.add_segment(DtmSegment {
+                dtm01_qualifier: "036".to_string(),
+                dtm02_date: x12_date(expiration),
+            });
+        }
+        if !claim.received_date.is_empty() {
+            transaction.add_segment(DtmSegment {
+                dtm01_qualifier: "050".to_string(),
+                dtm02_date: x12_date(&claim.received_date),
+            });
+        }
+
+        
+        if claim.crossover_carrier.is_some() || claim.status == ClaimStatus::Denied {
+            if let Some(ref contact) = payer.business_contact {
+                transaction.add_segment(PerSegment {
+                    per01_function_code: "CX".to_string(),
+                    per02_name: contact.name.clone(),
+                    communications: contact.communications.clone(),
+                });
+            }
+        }
+
+        
+        let amounts = [
+            ("AU", claim.total_payment + claim.patient_responsibility),
+            ("D8", claim.prompt_pay_discount),
+            ("F5", claim.patient_amount_paid),
+            ("I", claim.interest_amount),
+        ];
+        for (qualifier, amount) in amounts {
+            if amount > 0 {
+                transaction.add_segment(AmtSegment {
+                    amt01_qualifier: qualifier.to_string(),
+                    amt02_amount: claim.signed(amount),
+                });
+            }
+        }
+        if claim.covered_days > 0 {
+            transaction.add_segment(QtySegment {
+                qty01_qualifier: "CA".to_string(),
+                qty02_quantity: claim.covered_days as f64,
+            });
+        }
     }
 
-    
-    fn generate_json(&mut self, mut writer: Box<dyn Write>, pretty: bool) -> io::Result<()> {
-        let mut claims = Vec::with_capacity(self.config.claim_count);
+    /// Adds CAS segments for `adjustments`, one or more per group.
+    fn add_adjustments(
+        transaction: &mut TransactionSet,
+        claim: &Claim,
+        adjustments: &[Adjustment],
+    ) {
+        let mut groups: BTreeMap<AdjustmentGroup, Vec<&Adjustment>> = BTreeMap::new();
+        for adjustment in adjustments.iter().filter(|a| a.amount > 0) {
+            groups.entry(adjustment.group).or_default().push(adjustment);
+        }
+
+        for (group, adjustments) in groups {
+            for chunk in adjustments.chunks(CasSegment::MAX_ADJUSTMENTS) {
+                transaction.add_segment(CasSegment {
+                    cas01_group_code: group.code().to_string(),
+                    adjustments: chunk
+                        .iter()
+                        .map(|a| CasAdjustment {
+                            reason_code: a.reason_code.clone(),
+                            amount: claim.signed(a.amount),
+                            quantity: a.quantity,
+                        })
+                        .collect(),
+                });
+            }
+        }
+    }
 
-        for _ in 0..self.config.claim_count {
-            let result = self.generate_single_claim();
-            claims.push(JsonOutput {
-                claim: result.claim,
-                anomalies: result.anomalies,
+    /// Adds PLB segments for the provider level adjustments.
+    fn add_provider_adjustments(
+        transaction: &mut TransactionSet,
+        payee: &Provider,
+        fiscal_period_date: &str,
+        adjustments: &[ProviderAdjustment],
+    ) {
+        for chunk in adjustments.chunks(PlbSegment::MAX_ADJUSTMENTS) {
+            transaction.add_segment(PlbSegment {
+                plb01_provider_id: payee.npi.clone(),
+                plb02_fiscal_period_date: fiscal_period_date.to_string(),
+                adjustments: chunk
+                    .iter()
+                    .map(|a| PlbAdjustment {
+                        reason_code: a.reason.code().to_string(),
+                        reference_id: a.reference_id.clone(),
+                        amount: Money::from(a.amount),
+                    })
+                    .collect(),
             });
         }
+    }
+
+    /// Writes the generated claims as a JSON array.
+    fn generate_json(&mut self, mut writer: Box<dyn Write + '_>, pretty: bool) -> io::Result<()> {
+        let mut claims: Vec<Claim> = (0..self.config.claim_count)
+            .map(|_| self.generate_single_claim())
+            .collect();
+        benefits::apply_benefits(&mut claims);
+
+        let claims: Vec<JsonOutput> = claims
+            .into_iter()
+            .map(|claim| {
+                let result = self.anomaly_injector.inject_anomalies(claim);
+                JsonOutput {
+                    claim: result.claim,
+                    anomalies: result.anomalies,
+                }
+            })
+            .collect();
 
         let writer_ref = &mut *writer;
         if pretty {
@@ -236,39 +771,258 @@ impl Generator {
         Ok(())
     }
 
-    
-    fn generate_single_claim(&mut self) -> AnomalyInjectionResult {
+    /// Generates claims for one payer and payee, grouped into remittances.
+    fn generate_remittances(&mut self, max_claims: usize) -> Vec<Remittance> {
+        let claim_count = if self.config.batch_remittances {
+            self.config
+                .claims_per_remittance
+                .sample(&mut self.rng)
+                .min(max_claims)
+        } else {
+            1
+        };
+
         
-        let patient = self.pop_generator.generate_person();
+        let payer = self.choose_payer();
+        let payee = self.pop_generator.generate_provider();
+        let claims = (0..claim_count)
+            .map(|_| self.generate_claim_for(payee.clone(), &payer))
+            .collect();
+
+        let mut remittances = group_claims(&payer, "", claims);
+        for remittance in &mut remittances {
+            remittance.payment_date = self.payment_date(&payer, &remittance.claims);
+            remittance.trace_number = format!("{:010}", self.rng.gen_range(0..10_000_000_000u64));
+        }
+        remittances
+    }
+
+    /// Adds reversals, anomalies and provider adjustments; returns whether any anomaly was
+    /// injected.
+    fn finish_remittance(&mut self, remittance: &mut Remittance) -> bool {
+        let mut anomalous = false;
+        let mut claims = Vec::with_capacity(remittance.claims.len());
+        for claim in std::mem::take(&mut remittance.claims) {
+            if claim.status_code.is_primary()
+                && claim.total_payment > 0
+                && self.rng.gen_bool(REVERSAL_RATE)
+            {
+                claims.push(claim.reversal());
+            }
+            claims.push(claim);
+        }
+        remittance.claims = claims
+            .into_iter()
+            .map(|claim| {
+                let result = self.anomaly_injector.inject_anomalies(claim);
+                anomalous |= !result.anomalies.is_empty();
+                result.claim
+            })
+            .collect();
+        remittance.provider_adjustments = self
+            .claim_generator
+            .generate_provider_adjustments(&remittance.claims, self.config.plb_rate);
+        anomalous
+    }
+
+    /// Generates one adjudicated claim for JSON output.
+    fn generate_single_claim(&mut self) -> Claim {
+        let payer = self.choose_payer();
         let billing_provider = self.pop_generator.generate_provider();
-        let rendering_provider = if rand::random() {
+        self.generate_claim_for(billing_provider, &payer)
+    }
+
+    /// Picks a payer from the payer mix.
+    fn choose_payer(&mut self) -> Payer {
+        self.payers[self.payer_mix.sample(&mut self.rng)].clone()
+    }
+
+    /// The payment date: the latest received date plus the payer's lag, capped at today.
+    fn payment_date(&mut self, payer: &Payer, claims: &[Claim]) -> String {
+        let today = self.clock.today();
+        let received = claims
+            .iter()
+            .filter_map(|claim| NaiveDate::parse_from_str(&claim.received_date, "%Y-%m-%d").ok())
+            .max()
+            .unwrap_or(today);
+        let lag = Duration::days(payer.payment_lag.sample(&mut self.rng));
+        (received + lag).min(today).format("%Y%m%d").to_string()
+    }
+
+    /// Picks a returning member or generates a new patient and subscriber.
+    fn choose_member(&mut self) -> (Person, Option<Person>) {
+        if !self.members.is_empty() && self.rng.gen_bool(RETURNING_MEMBER_RATE) {
+            let index = self.rng.gen_range(0..self.members.len());
+            return self.members[index].clone();
+        }
+        let patient = self.pop_generator.generate_person();
+        let subscriber = self.pop_generator.generate_subscriber_for(&patient);
+        let member = (patient, subscriber);
+        if self.members.len() < MEMBER_POOL_SIZE {
+            self.members.push(member.clone());
+        } else {
+            let index = self.rng.gen_range(0..self.members.len());
+            self.members[index] = member.clone();
+        }
+        member
+    }
+
+    /// Generates a claim billed by `billing_provider` and adjudicates it for `payer`.
+    fn generate_claim_for(&mut self, billing_provider: Provider, payer: &Payer) -> Claim {
+        
+        let (patient, subscriber) = self.choose_member();
+        let rendering_provider = if self.rng.gen_bool(0.5) {
             Some(self.pop_generator.generate_provider())
         } else {
             None
         };
 
         
-        let claim =
+        let mut claim =
             self.claim_generator
                 .generate_claim(patient, billing_provider, rendering_provider);
+        claim.subscriber = subscriber;
+        self.claim_generator.adjudicate(&mut claim, payer);
+        claim
+    }
+}
 
-        
-        self.anomaly_injector.inject_anomalies(claim)
+/// Fails when `output` has conformance errors outside the transactions with injected
+/// anomalies.
+fn self_check(
+    output: &str,
+    data_path: &Path,
+    anomalous_transactions: &HashSet<(String, String)>,
+) -> io::Result<()> {
+    let delimiters = detect_delimiters(output).unwrap_or_default();
+    let mut group = String::new();
+    let groups: Vec<String> = tokenize(output, &delimiters)
+        .iter()
+        .map(|raw| {
+            if raw.id == "GS" {
+                group = raw.elements.get(5).map_or("", |e| e.value()).to_string();
+            }
+            group.clone()
+        })
+        .collect();
+    let violations: Vec<Finding> = conformance::validate(output, data_path)
+        .into_iter()
+        .filter(|f| f.severity == Severity::Error)
+        .filter(|f| {
+            let group = groups.get(f.segment.wrapping_sub(1)).cloned();
+            !f.transaction
+                .clone()
+                .zip(group)
+                .is_some_and(|(t, g)| anomalous_transactions.contains(&(g, t)))
+        })
+        .collect();
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let mut message = format!(
+        "self-check found {} unintended conformance errors",
+        violations.len()
+    );
+    for finding in violations.iter().take(10) {
+        message.push_str("\n  ");
+        message.push_str(&finding.to_string());
     }
+    Err(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// An NM1 for `person` with a member ID.
+fn person_name(entity_id: &str, person: &Person, id: &str) -> Nm1Segment {
+    Nm1Segment {
+        nm101_entity_id: entity_id.to_string(),
+        nm102_entity_type: "1".to_string(),
+        nm103_last_name: person.last_name.clone(),
+        nm104_first_name: Some(person.first_name.clone()),
+        nm105_middle_name: None,
+        nm107_suffix: None,
+        nm108_id_qualifier: Some("MI".to_string()),
+        nm109_id: Some(id.to_string()),
+    }
+}
+
+/// The SVC01/SVC06 composite for `code`, with its qualifier inferred from the code format.
+pub fn medical_procedure(code: &str, modifiers: &[String], description: &str) -> MedicalProcedure {
+    let digits = code.chars().filter(|c| c.is_ascii_digit()).count();
+    let qualifier = if digits == 11 && code.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        ProductServiceQualifier::Ndc
+    } else if code.len() == 5 && code.starts_with('D') && digits == 4 {
+        ProductServiceQualifier::Ada
+    } else if code.len() == 4 && digits == 4 {
+        ProductServiceQualifier::Nubc
+    } else {
+        ProductServiceQualifier::Hcpcs
+    };
+
+    let description = (NOT_OTHERWISE_CLASSIFIED.contains(&code) && !description.is_empty())
+        .then(|| description.chars().take(80).collect());
+
+    MedicalProcedure {
+        qualifier,
+        code: code.to_string(),
+        modifiers: modifiers.iter().take(4).cloned().collect(),
+        description,
+    }
+}
+
+/// Whether `code` is a laboratory CPT code (80000-89999).
+pub fn is_lab_procedure(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('8') && code.chars().all(|c| c.is_ascii_digit())
 }
 
+/// The earliest service date and latest service end date of `claim`.
+pub fn statement_period(claim: &Claim) -> (Option<&String>, Option<&String>) {
+    let from = claim.service_lines.iter().map(|l| &l.service_date).min();
+    let to = claim
+        .service_lines
+        .iter()
+        .map(|l| l.service_end_date.as_ref().unwrap_or(&l.service_date))
+        .max();
+    (from, to)
+}
+
+/// Converts a YYYY-MM-DD date to CCYYMMDD.
+pub fn x12_date(date: &str) -> String {
+    date.replace('-', "")
+}
 
+/// One claim of the JSON output.
 #[derive(Debug, Serialize)]
 struct JsonOutput {
     claim: Claim,
     anomalies: Vec<Anomaly>,
 }
 
+/// Generates `config` into a string.
+#[cfg(test)]
+pub(crate) fn generate_to_string(config: Config) -> String {
+    let mut output = Vec::new();
+    Generator::new(config)
+        .generate_to_writer(Box::new(&mut output))
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::adjustments::ProviderAdjustmentReason;
+    use crate::config::ClaimsPerRemittance;
+    use crate::payers::PaymentMethod;
+    use chrono::NaiveDate;
     use tempfile::NamedTempFile;
 
+    fn remittance_for(claim: &Claim, provider_adjustments: Vec<ProviderAdjustment>) -> Remittance {
+        let mut remittances = group_claims(&Payer::default(), "20240315", vec![claim.clone()]);
+        let mut remittance = remittances.remove(0);
+        remittance.trace_number = "0000000001".to_string();
+        remittance.provider_adjustments = provider_adjustments;
+        remittance
+    }
+
     #[test]
     fn test_generator_creation() {
         let config = Config::default();
@@ -337,4 +1091,620 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_x12_header_loops() {
+        let config = Config {
+            claim_count: 1,
+            seed: Some(7),
+            output_format: crate::config::OutputFormat::X12,
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let claim = generator.generate_single_claim();
+        let transaction = generator
+            .create_835_transaction(&remittance_for(&claim, Vec::new()))
+            .to_string();
+
+        assert!(transaction.contains("N1*PR*PAYER NAME*XV*1234567890~"));
+        for reference in [
+            "REF*2U*99999~",
+            "REF*EO*PAYER001~",
+            "REF*HI*HIN12345~",
+            "REF*NF*12345~",
+        ] {
+            assert!(transaction.contains(reference), "{}", reference);
+        }
+        assert!(transaction.contains("PER*BL*EDI SUPPORT*TE*8005551313*EM*"));
+        assert!(transaction.contains("PER*IC**UR*"));
+        assert!(transaction.contains(&format!(
+            "N1*PE*{}*XX*{}~",
+            claim.billing_provider.name, claim.billing_provider.npi
+        )));
+        assert!(transaction.contains(&format!("REF*TJ*{}~", claim.billing_provider.tax_id)));
+        assert!(transaction.contains(&format!("N4*{}*", claim.billing_provider.address.city)));
+
+        let payee = transaction.find("N1*PE").unwrap();
+        let rdm = transaction.find("RDM*BM*BILLING OFFICE~").unwrap();
+        let clp = transaction.find("CLP*").unwrap();
+        assert!(payee < rdm && rdm < clp);
+    }
+
+    #[test]
+    fn test_x12_claim_level_segments() {
+        let config = Config {
+            claim_count: 1,
+            seed: Some(11),
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let mut claim = generator.generate_single_claim();
+        let mut subscriber = claim.patient.clone();
+        subscriber.first_name = "SUBSCRIBER".to_string();
+        subscriber.member_id = "W000000001".to_string();
+        claim.subscriber = Some(subscriber);
+        claim.medical_record_number = Some("MR0000001".to_string());
+        claim.interest_amount = 125;
+
+        let transaction = generator
+            .create_835_transaction(&remittance_for(&claim, Vec::new()))
+            .to_string();
+
+        assert!(transaction.contains(&format!(
+            "NM1*QC*1*{}*{}****MI*{}~",
+            claim.patient.last_name, claim.patient.first_name, claim.patient.member_id
+        )));
+        assert!(transaction.contains("*SUBSCRIBER****MI*W000000001~"));
+        assert!(transaction.contains("REF*EA*MR0000001~"));
+        assert!(transaction.contains("DTM*232*"));
+        assert!(transaction.contains("DTM*233*"));
+        assert!(transaction.contains("AMT*I*1.25~"));
+        assert!(!transaction.contains("AMT*F5*0.00"));
+
+        
+        let clp = transaction.find("CLP*").unwrap();
+        let patient = transaction.find("NM1*QC").unwrap();
+        let svc = transaction.find("SVC*").unwrap();
+        assert!(clp < patient && patient < svc);
+    }
+
+    #[test]
+    fn test_x12_denied_claim_references() {
+        let output = generate_to_string(Config {
+            claim_count: 40,
+            seed: Some(12),
+            anomaly_rate: 0.0,
+            self_check: true,
+            payers: vec![Payer {
+                denial_rate: 1.0,
+                ..Default::default()
+            }],
+            denial_mix: "eligibility=1,wrong_payer=1".parse().unwrap(),
+            ..Default::default()
+        });
+
+        let claims: Vec<&str> = output.split("\nCLP*").skip(1).collect();
+        let crossovers: Vec<&&str> = claims.iter().filter(|c| c.contains("NM1*TT*")).collect();
+        assert!(!crossovers.is_empty());
+        for claim in crossovers {
+            let carrier = claim.find("NM1*TT*").unwrap();
+            let reference = claim.find("\nREF*1K*").expect("crossover without REF*1K");
+            assert!(carrier < reference);
+        }
+
+        let expirations: Vec<&&str> = claims.iter().filter(|c| c.contains("DTM*036*")).collect();
+        assert!(!expirations.is_empty());
+        for claim in expirations {
+            let date = |qualifier: &str| {
+                let start = claim.find(qualifier).unwrap() + qualifier.len();
+                &claim[start..start + 8]
+            };
+            assert!(date("DTM*036*") < date("DTM*232*"));
+        }
+    }
+
+    #[test]
+    fn test_x12_service_line_details() {
+        let config = Config {
+            claim_count: 1,
+            seed: Some(5),
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let mut claim = generator.generate_single_claim();
+        claim.service_lines.truncate(1);
+        let line = &mut claim.service_lines[0];
+        line.service_date = "2024-03-01".to_string();
+        line.service_end_date = Some("2024-03-04".to_string());
+        line.place_of_service = "21".to_string();
+        line.line_item_control_number = "LINE-0001".to_string();
+        line.allowed_amount = 12345;
+        line.remark_codes = vec!["N130".to_string()];
+
+        let transaction = generator
+            .create_835_transaction(&remittance_for(&claim, Vec::new()))
+            .to_string();
+
+        assert!(transaction.contains("DTM*150*20240301~\nDTM*151*20240304~"));
+        assert!(!transaction.contains("DTM*472"));
+        assert!(transaction.contains("REF*LU*21~"));
+        assert!(transaction.contains("REF*6R*LINE-0001~"));
+        assert!(transaction.contains("AMT*B6*123.45~"));
+        assert!(transaction.contains("LQ*HE*N130~"));
+
+        
+        let svc = transaction.find("SVC*").unwrap();
+        let control = transaction.find("REF*6R").unwrap();
+        let remark = transaction.find("LQ*HE").unwrap();
+        assert!(svc < control && control < remark);
+    }
+
+    #[test]
+    fn test_x12_inpatient_service_periods() {
+        let output = generate_to_string(Config {
+            claim_count: 200,
+            seed: Some(15),
+            anomaly_rate: 0.0,
+            self_check: true,
+            ..Default::default()
+        });
+
+        let periods: Vec<&str> = output
+            .split("\nSVC*")
+            .filter(|line| line.contains("\nDTM*150*"))
+            .collect();
+        assert!(!periods.is_empty());
+        for line in periods {
+            let date = |qualifier: &str| {
+                let start = line.find(qualifier).unwrap() + qualifier.len();
+                &line[start..start + 8]
+            };
+            assert!(line.contains("\nDTM*151*"), "DTM*150 without DTM*151");
+            assert!(date("\nDTM*150*") < date("\nDTM*151*"));
+        }
+    }
+
+    #[test]
+    fn test_x12_adjustment_groups() {
+        let config = Config {
+            claim_count: 1,
+            seed: Some(11),
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let mut claim = generator.generate_single_claim();
+        claim.service_lines.truncate(1);
+        let mut adjustments = vec![
+            Adjustment::new(AdjustmentGroup::PatientResponsibility, "3", 2000),
+            Adjustment::new(AdjustmentGroup::Contractual, "45", 4550),
+            Adjustment::new(AdjustmentGroup::PatientResponsibility, "2", 1000),
+            Adjustment::new(AdjustmentGroup::Other, "23", 0),
+        ];
+        for reason in ["1", "2", "3", "45", "59", "97"] {
+            adjustments.push(Adjustment::new(AdjustmentGroup::Correction, reason, 100));
+        }
+        claim.service_lines[0].adjustments = adjustments;
+
+        let transaction = generator
+            .create_835_transaction(&remittance_for(&claim, Vec::new()))
+            .to_string();
+
+        assert!(transaction.contains("CAS*CO*45*45.50~\nCAS*PR*3*20.00**2*10.00~"));
+        assert!(transaction.contains("CAS*CR*1*1.00**2*1.00**3*1.00**45*1.00**59*1.00**97*1.00~"));
+        assert!(!transaction.contains("CAS*OA"));
+    }
+
+    #[test]
+    fn test_x12_provider_level_adjustments() {
+        let config = Config {
+            claim_count: 1,
+            seed: Some(13),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let mut claim = generator.generate_single_claim();
+        claim.total_payment = 50000;
+        claim.billing_provider.npi = "1234567890".to_string();
+        let adjustments = vec![
+            ProviderAdjustment {
+                reason: ProviderAdjustmentReason::Interest,
+                reference_id: Some(claim.claim_id.clone()),
+                amount: -1250,
+            },
+            ProviderAdjustment {
+                reason: ProviderAdjustmentReason::OverpaymentRecovery,
+                reference_id: Some("CLM00000001".to_string()),
+                amount: 10000,
+            },
+        ];
+
+        let transaction = generator
+            .create_835_transaction(&remittance_for(&claim, adjustments))
+            .to_string();
+
+        assert!(transaction.starts_with("ST*835*"));
+        assert!(transaction.contains("BPR*C*412.50*"));
+        let plb = format!(
+            "PLB*1234567890*20241231*L6:{}*-12.50*WO:CLM00000001*100.00~",
+            claim.claim_id
+        );
+        assert!(transaction.contains(&plb));
+        assert!(transaction.find("PLB*").unwrap() > transaction.rfind("SVC*").unwrap());
+    }
+
+    #[test]
+    fn test_batched_remittances() {
+        let config = Config {
+            claim_count: 10,
+            seed: Some(17),
+            anomaly_rate: 0.0,
+            plb_rate: 1.0,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Fixed(4),
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let mut sizes = Vec::new();
+        let mut remaining = 10;
+        while remaining > 0 {
+            for remittance in generator.generate_remittances(remaining) {
+                remaining -= remittance.claims.len();
+                sizes.push(remittance.claims.len());
+
+                assert!(remittance
+                    .claims
+                    .iter()
+                    .all(|c| c.billing_provider.npi == remittance.payee.npi));
+
+                let transaction = generator.create_835_transaction(&remittance).to_string();
+                assert_eq!(
+                    transaction.matches("\nCLP*").count(),
+                    remittance.claims.len()
+                );
+                assert_eq!(transaction.matches("\nBPR*").count(), 1);
+                assert!(transaction.contains(&format!(
+                    "BPR*C*{}*",
+                    Money::from(remittance.payment_amount())
+                )));
+            }
+        }
+        assert_eq!(sizes, vec![4, 4, 2]);
+    }
+
+    #[test]
+    fn test_x12_header_number_loops() {
+        let config = Config {
+            claim_count: 3,
+            seed: Some(19),
+            anomaly_rate: 0.0,
+            ..Default::default()
+        };
+
+        let mut generator = Generator::new(config);
+        let payee = generator.pop_generator.generate_provider();
+        let mut claims: Vec<Claim> = (0..3)
+            .map(|_| generator.generate_claim_for(payee.clone(), &Payer::default()))
+            .collect();
+        claims[0].bill_type = Some("111".to_string());
+        claims[0].covered_days = 4;
+        claims[1].bill_type = None;
+        for line in &mut claims[1].service_lines {
+            line.place_of_service = "11".to_string();
+        }
+        claims[2].bill_type = Some("111".to_string());
+        claims[2].covered_days = 3;
+
+        let mut remittance = group_claims(&Payer::default(), "20240315", claims).remove(0);
+        remittance.trace_number = "0000000001".to_string();
+        let transaction = generator.create_835_transaction(&remittance).to_string();
+
+        assert_eq!(transaction.matches("\nLX*").count(), 2);
+        assert_eq!(transaction.matches("\nTS3*").count(), 1);
+        assert!(transaction.contains(&format!("\nTS3*{}*11*", payee.npi)));
+        assert!(transaction.contains("*3.5*2*7*7~"));
+
+        let first = transaction.find("LX*1~").unwrap();
+        let second = transaction.find("LX*2~").unwrap();
+        let ts3 = transaction.find("TS3*").unwrap();
+        let ts2 = transaction.find("TS2*").unwrap();
+        assert!(first < ts3 && ts3 < ts2 && ts2 < second);
+        assert_eq!(transaction[first..second].matches("\nCLP*").count(), 2);
+        assert_eq!(transaction[second..].matches("\nCLP*").count(), 1);
+    }
+
+    #[test]
+    fn test_x12_dates_follow_as_of() {
+        let output = generate_to_string(Config {
+            claim_count: 20,
+            seed: Some(5),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            ..Default::default()
+        });
+
+        assert!(output.starts_with("ISA*"));
+        assert!(output.contains("*240315*0000*^*"));
+        assert!(output.contains("*RECEIVER01*20240315*0000*"));
+        let payment_dates: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("DTM*405*"))
+            .map(|date| date.trim_end_matches('~'))
+            .collect();
+        assert!(!payment_dates.is_empty());
+        for date in payment_dates {
+            assert!(date > "20231216" && date <= "20240315", "{}", date);
+            assert!(output.contains(&format!("*{}~\nTRN*", date)));
+        }
+        let service_dates: Vec<&str> = output
+            .lines()
+            .filter(|line| {
+                ["DTM*472", "DTM*150", "DTM*151", "DTM*232", "DTM*233"]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix))
+            })
+            .map(|line| line[8..].trim_end_matches('~'))
+            .collect();
+        assert!(!service_dates.is_empty());
+        assert!(service_dates
+            .iter()
+            .all(|date| *date >= "20231216" && *date < "20240315"));
+    }
+
+    #[test]
+    fn test_seeded_output_is_reproducible() {
+        let render = |seed: u64, output_format: OutputFormat| {
+            generate_to_string(Config {
+                claim_count: 25,
+                anomaly_rate: 0.2,
+                seed: Some(seed),
+                as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+                output_format,
+                batch_remittances: true,
+                ..Default::default()
+            })
+            .into_bytes()
+        };
+
+        let digest = |bytes: &[u8]| {
+            bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+        };
+
+        let golden: [(OutputFormat, u64); 2] = [
+            (OutputFormat::X12, 0x75aa_d050_299b_c871),
+            (OutputFormat::Json, 0xc4ad_aa18_4b9e_ab0f),
+        ];
+        for (output_format, expected) in golden {
+            let first = render(42, output_format);
+            assert!(!first.is_empty());
+            assert_eq!(first, render(42, output_format));
+            assert_ne!(first, render(43, output_format));
+            assert_eq!(
+                format!("{:#018x}", digest(&first)),
+                format!("{:#018x}", expected),
+                "seed 42 output changed for {:?}; update the golden digest if this is intended",
+                output_format
+            );
+        }
+    }
+
+    #[test]
+    fn test_x12_control_numbers() {
+        let state_file = NamedTempFile::new().unwrap();
+        std::fs::remove_file(state_file.path()).unwrap();
+        let render = || {
+            generate_to_string(Config {
+                claim_count: 6,
+                seed: Some(11),
+                batch_remittances: true,
+                claims_per_remittance: ClaimsPerRemittance::Fixed(2),
+                control_number_file: Some(state_file.path().to_owned()),
+                payers: vec![Payer::default()],
+                ..Default::default()
+            })
+        };
+
+        let first = render();
+        let isa: Vec<&str> = first.lines().next().unwrap().split('*').collect();
+        assert_eq!(isa[13], "000000001");
+        assert!(first.contains("*1*X*005010X221A1~"));
+        assert!(first.contains("IEA*1*000000001~"));
+        let st02: Vec<&str> = first
+            .lines()
+            .filter_map(|line| line.strip_prefix("ST*835*"))
+            .filter_map(|rest| rest.split('*').next())
+            .collect();
+        let se02: Vec<&str> = first
+            .lines()
+            .filter_map(|line| line.strip_prefix("SE*"))
+            .filter_map(|rest| rest.trim_end_matches('~').split('*').nth(1))
+            .collect();
+        assert_eq!(st02, ["0001", "0002", "0003"]);
+        assert_eq!(se02, st02);
+
+        let second = render();
+        assert!(second.contains("*^*00501*000000002*0*P*:~"));
+        assert!(second.contains("GE*3*2~"));
+    }
+
+    #[test]
+    fn test_self_check() {
+        let output = generate_to_string(Config {
+            claim_count: 40,
+            seed: Some(8),
+            anomaly_rate: 0.0,
+            plb_rate: 0.5,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Fixed(4),
+            self_check: true,
+            ..Default::default()
+        });
+
+        let broken = output.replacen("CLP*", "CLP*X*", 1);
+        let error = self_check(&broken, Path::new("data"), &HashSet::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("[SNIP 2]"), "{}", error);
+
+        let first = HashSet::from([("1".to_string(), "0001".to_string())]);
+        assert!(self_check(&broken, Path::new("data"), &first).is_ok());
+
+        generate_to_string(Config {
+            claim_count: 40,
+            seed: Some(8),
+            anomaly_rate: 0.5,
+            self_check: true,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn test_x12_split_per_payer() {
+        let denying = Payer {
+            name: "DENYING PLAN".to_string(),
+            payer_id: "55555".to_string(),
+            payment_method: PaymentMethod::Check,
+            denial_rate: 1.0,
+            ..Default::default()
+        };
+        let generous = Payer {
+            name: "GENEROUS PLAN".to_string(),
+            payer_id: "77777".to_string(),
+            fee_schedule_multiplier: 2.0,
+            ..Default::default()
+        };
+        let output = generate_to_string(Config {
+            claim_count: 60,
+            seed: Some(21),
+            anomaly_rate: 0.0,
+            plb_rate: 0.0,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Fixed(3),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            self_check: true,
+            payers: vec![Payer::default(), denying, generous],
+            payer_mix: "55555=1,77777=1".parse().unwrap(),
+            ..Default::default()
+        });
+
+        let mut senders: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("GS*HP*"))
+            .filter_map(|rest| rest.split('*').next())
+            .collect();
+        senders.sort();
+        assert_eq!(senders, ["55555", "77777"]);
+
+        let mut generous_paid = Vec::new();
+        for transaction in output.split("ST*835*").skip(1) {
+            let paid: Vec<&str> = transaction
+                .lines()
+                .filter_map(|line| line.strip_prefix("CLP*"))
+                .filter_map(|rest| rest.split('*').nth(3))
+                .collect();
+            assert!(!paid.is_empty());
+            if transaction.contains("N1*PR*DENYING PLAN*XV*55555~") {
+                assert!(transaction.contains("BPR*H*0.00*C*NON************"));
+                assert!(paid.iter().all(|amount| *amount == "0.00"));
+            } else {
+                assert!(transaction.contains("N1*PR*GENEROUS PLAN*XV*77777~"));
+                if paid.iter().any(|amount| *amount != "0.00") {
+                    assert!(transaction.contains("*C*ACH*CCP*"));
+                }
+                generous_paid.extend(paid);
+            }
+        }
+        assert!(generous_paid.iter().any(|amount| *amount != "0.00"));
+    }
+
+    #[test]
+    fn test_fee_schedule_follows_data_dir() {
+        let data_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            data_dir.path().join("fee_schedule.csv"),
+            "procedure_code,modifier,locality,allowed_amount,rvu\n99213,,,4321,\n",
+        )
+        .unwrap();
+        let output = generate_to_string(Config {
+            claim_count: 20,
+            seed: Some(23),
+            anomaly_rate: 0.0,
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            data_dir: data_dir.path().to_owned(),
+            ..Default::default()
+        });
+
+        let allowed: Vec<&str> = output
+            .lines()
+            .filter(|segment| segment.starts_with("AMT*B6*"))
+            .collect();
+        assert!(!allowed.is_empty());
+        assert!(
+            allowed.iter().all(|amt| *amt == "AMT*B6*43.21~"),
+            "{:?}",
+            allowed
+        );
+    }
+
+    #[test]
+    fn test_x12_claim_statuses() {
+        let output = generate_to_string(Config {
+            claim_count: 400,
+            seed: Some(25),
+            anomaly_rate: 0.0,
+            batch_remittances: true,
+            self_check: true,
+            payers: vec![Payer {
+                denial_rate: 0.2,
+                ..Default::default()
+            }],
+            denial_mix: "duplicate=1".parse().unwrap(),
+            ..Default::default()
+        });
+
+        let claims: Vec<&str> = output.split("\nCLP*").skip(1).collect();
+        let fields = |claim: &str| -> Vec<String> {
+            claim
+                .lines()
+                .next()
+                .unwrap()
+                .split('*')
+                .map(str::to_string)
+                .collect()
+        };
+        let statuses: HashSet<String> = claims
+            .iter()
+            .map(|claim| fields(claim)[1].clone())
+            .collect();
+        for status in ["1", "2", "4", "19", "22"] {
+            assert!(
+                statuses.contains(status),
+                "no CLP02 {} in {:?}",
+                status,
+                statuses
+            );
+        }
+
+        for (i, claim) in claims.iter().enumerate() {
+            let clp = fields(claim);
+            match clp[1].as_str() {
+                "4" => {
+                    assert_eq!(clp[3], "0.00");
+                    assert!(claim.contains("CAS*CO*18*") && claim.contains("LQ*HE*M86~"));
+                }
+                "22" => {
+                    let corrected = fields(claims[i + 1]);
+                    assert_eq!(corrected[0], clp[0]);
+                    assert_eq!(format!("-{}", corrected[3]), clp[3]);
+                }
+                _ => {}
+            }
+        }
+    }
 }
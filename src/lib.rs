@@ -1,33 +1,54 @@
-
+//! Synthetic X12 835 remittance generation, conversion and conformance checking.
 
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
 
+pub mod ack;
+pub mod adjustments;
 pub mod anomalies;
+pub mod balancing;
+pub mod benefits;
 pub mod claims;
 pub mod cli;
+pub mod clock;
 pub mod config;
+pub mod conformance;
+pub mod convert;
+pub mod denials;
 pub mod errors;
+pub mod fee_schedule;
 pub mod generator;
+pub mod money;
+pub mod payers;
 pub mod population;
+pub mod remittance;
+pub mod seeding;
+pub mod submission;
 pub mod x12;
 
-
 pub use {
     anomalies::AnomalyInjector,
     claims::Claim,
+    clock::Clock,
     config::Config,
+    conformance::{ConformanceResult, Finding, Severity, SnipLevel},
+    convert::RemittanceDocument,
     errors::Error,
     generator::Generator,
+    money::Money,
+    payers::Payer,
     population::{Person, Provider},
+    remittance::Remittance,
     x12::{
+        delimiters::{Delimiters, LineEnding},
         envelope::{FunctionalGroup, TransactionSet, X12Interchange},
         segments::{
             BprSegment, ClpSegment, DtmSegment, GeSegment, GsSegment, IeaSegment, IsaSegment,
-            N1Segment, SeSegment, StSegment, SvcSegment, TrnSegment, X12Segment,
+            N1Segment, N3Segment, N4Segment, PerSegment, RdmSegment, RefSegment, SeSegment,
+            StSegment, SvcSegment, Ta1Segment, TrnSegment, X12Segment,
         },
     },
 };
 
-
+/// Result type used throughout the crate.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,7 +1,4 @@
-
-
-
-
+//! Command line interface for generating, converting, validating and acknowledging X12 835 files.
 
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
@@ -11,24 +8,17 @@ use log::{error, info};
 use std::process;
 use std::time::Instant;
 
-mod anomalies;
-mod claims;
-mod cli;
-mod config;
-mod conformance;
-mod errors;
-mod generator;
-mod population;
-mod x12;
-
-use crate::{
-    cli::{Cli, Command, ConformanceArgs, GenerateArgs},
-    config::Config,
-    errors::Result,
+use zedi_gen::{
+    ack,
+    cli::{Cli, Command},
+    config::{Config, OutputFormat},
+    conformance, convert,
+    errors::{Error, Result},
     generator::Generator,
+    payers::PayerRegistry,
 };
 
-
+/// Parses the command line and runs the chosen subcommand.
 fn main() -> Result<()> {
     
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
@@ -45,7 +35,7 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-
+/// Runs the subcommand of `cli`.
 fn run(cli: Cli) -> Result<()> {
     match cli.command {
         Command::Generate(gen) => {
@@ -53,6 +43,17 @@ fn run(cli: Cli) -> Result<()> {
 
             
             let config = Config::from_cli(&gen);
+            config.delimiters.validate().map_err(Error::Config)?;
+            if config.self_check && config.output_format != OutputFormat::X12 {
+                return Err(Error::Config("--self-check requires X12 output".to_string()));
+            }
+            if config.claims_output.is_some() && config.output_format != OutputFormat::X12 {
+                return Err(Error::Config("--claims-output requires X12 output".to_string()));
+            }
+            PayerRegistry::from_config(&config)
+                .map_err(Error::Config)?
+                .mix(&config.payer_mix)
+                .map_err(Error::Config)?;
 
             info!(
                 "Generating {} claims with {:.2}% anomalies...",
@@ -79,14 +80,16 @@ fn run(cli: Cli) -> Result<()> {
             );
             Ok(())
         }
-        Command::Conformance(conf) => conformance::run(&conf.input_path),
+        Command::Conformance(conf) => conformance::run(&conf),
+        Command::Convert(args) => convert::run(&args),
+        Command::Ack(args) => ack::run(&args),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::{Command, GenerateArgs, OutputFormat};
+    use zedi_gen::cli::{GenerateArgs, LineEnding, OutputFormat};
     use std::path::PathBuf;
 
     #[test]
@@ -98,6 +101,20 @@ mod tests {
             seed: Some(42),
             data_dir: PathBuf::from("data"),
             format: OutputFormat::Json,
+            plb_rate: 0.05,
+            batch: true,
+            claims_per_remittance: "2-4".parse().unwrap(),
+            element_separator: '*',
+            component_separator: ':',
+            repetition_separator: '^',
+            segment_terminator: '~',
+            line_ending: LineEnding::Lf,
+            as_of: None,
+            control_number_file: None,
+            self_check: false,
+            claims_output: None,
+            payer_mix: None,
+            denial_mix: None,
         };
         let cli = Cli {
             command: Command::Generate(gen),
@@ -0,0 +1,163 @@
+//! Signed monetary amounts in cents.
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A signed amount of money in cents, written with two decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    /// Zero dollars.
+    pub const ZERO: Money = Money(0);
+
+    /// An amount of `cents`.
+    pub fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    /// The amount in cents.
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    /// The amount in cents, or zero when negative.
+    pub fn unsigned_cents(self) -> u64 {
+        u64::try_from(self.0).unwrap_or(0)
+    }
+
+    /// Whether the amount is zero.
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u64> for Money {
+    fn from(cents: u64) -> Self {
+        Money(cents as i64)
+    }
+}
+
+impl From<i64> for Money {
+    fn from(cents: i64) -> Self {
+        Money(cents)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid monetary amount: {:?}", s);
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+            return Err(invalid());
+        }
+        if fraction.len() > 2 && fraction[2..].chars().any(|c| c != '0') {
+            return Err(invalid());
+        }
+
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| invalid())?
+        };
+        let fraction: i64 = format!("{:0<2}", &fraction[..fraction.len().min(2)])
+            .parse()
+            .map_err(|_| invalid())?;
+        let cents = whole
+            .checked_mul(100)
+            .and_then(|cents| cents.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(Money(if negative { -cents } else { cents }))
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, other: Money) -> Money {
+        Money(self.0 + other.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, other: Money) -> Money {
+        Money(self.0 - other.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, other: Money) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, other: Money) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_money_round_trips_exactly() {
+        for text in ["0.00", "0.10", "12.34", "-0.05", "-1250.00"] {
+            assert_eq!(text.parse::<Money>().unwrap().to_string(), text);
+        }
+        let max = Money::from_cents(i64::MAX);
+        assert_eq!(max.to_string().parse::<Money>().unwrap(), max);
+        assert_eq!("130".parse::<Money>().unwrap(), Money::from_cents(13000));
+        assert_eq!(".5".parse::<Money>().unwrap(), Money::from_cents(50));
+        assert_eq!("1.100".parse::<Money>().unwrap(), Money::from_cents(110));
+
+        for text in ["", "-", ".", "1.005", "1,00", "+1", "1e3", " 1"] {
+            assert!(text.parse::<Money>().is_err(), "{:?}", text);
+        }
+        assert!("92233720368547758.08".parse::<Money>().is_err());
+    }
+
+    #[test]
+    fn test_money_arithmetic_is_exact() {
+        let dime: Money = "0.10".parse().unwrap();
+        let total: Money = std::iter::repeat(dime).take(3).sum();
+
+        assert_eq!(total, "0.30".parse().unwrap());
+        assert_eq!(total - Money::from(30u64), Money::ZERO);
+        assert_eq!((-total).to_string(), "-0.30");
+    }
+}
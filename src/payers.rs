@@ -0,0 +1,490 @@
+//! Payer profiles and the payer registry.
+
+use crate::config::Config;
+use crate::denials::DenialMix;
+use crate::population::{Address, Provider};
+use rand::Rng;
+use rand_distr::{Distribution, Normal, WeightedIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A payer profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payer {
+    /// Payer name.
+    pub name: String,
+
+    /// Payer identifier, also used in TRN03.
+    pub payer_id: String,
+
+    /// BPR10 originating company identifier.
+    pub originating_company_id: String,
+
+    /// Payer address.
+    pub address: Address,
+
+    /// Additional payer identifiers as qualifier and ID pairs.
+    pub additional_ids: Vec<(String, String)>,
+
+    /// Business contact for providers.
+    pub business_contact: Option<Contact>,
+
+    /// Technical contact for EDI questions.
+    pub technical_contact: Contact,
+
+    /// Website for payer policies.
+    pub website: Option<String>,
+
+    /// How remittances are delivered when sent separately from the payment.
+    pub remittance_delivery: Option<RemittanceDelivery>,
+
+    /// How payments are made.
+    #[serde(default)]
+    pub payment_method: PaymentMethod,
+
+    /// Days between claim receipt and payment.
+    #[serde(default)]
+    pub payment_lag: PaymentLag,
+
+    /// Share of claims (0.0 to 1.0) denied outright.
+    #[serde(default)]
+    pub denial_rate: f64,
+
+    /// Share of service lines (0.0 to 1.0) denied on paid claims.
+    #[serde(default)]
+    pub line_denial_rate: f64,
+
+    /// Denial reason weights.
+    #[serde(default)]
+    pub denial_mix: DenialMix,
+
+    /// Multiplier applied to fee schedule allowed amounts.
+    #[serde(default = "default_fee_schedule_multiplier")]
+    pub fee_schedule_multiplier: f64,
+}
+
+fn default_fee_schedule_multiplier() -> f64 {
+    1.0
+}
+
+/// A PER contact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    /// Contact name.
+    pub name: Option<String>,
+
+    /// Communication number qualifier and number pairs.
+    pub communications: Vec<(String, String)>,
+}
+
+/// An RDM remittance delivery method.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemittanceDelivery {
+    /// Report transmission code (`BM`, `EM`, `FT` or `OL`).
+    pub method: String,
+
+    /// Name, for remittances sent by mail.
+    pub name: Option<String>,
+
+    /// Communication number, for electronic delivery.
+    pub communication_number: Option<String>,
+}
+
+impl FromStr for RemittanceDelivery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid remittance delivery: {}", s);
+        let (method, value) = s.split_once('=').ok_or_else(invalid)?;
+        let value = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        match method.trim() {
+            "BM" => Ok(Self {
+                method: "BM".to_string(),
+                name: value,
+                communication_number: None,
+            }),
+            method @ ("EM" | "FT" | "OL") if value.is_some() => Ok(Self {
+                method: method.to_string(),
+                name: None,
+                communication_number: value,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn parse_additional_ids(s: &str) -> Result<Vec<(String, String)>, String> {
+    let invalid = || format!("invalid additional ids: {}", s);
+    s.split(',')
+        .map(|entry| {
+            let (qualifier, id) = entry.split_once('=').ok_or_else(invalid)?;
+            let (qualifier, id) = (qualifier.trim(), id.trim());
+            if !["2U", "EO", "HI", "NF"].contains(&qualifier) || id.is_empty() {
+                return Err(invalid());
+            }
+            Ok((qualifier.to_string(), id.to_string()))
+        })
+        .collect()
+}
+
+/// BPR04 payment method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaymentMethod {
+    /// Automated clearing house transfer.
+    #[default]
+    #[serde(rename = "ACH")]
+    Ach,
+
+    /// Check.
+    #[serde(rename = "CHK")]
+    Check,
+
+    /// Federal Reserve wire transfer.
+    #[serde(rename = "FWT")]
+    Wire,
+}
+
+impl PaymentMethod {
+    /// The X12 payment method code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PaymentMethod::Ach => "ACH",
+            PaymentMethod::Check => "CHK",
+            PaymentMethod::Wire => "FWT",
+        }
+    }
+
+    /// Looks up a payment method by its X12 code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ACH" => Some(PaymentMethod::Ach),
+            "CHK" => Some(PaymentMethod::Check),
+            "FWT" => Some(PaymentMethod::Wire),
+            _ => None,
+        }
+    }
+}
+
+/// Normally distributed payment lag.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PaymentLag {
+    /// Mean lag in days.
+    pub mean_days: f64,
+
+    /// Standard deviation in days.
+    pub std_dev_days: f64,
+}
+
+impl PaymentLag {
+    /// Samples a non-negative lag in days.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> i64 {
+        let days = Normal::new(self.mean_days, self.std_dev_days)
+            .map(|normal| normal.sample(rng))
+            .unwrap_or(self.mean_days);
+        days.round().max(0.0) as i64
+    }
+}
+
+impl Default for PaymentLag {
+    fn default() -> Self {
+        Self {
+            mean_days: 14.0,
+            std_dev_days: 4.0,
+        }
+    }
+}
+
+impl Payer {
+    /// The payee identifier this payer assigns to `provider`.
+    pub fn payee_id(&self, provider: &Provider) -> String {
+        let suffix = &provider.npi[provider.npi.len().saturating_sub(6)..];
+        format!("{}{}", &self.payer_id[..self.payer_id.len().min(4)], suffix)
+    }
+}
+
+impl Default for Payer {
+    fn default() -> Self {
+        Self {
+            name: "PAYER NAME".to_string(),
+            payer_id: "1234567890".to_string(),
+            originating_company_id: "1999999999".to_string(),
+            address: Address {
+                line1: "100 Insurance Plaza".to_string(),
+                line2: Some("Claims Department".to_string()),
+                city: "Hartford".to_string(),
+                state: "CT".to_string(),
+                zip_code: "06103".to_string(),
+            },
+            additional_ids: vec![
+                ("2U".to_string(), "99999".to_string()),
+                ("EO".to_string(), "PAYER001".to_string()),
+                ("HI".to_string(), "HIN12345".to_string()),
+                ("NF".to_string(), "12345".to_string()),
+            ],
+            business_contact: Some(Contact {
+                name: Some("PROVIDER SERVICES".to_string()),
+                communications: vec![("TE".to_string(), "8005551212".to_string())],
+            }),
+            technical_contact: Contact {
+                name: Some("EDI SUPPORT".to_string()),
+                communications: vec![
+                    ("TE".to_string(), "8005551313".to_string()),
+                    ("EM".to_string(), "edi@payer.example.com".to_string()),
+                ],
+            },
+            website: Some("www.payer.example.com/policies".to_string()),
+            remittance_delivery: Some(RemittanceDelivery {
+                method: "BM".to_string(),
+                name: Some("BILLING OFFICE".to_string()),
+                communication_number: None,
+            }),
+            payment_method: PaymentMethod::default(),
+            payment_lag: PaymentLag::default(),
+            denial_rate: 0.0,
+            line_denial_rate: 0.0,
+            denial_mix: DenialMix::default(),
+            fee_schedule_multiplier: default_fee_schedule_multiplier(),
+        }
+    }
+}
+
+/// Relative weights of payer IDs, parsed from `PAYER_ID=WEIGHT,...`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PayerMix(pub BTreeMap<String, f64>);
+
+impl FromStr for PayerMix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid payer mix: {}", s);
+        let mut weights = BTreeMap::new();
+        for entry in s.split(',') {
+            let (payer_id, weight) = entry.split_once('=').ok_or_else(invalid)?;
+            let weight: f64 = weight.trim().parse().map_err(|_| invalid())?;
+            if payer_id.trim().is_empty() || !weight.is_finite() || weight < 0.0 {
+                return Err(invalid());
+            }
+            weights.insert(payer_id.trim().to_string(), weight);
+        }
+        Ok(PayerMix(weights))
+    }
+}
+
+/// The payers claims can be assigned to.
+#[derive(Debug, Clone)]
+pub struct PayerRegistry {
+    payers: Vec<Payer>,
+}
+
+impl PayerRegistry {
+    /// A registry of `payers`, or of the default payer when empty.
+    pub fn new(payers: Vec<Payer>) -> Self {
+        if payers.is_empty() {
+            return Self {
+                payers: vec![Payer::default()],
+            };
+        }
+        Self { payers }
+    }
+
+    /// Loads `payers.csv` from `data_path`.
+    pub fn load(data_path: &Path) -> Result<Self, String> {
+        #[derive(Debug, Deserialize)]
+        struct CsvPayer {
+            name: String,
+            payer_id: String,
+            originating_company_id: String,
+            address: String,
+            city: String,
+            state: String,
+            zip_code: String,
+            phone: String,
+            website: Option<String>,
+            payment_method: PaymentMethod,
+            additional_ids: Option<String>,
+            remittance_delivery: Option<String>,
+            denial_rate: f64,
+            line_denial_rate: Option<f64>,
+            denial_mix: Option<String>,
+            lag_mean_days: f64,
+            lag_std_dev_days: f64,
+            fee_schedule_multiplier: f64,
+        }
+
+        let path = data_path.join("payers.csv");
+        let mut payers = Vec::new();
+        if let Ok(mut rdr) = csv::Reader::from_path(&path) {
+            for rec in rdr.deserialize::<CsvPayer>().flatten() {
+                let invalid =
+                    |e: String| format!("{}: payer {}: {}", path.display(), rec.payer_id, e);
+                let column = |value: &Option<String>| value.clone().filter(|v| !v.is_empty());
+                let denial_mix = match column(&rec.denial_mix) {
+                    Some(mix) => mix.parse().map_err(invalid)?,
+                    None => DenialMix::default(),
+                };
+                let additional_ids = match column(&rec.additional_ids) {
+                    Some(ids) => parse_additional_ids(&ids).map_err(invalid)?,
+                    None => Vec::new(),
+                };
+                let remittance_delivery = column(&rec.remittance_delivery)
+                    .map(|delivery| delivery.parse())
+                    .transpose()
+                    .map_err(invalid)?;
+                let contact = |name: &str| Contact {
+                    name: Some(name.to_string()),
+                    communications: vec![("TE".to_string(), rec.phone.clone())],
+                };
+                payers.push(Payer {
+                    address: Address {
+                        line1: rec.address.clone(),
+                        line2: None,
+                        city: rec.city.clone(),
+                        state: rec.state.clone(),
+                        zip_code: rec.zip_code.clone(),
+                    },
+                    additional_ids,
+                    business_contact: Some(contact("PROVIDER SERVICES")),
+                    technical_contact: contact("EDI SUPPORT"),
+                    website: column(&rec.website),
+                    remittance_delivery,
+                    payment_method: rec.payment_method,
+                    payment_lag: PaymentLag {
+                        mean_days: rec.lag_mean_days,
+                        std_dev_days: rec.lag_std_dev_days,
+                    },
+                    denial_rate: rec.denial_rate.clamp(0.0, 1.0),
+                    line_denial_rate: rec.line_denial_rate.unwrap_or(0.0).clamp(0.0, 1.0),
+                    denial_mix,
+                    fee_schedule_multiplier: rec.fee_schedule_multiplier,
+                    name: rec.name,
+                    payer_id: rec.payer_id,
+                    originating_company_id: rec.originating_company_id,
+                });
+            }
+        }
+
+        Ok(Self::new(payers))
+    }
+
+    /// The payers from the configuration, or from the data directory when none are configured.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        if config.payers.is_empty() {
+            Self::load(&config.data_dir)
+        } else {
+            Ok(Self::new(config.payers.clone()))
+        }
+    }
+
+    /// Every payer in the registry.
+    pub fn payers(&self) -> &[Payer] {
+        &self.payers
+    }
+
+    /// The payer with `payer_id`.
+    pub fn get(&self, payer_id: &str) -> Option<&Payer> {
+        self.payers.iter().find(|payer| payer.payer_id == payer_id)
+    }
+
+    /// A weighted index over the registry's payers; an empty mix weights every payer equally.
+    pub fn mix(&self, mix: &PayerMix) -> Result<WeightedIndex<f64>, String> {
+        if let Some(unknown) = mix.0.keys().find(|payer_id| self.get(payer_id).is_none()) {
+            return Err(format!("payer mix references unknown payer {:?}", unknown));
+        }
+        let weights = self.payers.iter().map(|payer| {
+            if mix.0.is_empty() {
+                1.0
+            } else {
+                mix.0.get(&payer.payer_id).copied().unwrap_or(0.0)
+            }
+        });
+        WeightedIndex::new(weights).map_err(|e| format!("invalid payer mix: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::denials::DenialReason;
+    use crate::population::PopulationGenerator;
+    use crate::seeding::{stream_rng, Stream};
+
+    #[test]
+    fn test_payee_id_is_derived_from_npi() {
+        let payer = Payer::default();
+        let provider = PopulationGenerator::new(Some(42), Path::new("data")).generate_provider();
+
+        let payee_id = payer.payee_id(&provider);
+        assert!(payee_id.starts_with("1234"));
+        assert!(payee_id.ends_with(&provider.npi[4..]));
+    }
+
+    #[test]
+    fn test_registry_loads_payers_csv() {
+        let registry = PayerRegistry::load(Path::new("data")).unwrap();
+        assert!(registry.payers().len() > 1);
+        let payer = registry.get("74431").unwrap();
+        assert_eq!(payer.payment_method, PaymentMethod::Check);
+        assert_eq!(payer.payment_lag.mean_days, 30.0);
+        assert!(payer.denial_rate > 0.0);
+        assert!(payer.line_denial_rate > 0.0);
+        assert_eq!(
+            payer.denial_mix.0.get(&DenialReason::WrongPayer),
+            Some(&1.0)
+        );
+        assert!(registry.get("60101").unwrap().denial_mix.is_empty());
+        assert_eq!(
+            payer.additional_ids,
+            [("2U".to_string(), "74431".to_string())]
+        );
+        let delivery = registry.get("47198").unwrap().remittance_delivery.clone();
+        assert_eq!(
+            delivery.and_then(|delivery| delivery.communication_number),
+            Some("remits@blueriver.example.com".to_string())
+        );
+        assert!("EM=".parse::<RemittanceDelivery>().is_err());
+        assert!(parse_additional_ids("XX=1").is_err());
+
+        let fallback = PayerRegistry::load(Path::new("does-not-exist")).unwrap();
+        assert_eq!(fallback.payers().len(), 1);
+        assert_eq!(fallback.payers()[0].name, "PAYER NAME");
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = std::fs::read_to_string("data/payers.csv").unwrap();
+        let invalid = csv.replacen("wrong_payer=1", "wrong_payer=1,late=2", 1);
+        std::fs::write(dir.path().join("payers.csv"), invalid).unwrap();
+        let error = PayerRegistry::load(dir.path()).unwrap_err();
+        assert!(error.contains("payer 74431"), "{}", error);
+        assert!(
+            error.contains("unknown denial reason \"late\""),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_payer_mix() {
+        let registry = PayerRegistry::new(vec![
+            Payer::default(),
+            Payer {
+                payer_id: "60101".to_string(),
+                ..Default::default()
+            },
+        ]);
+        let mix: PayerMix = "1234567890=3, 60101=1".parse().unwrap();
+        assert_eq!(mix.0.get("60101"), Some(&1.0));
+
+        let index = registry.mix(&mix).unwrap();
+        let mut rng = stream_rng(Some(1), Stream::Remittances);
+        let first = (0..1000).filter(|_| index.sample(&mut rng) == 0).count();
+        assert!((650..850).contains(&first), "{}", first);
+
+        assert!(registry.mix(&"99999=1".parse().unwrap()).is_err());
+        assert!(registry.mix(&"60101=0".parse().unwrap()).is_err());
+        for text in ["", "60101", "60101=-1", "=1", "60101=x"] {
+            assert!(text.parse::<PayerMix>().is_err(), "{:?}", text);
+        }
+    }
+}
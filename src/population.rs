@@ -1,11 +1,13 @@
+//! Synthetic patients and providers.
 
-
+use crate::benefits::BenefitPlan;
+use crate::clock::Clock;
+use crate::seeding::{stream_rng, Stream};
 use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::env;
 use std::path::Path;
 
 use csv;
@@ -29,67 +31,79 @@ struct CityRecord {
     zip: String,
 }
 
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A synthetic patient or subscriber.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Person {
-    
+    /// Unique identifier.
     pub id: String,
 
-    
+    /// Member identification number.
+    #[serde(default)]
+    pub member_id: String,
+
+    /// First name.
     pub first_name: String,
 
-    
+    /// Last name.
     pub last_name: String,
 
-    
+    /// Date of birth (YYYY-MM-DD).
     pub date_of_birth: String,
 
-    
+    /// Gender code (`M` or `F`).
     pub gender: String,
 
-    
+    /// Home address.
     pub address: Address,
-}
 
+    /// Benefit plan the person is covered by.
+    #[serde(default)]
+    pub benefit_plan: Option<BenefitPlan>,
+}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A postal address.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Address {
-    
+    /// First address line.
     pub line1: String,
 
-    
+    /// Second address line.
     pub line2: Option<String>,
 
-    
+    /// City.
     pub city: String,
 
-    
+    /// Two-letter state code.
     pub state: String,
 
-    
+    /// ZIP code.
     pub zip_code: String,
 }
 
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A synthetic health care provider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Provider {
-    
+    /// National Provider Identifier.
     pub npi: String,
 
-    
+    /// Federal tax identification number.
+    #[serde(default)]
+    pub tax_id: String,
+
+    /// Provider type or specialty.
     pub provider_type: String,
 
-    
+    /// Provider or organization name.
     pub name: String,
 
-    
+    /// Practice address.
     pub address: Address,
 
-    
+    /// Health care provider taxonomy codes.
     pub taxonomy_codes: Vec<String>,
 }
 
-
+/// Generates synthetic patients and providers.
 pub struct PopulationGenerator {
     rng: ChaCha8Rng,
     first_names: HashMap<String, Vec<String>>,
@@ -97,31 +111,24 @@ pub struct PopulationGenerator {
     cities: Vec<(String, String, String)>, 
     provider_types: Vec<String>,
     taxonomy_codes: Vec<String>,
+    benefit_plans: Vec<BenefitPlan>,
+    clock: Clock,
 }
 
 impl PopulationGenerator {
-    
-    pub fn new(seed: Option<u64>) -> Self {
-        let rng = match seed {
-            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
-            None => ChaCha8Rng::from_entropy(),
-        };
-
-        
-        let data_dir = env::var("ZEDI_GEN_DATA_DIR").unwrap_or_else(|_| "data".to_string());
-        let data_path = Path::new(&data_dir);
+    /// Creates a generator, loading name, location and plan data from `data_path`.
+    pub fn new(seed: Option<u64>, data_path: &Path) -> Self {
+        let rng = stream_rng(seed, Stream::Population);
 
         
         let mut first_names: HashMap<String, Vec<String>> = HashMap::new();
         if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("first_names.csv")) {
-            for result in rdr.deserialize() {
-                if let Ok(rec) = result {
-                    let rec: FirstNameRecord = rec;
-                    first_names
-                        .entry(rec.gender.clone())
-                        .or_default()
-                        .push(rec.name.clone());
-                }
+            for rec in rdr.deserialize().flatten() {
+                let rec: FirstNameRecord = rec;
+                first_names
+                    .entry(rec.gender.clone())
+                    .or_default()
+                    .push(rec.name.clone());
             }
         }
         if first_names.is_empty() {
@@ -141,11 +148,9 @@ impl PopulationGenerator {
             .has_headers(false)
             .from_path(data_path.join("last_names.csv"))
         {
-            for result in rdr.records() {
-                if let Ok(record) = result {
-                    if let Some(name) = record.get(0) {
-                        last_names.push(name.to_string());
-                    }
+            for record in rdr.records().flatten() {
+                if let Some(name) = record.get(0) {
+                    last_names.push(name.to_string());
                 }
             }
         }
@@ -160,11 +165,9 @@ impl PopulationGenerator {
         
         let mut cities = Vec::new();
         if let Ok(mut rdr) = csv::Reader::from_path(data_path.join("cities.csv")) {
-            for result in rdr.deserialize() {
-                if let Ok(rec) = result {
-                    let rec: CityRecord = rec;
-                    cities.push((rec.city, rec.state, rec.zip));
-                }
+            for rec in rdr.deserialize().flatten() {
+                let rec: CityRecord = rec;
+                cities.push((rec.city, rec.state, rec.zip));
             }
         }
         if cities.is_empty() {
@@ -177,11 +180,9 @@ impl PopulationGenerator {
             .has_headers(false)
             .from_path(data_path.join("provider_types.csv"))
         {
-            for result in rdr.records() {
-                if let Ok(record) = result {
-                    if let Some(pt) = record.get(0) {
-                        provider_types.push(pt.to_string());
-                    }
+            for record in rdr.records().flatten() {
+                if let Some(pt) = record.get(0) {
+                    provider_types.push(pt.to_string());
                 }
             }
         }
@@ -195,11 +196,9 @@ impl PopulationGenerator {
             .has_headers(false)
             .from_path(data_path.join("taxonomy_codes.csv"))
         {
-            for result in rdr.records() {
-                if let Ok(record) = result {
-                    if let Some(code) = record.get(0) {
-                        taxonomy_codes.push(code.to_string());
-                    }
+            for record in rdr.records().flatten() {
+                if let Some(code) = record.get(0) {
+                    taxonomy_codes.push(code.to_string());
                 }
             }
         }
@@ -214,10 +213,18 @@ impl PopulationGenerator {
             cities,
             provider_types,
             taxonomy_codes,
+            benefit_plans: BenefitPlan::load_all(data_path),
+            clock: Clock::system(),
         }
     }
 
-    
+    /// Uses `clock` for dates of birth.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Generates a patient.
     pub fn generate_person(&mut self) -> Person {
         
         let gender = if self.rng.gen_bool(0.5) { "M" } else { "F" }.to_string();
@@ -233,7 +240,7 @@ impl PopulationGenerator {
             .cloned()
             .unwrap_or_else(|| LastName().fake_with_rng(&mut self.rng));
         
-        let today = chrono::Utc::now().date_naive();
+        let today = self.clock.today();
         let min_age_days = 18 * 365;
         let max_age_days = 90 * 365;
         let age_days = self.rng.gen_range(min_age_days..=max_age_days) as i64;
@@ -253,8 +260,13 @@ impl PopulationGenerator {
         } else {
             None
         };
+        let member_id = format!("W{:09}", self.rng.gen_range(0..1_000_000_000u32));
+        let benefit_plan = self.benefit_plans.choose(&mut self.rng).cloned();
         Person {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: uuid::Builder::from_random_bytes(self.rng.gen())
+                .into_uuid()
+                .to_string(),
+            member_id,
             first_name,
             last_name,
             date_of_birth,
@@ -266,16 +278,32 @@ impl PopulationGenerator {
                 state,
                 zip_code,
             },
+            benefit_plan,
+        }
+    }
+
+    /// Generates a subscriber for `patient` when the patient is a dependent, which happens
+    /// for about 30% of patients.
+    pub fn generate_subscriber_for(&mut self, patient: &Person) -> Option<Person> {
+        if !self.rng.gen_bool(0.3) {
+            return None;
         }
+        let mut subscriber = self.generate_person();
+        subscriber.last_name = patient.last_name.clone();
+        subscriber.address = patient.address.clone();
+        subscriber.benefit_plan = patient.benefit_plan.clone();
+        Some(subscriber)
     }
 
-    
+    /// Generates a provider.
     pub fn generate_provider(&mut self) -> Provider {
         
-        let npi = format!(
-            "{:010}",
-            self.rng.gen_range(1_000_000_000u64..=9_999_999_999u64)
-        );
+        let base = self
+            .rng
+            .gen_range(100_000_000u32..=299_999_999u32)
+            .to_string();
+        let npi = format!("{}{}", base, npi_check_digit(&base));
+        let tax_id = format!("{:09}", self.rng.gen_range(100_000_000u32..=999_999_999u32));
         let provider_type = self
             .provider_types
             .choose(&mut self.rng)
@@ -303,6 +331,7 @@ impl PopulationGenerator {
             .unwrap_or_else(|| "207Q00000X".to_string())];
         Provider {
             npi,
+            tax_id,
             provider_type,
             name,
             address: Address {
@@ -317,16 +346,33 @@ impl PopulationGenerator {
     }
 }
 
+/// The Luhn check digit of a 9-digit NPI base, including the 80840 prefix.
+pub fn npi_check_digit(base: &str) -> u32 {
+    let sum: u32 = format!("80840{}", base)
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| match (i % 2 == 0, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_person_generation() {
-        let mut generator = PopulationGenerator::new(Some(42));
+        let mut generator = PopulationGenerator::new(Some(42), Path::new("data"));
         let person = generator.generate_person();
 
         assert!(!person.id.is_empty());
+        assert_eq!(person.member_id.len(), 10);
         assert!(!person.first_name.is_empty());
         assert!(!person.last_name.is_empty());
         assert!(!person.date_of_birth.is_empty());
@@ -339,10 +385,13 @@ mod tests {
 
     #[test]
     fn test_provider_generation() {
-        let mut generator = PopulationGenerator::new(Some(42));
+        let mut generator = PopulationGenerator::new(Some(42), Path::new("data"));
         let provider = generator.generate_provider();
 
         assert_eq!(provider.npi.len(), 10);
+        let check_digit = npi_check_digit(&provider.npi[..9]);
+        assert_eq!(check_digit.to_string(), &provider.npi[9..]);
+        assert_eq!(provider.tax_id.len(), 9);
         assert!(!provider.provider_type.is_empty());
         assert!(!provider.name.is_empty());
         assert!(!provider.address.line1.is_empty());
@@ -351,4 +400,21 @@ mod tests {
         assert!(!provider.address.zip_code.is_empty());
         assert!(!provider.taxonomy_codes.is_empty());
     }
+
+    #[test]
+    fn test_benefit_plans_follow_data_dir() {
+        let data_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            data_dir.path().join("benefit_plans.csv"),
+            "name,deductible,coinsurance_percent,office_copay,emergency_copay,inpatient_copay,\
+             outpatient_copay,laboratory_copay,out_of_pocket_max\nTEST EPO,0,0,,,,,,0\n",
+        )
+        .unwrap();
+        let mut generator = PopulationGenerator::new(Some(24), data_dir.path());
+
+        for _ in 0..10 {
+            let plan = generator.generate_person().benefit_plan.unwrap();
+            assert_eq!(plan.name, "TEST EPO");
+        }
+    }
 }
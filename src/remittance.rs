@@ -0,0 +1,103 @@
+//! Remittances: the claims paid to one payee in one payment.
+
+use crate::adjustments::ProviderAdjustment;
+use crate::claims::Claim;
+use crate::payers::Payer;
+use crate::population::Provider;
+use serde::{Deserialize, Serialize};
+
+/// The claims a payer pays to one payee with a single payment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remittance {
+    /// The paying payer.
+    pub payer: Payer,
+
+    /// The payee, the billing provider of every claim.
+    pub payee: Provider,
+
+    /// Payment date (CCYYMMDD).
+    pub payment_date: String,
+
+    /// Check or EFT trace number.
+    pub trace_number: String,
+
+    /// Claims paid by the remittance.
+    pub claims: Vec<Claim>,
+
+    /// Provider level adjustments.
+    pub provider_adjustments: Vec<ProviderAdjustment>,
+}
+
+impl Remittance {
+    /// Total claim payments in cents.
+    pub fn claim_payment_total(&self) -> i64 {
+        self.claims
+            .iter()
+            .map(|c| c.signed(c.total_payment).cents())
+            .sum()
+    }
+
+    /// Total provider level adjustments in cents.
+    pub fn provider_adjustment_total(&self) -> i64 {
+        self.provider_adjustments.iter().map(|a| a.amount).sum()
+    }
+
+    /// Total payment in cents: claim payments less provider level adjustments.
+    pub fn payment_amount(&self) -> i64 {
+        self.claim_payment_total() - self.provider_adjustment_total()
+    }
+}
+
+/// Groups `claims` into one remittance per billing provider.
+pub fn group_claims(payer: &Payer, payment_date: &str, claims: Vec<Claim>) -> Vec<Remittance> {
+    let mut remittances: Vec<Remittance> = Vec::new();
+    for claim in claims {
+        match remittances
+            .iter_mut()
+            .find(|r| r.payee.npi == claim.billing_provider.npi)
+        {
+            Some(remittance) => remittance.claims.push(claim),
+            None => remittances.push(Remittance {
+                payer: payer.clone(),
+                payee: claim.billing_provider.clone(),
+                payment_date: payment_date.to_string(),
+                trace_number: String::new(),
+                claims: vec![claim],
+                provider_adjustments: Vec::new(),
+            }),
+        }
+    }
+    remittances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claims::ClaimGenerator;
+    use crate::population::PopulationGenerator;
+    use std::path::Path;
+
+    #[test]
+    fn test_group_claims_by_payee() {
+        let mut claim_gen = ClaimGenerator::new(Some(3), Path::new("data"));
+        let mut pop_gen = PopulationGenerator::new(Some(3), Path::new("data"));
+        let first = pop_gen.generate_provider();
+        let second = pop_gen.generate_provider();
+
+        let claims: Vec<Claim> = [&first, &second, &first]
+            .into_iter()
+            .map(|provider| {
+                claim_gen.generate_claim(pop_gen.generate_person(), provider.clone(), None)
+            })
+            .collect();
+        let expected: i64 = claims[0].total_payment as i64 + claims[2].total_payment as i64;
+
+        let remittances = group_claims(&Payer::default(), "20240315", claims);
+
+        assert_eq!(remittances.len(), 2);
+        assert_eq!(remittances[0].payee.npi, first.npi);
+        assert_eq!(remittances[0].claims.len(), 2);
+        assert_eq!(remittances[1].claims.len(), 1);
+        assert_eq!(remittances[0].payment_amount(), expected);
+    }
+}
@@ -0,0 +1,568 @@
+//! 837P and 837I claims matching the generated 835 remittances.
+
+use crate::claims::{self, Claim, ClaimStatusCode, ServiceLine};
+use crate::clock::Clock;
+use crate::generator::{medical_procedure, statement_period, x12_date, RECEIVER_ID, SENDER_ID};
+use crate::population::{Address, Person};
+use crate::remittance::Remittance;
+use crate::x12::control_numbers::ControlNumbers;
+use crate::x12::delimiters::Element;
+use crate::x12::envelope::{FunctionalGroup, TransactionSet, X12Interchange};
+use crate::x12::segments::{
+    GenericSegment, LxSegment, N3Segment, N4Segment, Nm1Segment, PerSegment, RefSegment,
+};
+
+/// Phone number on the submitter PER contact.
+const SUBMITTER_PHONE: &str = "8005551212";
+
+/// Diagnosis codes assigned to generated claims.
+const DIAGNOSIS_CODES: &[&str] = &[
+    "Z0000", "I10", "E119", "J069", "M545", "R079", "K219", "N390", "E785", "M1711",
+];
+
+/// The kind of 837 transaction a claim is submitted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimType {
+    /// An 837P professional claim.
+    Professional,
+
+    /// An 837I institutional claim.
+    Institutional,
+}
+
+impl ClaimType {
+    /// The claim type for `claim`, based on its billing form.
+    pub fn of(claim: &Claim) -> Self {
+        if claim.is_institutional() {
+            ClaimType::Institutional
+        } else {
+            ClaimType::Professional
+        }
+    }
+
+    /// The implementation guide version for GS08 and ST03.
+    pub fn implementation_guide(&self) -> &'static str {
+        match self {
+            ClaimType::Professional => "005010X222A1",
+            ClaimType::Institutional => "005010X223A2",
+        }
+    }
+}
+
+/// Builds an 837 interchange holding the claims behind `remittances`.
+pub fn build_interchange(
+    remittances: &[Remittance],
+    clock: Clock,
+    control_numbers: &mut ControlNumbers,
+) -> X12Interchange {
+    let interchange_control_number = control_numbers.next_interchange();
+    let now = clock.now();
+    let date = now.format("%Y%m%d").to_string();
+    let time = now.format("%H%M").to_string();
+    let mut interchange = X12Interchange::new(
+        RECEIVER_ID,
+        SENDER_ID,
+        &interchange_control_number,
+        &now.format("%y%m%d").to_string(),
+        &time,
+    );
+
+    for claim_type in [ClaimType::Professional, ClaimType::Institutional] {
+        let submissions: Vec<(&Remittance, Vec<&Claim>)> = remittances
+            .iter()
+            .filter_map(|remittance| {
+                let claims: Vec<&Claim> = remittance
+                    .claims
+                    .iter()
+                    .filter(|claim| claim.status_code != ClaimStatusCode::Reversal)
+                    .filter(|claim| ClaimType::of(claim) == claim_type)
+                    .collect();
+                (!claims.is_empty()).then_some((remittance, claims))
+            })
+            .collect();
+        if submissions.is_empty() {
+            continue;
+        }
+
+        let mut group = FunctionalGroup::new(
+            RECEIVER_ID,
+            SENDER_ID,
+            &control_numbers.next_group(),
+            &date,
+            &time,
+        );
+        group.gs.gs01_functional_identifier_code = "HC".to_string();
+        group.gs.gs08_version = claim_type.implementation_guide().to_string();
+        for (remittance, claims) in submissions {
+            let control_number = control_numbers.next_transaction();
+            let reference = format!(
+                "{}{}{}",
+                interchange_control_number, group.gs.gs06_group_control_number, control_number
+            );
+            let mut transaction = TransactionSet::new(&control_number);
+            transaction.st.st01_transaction_set_id = "837".to_string();
+            transaction.st.st03_implementation_reference =
+                claim_type.implementation_guide().to_string();
+            transaction.add_segment(segment(
+                "BHT",
+                vec![
+                    "0019".into(),
+                    "00".into(),
+                    reference.into(),
+                    (&date).into(),
+                    (&time).into(),
+                    "CH".into(),
+                ],
+            ));
+            add_submission(&mut transaction, claim_type, remittance, &claims);
+            group.add_transaction_set(transaction);
+        }
+        interchange.add_functional_group(group);
+    }
+    interchange
+}
+
+/// Adds the submitter, receiver and billing provider loops for one payer.
+fn add_submission(
+    transaction: &mut TransactionSet,
+    claim_type: ClaimType,
+    remittance: &Remittance,
+    claims: &[&Claim],
+) {
+    let provider = &remittance.payee;
+    transaction.add_segment(organization_name("41", &provider.name, "46", RECEIVER_ID));
+    transaction.add_segment(PerSegment {
+        per01_function_code: "IC".to_string(),
+        per02_name: Some(provider.name.clone()),
+        communications: vec![("TE".to_string(), SUBMITTER_PHONE.to_string())],
+    });
+    transaction.add_segment(organization_name(
+        "40",
+        &remittance.payer.name,
+        "46",
+        &remittance.payer.payer_id,
+    ));
+
+    transaction.add_segment(hierarchy(1, None, "20", true));
+    if let Some(taxonomy) = provider.taxonomy_codes.first() {
+        transaction.add_segment(segment(
+            "PRV",
+            vec!["BI".into(), "PXC".into(), taxonomy.into()],
+        ));
+    }
+    transaction.add_segment(organization_name("85", &provider.name, "XX", &provider.npi));
+    add_address(transaction, &provider.address);
+    transaction.add_segment(RefSegment {
+        ref01_qualifier: "EI".to_string(),
+        ref02_identifier: provider.tax_id.clone(),
+    });
+
+    let mut next_id = 2;
+    for claim in claims {
+        let subscriber_id = next_id;
+        let subscriber = claim.subscriber.as_ref().unwrap_or(&claim.patient);
+        let dependent = claim.subscriber.is_some();
+        transaction.add_segment(hierarchy(subscriber_id, Some(1), "22", dependent));
+        transaction.add_segment(segment(
+            "SBR",
+            vec![
+                "P".into(),
+                if dependent { "" } else { "18" }.into(),
+                Element::default(),
+                Element::default(),
+                Element::default(),
+                Element::default(),
+                Element::default(),
+                Element::default(),
+                "CI".into(),
+            ],
+        ));
+        add_person(transaction, "IL", subscriber, Some(&subscriber.member_id));
+        transaction.add_segment(organization_name(
+            "PR",
+            &remittance.payer.name,
+            "PI",
+            &remittance.payer.payer_id,
+        ));
+        next_id += 1;
+
+        if dependent {
+            transaction.add_segment(hierarchy(next_id, Some(subscriber_id), "23", false));
+            transaction.add_segment(segment("PAT", vec!["G8".into()]));
+            add_person(transaction, "QC", &claim.patient, None);
+            next_id += 1;
+        }
+
+        add_claim(transaction, claim_type, claim);
+    }
+}
+
+/// Adds the subscriber and 2300 claim loops for `claim`.
+fn add_claim(transaction: &mut TransactionSet, claim_type: ClaimType, claim: &Claim) {
+    let institutional = claim_type == ClaimType::Institutional;
+    transaction.add_segment(segment(
+        "CLM",
+        vec![
+            (&claim.claim_id).into(),
+            amount(claim.total_charge),
+            Element::default(),
+            Element::default(),
+            Element::composite(vec![
+                claim.facility_type_code().to_string(),
+                if institutional { "A" } else { "B" }.to_string(),
+                claim.claim_frequency_code.clone(),
+            ]),
+            if institutional { "" } else { "Y" }.into(),
+            "A".into(),
+            "Y".into(),
+            "Y".into(),
+        ],
+    ));
+
+    let inpatient = claim
+        .bill_type
+        .as_deref()
+        .is_some_and(|b| b.starts_with("11"));
+    if institutional {
+        if let (Some(from), Some(to)) = statement_period(claim) {
+            transaction.add_segment(segment(
+                "DTP",
+                vec![
+                    "434".into(),
+                    "RD8".into(),
+                    format!("{}-{}", x12_date(from), x12_date(to)).into(),
+                ],
+            ));
+            if inpatient {
+                transaction.add_segment(segment(
+                    "DTP",
+                    vec!["435".into(), "D8".into(), x12_date(from).into()],
+                ));
+            }
+        }
+        if inpatient {
+            transaction.add_segment(segment(
+                "CL1",
+                vec!["3".into(), Element::default(), "01".into()],
+            ));
+        }
+    }
+
+    let references = [
+        ("F8", claim.original_reference.as_ref()),
+        ("G1", claim.prior_authorization.as_ref()),
+        ("EA", claim.medical_record_number.as_ref()),
+    ];
+    for (qualifier, value) in references {
+        if let Some(value) = value {
+            transaction.add_segment(RefSegment {
+                ref01_qualifier: qualifier.to_string(),
+                ref02_identifier: value.clone(),
+            });
+        }
+    }
+
+    let diagnosis = diagnosis_code(claim).to_string();
+    transaction.add_segment(segment(
+        "HI",
+        vec![Element::composite(vec![
+            "ABK".to_string(),
+            diagnosis.clone(),
+        ])],
+    ));
+    if inpatient {
+        transaction.add_segment(segment(
+            "HI",
+            vec![Element::composite(vec!["ABJ".to_string(), diagnosis])],
+        ));
+    }
+
+    if !institutional {
+        if let Some(ref rendering) = claim.rendering_provider {
+            if rendering.npi != claim.billing_provider.npi {
+                transaction.add_segment(organization_name(
+                    "82",
+                    &rendering.name,
+                    "XX",
+                    &rendering.npi,
+                ));
+            }
+        }
+    }
+
+    for (index, line) in claim.service_lines.iter().enumerate() {
+        add_service_line(transaction, claim_type, index as u32 + 1, line);
+    }
+}
+
+/// Adds a 2400 service line loop.
+fn add_service_line(
+    transaction: &mut TransactionSet,
+    claim_type: ClaimType,
+    number: u32,
+    line: &ServiceLine,
+) {
+    transaction.add_segment(LxSegment {
+        lx01_assigned_number: number,
+    });
+
+    let procedure = match line.submitted_procedure_code {
+        Some(ref code) => medical_procedure(code, &line.modifiers, ""),
+        None => medical_procedure(
+            &line.procedure_code,
+            &line.modifiers,
+            &line.procedure_description,
+        ),
+    };
+    let service = match claim_type {
+        ClaimType::Professional => segment(
+            "SV1",
+            vec![
+                procedure.to_element(),
+                amount(line.charge_amount),
+                "UN".into(),
+                line.units.to_string().into(),
+                Element::default(),
+                Element::default(),
+                "1".into(),
+            ],
+        ),
+        ClaimType::Institutional => segment(
+            "SV2",
+            vec![
+                revenue_code(line).into(),
+                procedure.to_element(),
+                amount(line.charge_amount),
+                "UN".into(),
+                line.units.to_string().into(),
+            ],
+        ),
+    };
+    transaction.add_segment(service);
+
+    let service_date = match line.service_end_date {
+        Some(ref end) if *end != line.service_date => (
+            "RD8",
+            format!("{}-{}", x12_date(&line.service_date), x12_date(end)),
+        ),
+        _ => ("D8", x12_date(&line.service_date)),
+    };
+    transaction.add_segment(segment(
+        "DTP",
+        vec!["472".into(), service_date.0.into(), service_date.1.into()],
+    ));
+    transaction.add_segment(RefSegment {
+        ref01_qualifier: "6R".to_string(),
+        ref02_identifier: line.line_item_control_number.clone(),
+    });
+}
+
+/// Adds an NM1 loop for a person.
+fn add_person(
+    transaction: &mut TransactionSet,
+    entity_id: &str,
+    person: &Person,
+    id: Option<&str>,
+) {
+    transaction.add_segment(Nm1Segment {
+        nm101_entity_id: entity_id.to_string(),
+        nm102_entity_type: "1".to_string(),
+        nm103_last_name: person.last_name.clone(),
+        nm104_first_name: Some(person.first_name.clone()),
+        nm105_middle_name: None,
+        nm107_suffix: None,
+        nm108_id_qualifier: id.map(|_| "MI".to_string()),
+        nm109_id: id.map(str::to_string),
+    });
+    add_address(transaction, &person.address);
+    transaction.add_segment(segment(
+        "DMG",
+        vec![
+            "D8".into(),
+            x12_date(&person.date_of_birth).into(),
+            (&person.gender).into(),
+        ],
+    ));
+}
+
+/// Adds the N3/N4 segments for `address`.
+fn add_address(transaction: &mut TransactionSet, address: &Address) {
+    transaction.add_segment(N3Segment {
+        n301_address_line1: address.line1.clone(),
+        n302_address_line2: address.line2.clone(),
+    });
+    transaction.add_segment(N4Segment {
+        n401_city: address.city.clone(),
+        n402_state: address.state.clone(),
+        n403_postal_code: address.zip_code.clone(),
+    });
+}
+
+/// An NM1 segment naming an organization.
+fn organization_name(entity_id: &str, name: &str, qualifier: &str, id: &str) -> Nm1Segment {
+    Nm1Segment {
+        nm101_entity_id: entity_id.to_string(),
+        nm102_entity_type: "2".to_string(),
+        nm103_last_name: name.to_string(),
+        nm104_first_name: None,
+        nm105_middle_name: None,
+        nm107_suffix: None,
+        nm108_id_qualifier: Some(qualifier.to_string()),
+        nm109_id: Some(id.to_string()),
+    }
+}
+
+/// An HL segment.
+fn hierarchy(id: u32, parent: Option<u32>, level: &str, has_children: bool) -> GenericSegment {
+    segment(
+        "HL",
+        vec![
+            id.to_string().into(),
+            parent.map(|p| p.to_string()).unwrap_or_default().into(),
+            level.into(),
+            if has_children { "1" } else { "0" }.into(),
+        ],
+    )
+}
+
+/// A diagnosis code chosen stably from the claim ID.
+fn diagnosis_code(claim: &Claim) -> &'static str {
+    let hash = claim.claim_id.bytes().fold(0usize, |acc, b| {
+        acc.wrapping_mul(31).wrapping_add(b as usize)
+    });
+    DIAGNOSIS_CODES[hash % DIAGNOSIS_CODES.len()]
+}
+
+/// The revenue code billed on an institutional service line.
+fn revenue_code(line: &ServiceLine) -> String {
+    line.revenue_code
+        .clone()
+        .or_else(|| claims::revenue_code(&line.place_of_service))
+        .unwrap_or_default()
+}
+
+/// Formats `cents` as a dollar amount element.
+fn amount(cents: u64) -> Element {
+    format!("{}.{:02}", cents / 100, cents % 100).into()
+}
+
+/// A generic `id` segment with `elements`.
+fn segment(id: &str, elements: Vec<Element>) -> GenericSegment {
+    GenericSegment {
+        id: id.to_string(),
+        elements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claims::ClaimGenerator;
+    use crate::config::{ClaimsPerRemittance, Config};
+    use crate::generator::generate_to_string;
+    use crate::payers::Payer;
+    use crate::population::PopulationGenerator;
+    use crate::remittance::group_claims;
+    use chrono::NaiveDate;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+    use tempfile::NamedTempFile;
+
+    fn element_set(content: &str, id: &str, position: usize) -> BTreeSet<String> {
+        let prefix = format!("{}*", id);
+        content
+            .split('~')
+            .map(str::trim)
+            .filter(|segment| segment.starts_with(&prefix))
+            .map(|segment| segment.split('*').nth(position).unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_claims_reconcile_with_remittance() {
+        let claims_file = NamedTempFile::new().unwrap();
+        let remittance = generate_to_string(Config {
+            claim_count: 12,
+            seed: Some(10),
+            anomaly_rate: 0.0,
+            batch_remittances: true,
+            claims_per_remittance: ClaimsPerRemittance::Fixed(4),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            claims_output: Some(claims_file.path().to_owned()),
+            ..Default::default()
+        });
+        let claims = std::fs::read_to_string(claims_file.path()).unwrap();
+
+        assert_eq!(
+            element_set(&claims, "CLM", 1),
+            element_set(&remittance, "CLP", 1)
+        );
+        assert_eq!(element_set(&claims, "CLM", 1).len(), 12);
+        assert_eq!(
+            element_set(&claims, "REF*6R", 1),
+            element_set(&remittance, "REF*6R", 1)
+        );
+        assert_eq!(
+            element_set(&claims, "ISA", 6),
+            ["RECEIVER01     ".to_string()].into()
+        );
+        assert_eq!(
+            element_set(&claims, "ISA", 13),
+            ["000000002".to_string()].into()
+        );
+        assert_eq!(element_set(&claims, "ST", 1), ["837".to_string()].into());
+        assert_eq!(element_set(&claims, "GS", 6).len(), 2);
+        assert_eq!(
+            element_set(&claims, "BHT", 3).len(),
+            claims.matches("ST*837*").count()
+        );
+        assert_eq!(
+            claims.matches("SV1*").count() + claims.matches("SV2*").count(),
+            remittance.matches("SVC*").count()
+        );
+        let mut revenue_codes = element_set(&remittance, "SVC", 4);
+        revenue_codes.remove("");
+        assert!(!revenue_codes.is_empty());
+        assert_eq!(element_set(&claims, "SV2", 1), revenue_codes);
+        assert!(element_set(&claims, "GS", 8)
+            .iter()
+            .all(|guide| guide == "005010X222A1" || guide == "005010X223A2"));
+    }
+
+    #[test]
+    fn test_institutional_claims_use_sv2() {
+        let mut pop_gen = PopulationGenerator::new(Some(5), Path::new("data"));
+        let provider = pop_gen.generate_provider();
+        let mut claim = ClaimGenerator::new(Some(5), Path::new("data")).generate_claim(
+            pop_gen.generate_person(),
+            provider,
+            None,
+        );
+        claim.bill_type = Some("111".to_string());
+        for line in &mut claim.service_lines {
+            line.place_of_service = "21".to_string();
+            line.revenue_code = Some("0120".to_string());
+        }
+        let line_count = claim.service_lines.len();
+        let remittances = group_claims(&Payer::default(), "20240315", vec![claim]);
+        let clock = Clock::fixed(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+
+        let interchange = build_interchange(&remittances, clock, &mut ControlNumbers::default());
+        let output = interchange.to_string();
+
+        assert_eq!(interchange.functional_groups.len(), 1);
+        assert_eq!(
+            interchange.functional_groups[0].gs.gs08_version,
+            "005010X223A2"
+        );
+        assert_eq!(output.matches("SV2*0120*HC:").count(), line_count);
+        assert!(!output.contains("SV1*"));
+        assert!(output.contains("CLM*") && output.contains("*11:A:"));
+        assert!(output.contains("DTP*434*RD8*"));
+        assert!(output.contains("CL1*3**01~"));
+
+        let transaction = &interchange.functional_groups[0].transaction_sets[0];
+        let written = output.lines().filter(|line| !line.is_empty()).count();
+        assert_eq!(transaction.se.se01_segment_count as usize, written - 4);
+    }
+}
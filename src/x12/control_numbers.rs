@@ -0,0 +1,105 @@
+//! Interchange, group and transaction set control numbers.
+
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Largest nine-digit control number before wrapping to 1.
+const MAX_CONTROL_NUMBER: u32 = 999_999_999;
+
+/// Last used control numbers, persisted between runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlNumbers {
+    /// Last ISA13 interchange control number.
+    pub interchange: u32,
+
+    /// Last GS06 group control number.
+    pub group: u32,
+
+    /// Last ST02 transaction set control number within the current group.
+    #[serde(skip)]
+    pub transaction: u32,
+}
+
+impl ControlNumbers {
+    /// Loads the control numbers from `path`, starting from zero when it does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).map_err(Error::TomlDe),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    /// Writes the control numbers to `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let content = toml::to_string_pretty(self).map_err(Error::Toml)?;
+        fs::write(path, content).map_err(Error::Io)
+    }
+
+    /// Advances and returns the next nine-digit interchange control number.
+    pub fn next_interchange(&mut self) -> String {
+        self.interchange = next(self.interchange);
+        format!("{:09}", self.interchange)
+    }
+
+    /// Advances and returns the next group control number, restarting transaction numbering.
+    pub fn next_group(&mut self) -> String {
+        self.group = next(self.group);
+        self.transaction = 0;
+        self.group.to_string()
+    }
+
+    /// Advances and returns the next four-digit transaction set control number.
+    pub fn next_transaction(&mut self) -> String {
+        self.transaction = next(self.transaction);
+        format!("{:04}", self.transaction)
+    }
+}
+
+/// The control number after `current`.
+fn next(current: u32) -> u32 {
+    if current >= MAX_CONTROL_NUMBER {
+        1
+    } else {
+        current + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_sequential_control_numbers() {
+        let mut numbers = ControlNumbers::default();
+
+        assert_eq!(numbers.next_interchange(), "000000001");
+        assert_eq!(numbers.next_group(), "1");
+        assert_eq!(numbers.next_transaction(), "0001");
+        assert_eq!(numbers.next_transaction(), "0002");
+        assert_eq!(numbers.next_group(), "2");
+        assert_eq!(numbers.next_transaction(), "0001");
+
+        numbers.interchange = MAX_CONTROL_NUMBER;
+        assert_eq!(numbers.next_interchange(), "000000001");
+    }
+
+    #[test]
+    fn test_control_numbers_persist_between_runs() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::remove_file(temp_file.path()).unwrap();
+
+        let mut numbers = ControlNumbers::load(temp_file.path()).unwrap();
+        assert_eq!(numbers, ControlNumbers::default());
+        numbers.next_interchange();
+        numbers.next_group();
+        numbers.save(temp_file.path()).unwrap();
+
+        let mut reloaded = ControlNumbers::load(temp_file.path()).unwrap();
+        assert_eq!(reloaded.next_interchange(), "000000002");
+        assert_eq!(reloaded.next_group(), "2");
+    }
+}
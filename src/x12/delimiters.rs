@@ -0,0 +1,212 @@
+//! X12 delimiters and element values.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The separators used to write or read an interchange.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Delimiters {
+    /// Element separator (ISA position 4).
+    pub element: char,
+
+    /// Component element separator (ISA16).
+    pub component: char,
+
+    /// Repetition separator (ISA11).
+    pub repetition: char,
+
+    /// Segment terminator.
+    pub segment: char,
+
+    /// Line ending written after each segment terminator.
+    pub line_ending: LineEnding,
+}
+
+/// Line ending written after each segment terminator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineEnding {
+    /// Segments are not separated by line breaks.
+    None,
+
+    /// `\n` after each segment.
+    Lf,
+
+    /// `\r\n` after each segment.
+    CrLf,
+}
+
+impl LineEnding {
+    /// The characters written after each segment.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::None => "",
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self {
+            element: '*',
+            component: ':',
+            repetition: '^',
+            segment: '~',
+            line_ending: LineEnding::Lf,
+        }
+    }
+}
+
+impl Delimiters {
+    /// Checks that the delimiters are distinct and not alphanumeric or whitespace.
+    pub fn validate(&self) -> Result<(), String> {
+        let delimiters = [self.element, self.component, self.repetition, self.segment];
+        for (i, delimiter) in delimiters.iter().enumerate() {
+            let whitespace = delimiter.is_whitespace() && *delimiter != self.segment;
+            if delimiter.is_alphanumeric() || whitespace {
+                return Err(format!("invalid X12 delimiter: {:?}", delimiter));
+            }
+            if delimiters[..i].contains(delimiter) {
+                return Err(format!("duplicate X12 delimiter: {:?}", delimiter));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a segment, dropping trailing empty elements and components.
+    pub fn write_segment(
+        &self,
+        out: &mut dyn fmt::Write,
+        id: &str,
+        elements: &[Element],
+    ) -> fmt::Result {
+        let used = elements
+            .iter()
+            .rposition(|e| !e.is_empty())
+            .map_or(0, |i| i + 1);
+        out.write_str(id)?;
+        for element in &elements[..used] {
+            out.write_char(self.element)?;
+            let components = element.components();
+            for (i, component) in components.iter().enumerate() {
+                if i > 0 {
+                    out.write_char(self.component)?;
+                }
+                out.write_str(component)?;
+            }
+        }
+        out.write_char(self.segment)
+    }
+
+    /// Writes the line ending that follows a segment.
+    pub fn end_segment(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        out.write_str(self.line_ending.as_str())
+    }
+}
+
+/// An element value, made of one or more components.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Element(Vec<String>);
+
+impl Element {
+    /// Creates an element from its components.
+    pub fn composite(components: Vec<String>) -> Self {
+        Self(components)
+    }
+
+    /// The components, without trailing empty ones.
+    pub fn components(&self) -> &[String] {
+        let used = self
+            .0
+            .iter()
+            .rposition(|c| !c.is_empty())
+            .map_or(0, |i| i + 1);
+        &self.0[..used]
+    }
+
+    /// Whether every component is empty.
+    pub fn is_empty(&self) -> bool {
+        self.components().is_empty()
+    }
+
+    /// The first component, or the whole value of a simple element.
+    pub fn value(&self) -> &str {
+        self.0.first().map_or("", String::as_str)
+    }
+}
+
+impl From<String> for Element {
+    fn from(value: String) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl From<&str> for Element {
+    fn from(value: &str) -> Self {
+        Self(vec![value.to_string()])
+    }
+}
+
+impl From<&String> for Element {
+    fn from(value: &String) -> Self {
+        Self(vec![value.clone()])
+    }
+}
+
+impl From<Option<&str>> for Element {
+    fn from(value: Option<&str>) -> Self {
+        value.unwrap_or_default().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_segment_with_custom_delimiters() {
+        let delimiters = Delimiters {
+            element: '|',
+            component: '>',
+            repetition: '!',
+            segment: '\n',
+            line_ending: LineEnding::None,
+        };
+        let elements = [
+            Element::composite(vec!["HC".to_string(), "99213".to_string(), String::new()]),
+            "150.00".into(),
+            Element::default(),
+        ];
+
+        let mut out = String::new();
+        let result = delimiters.write_segment(&mut out, "SVC", &elements);
+        assert!(result.is_ok());
+        assert_eq!(out, "SVC|HC>99213|150.00\n");
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicates() {
+        assert!(Delimiters::default().validate().is_ok());
+
+        let duplicate = Delimiters {
+            component: '*',
+            ..Default::default()
+        };
+        assert!(duplicate.validate().is_err());
+
+        let alphanumeric = Delimiters {
+            element: 'A',
+            ..Default::default()
+        };
+        assert!(alphanumeric.validate().is_err());
+
+        let newline_terminator = Delimiters {
+            segment: '\n',
+            line_ending: LineEnding::None,
+            ..Default::default()
+        };
+        assert!(newline_terminator.validate().is_ok());
+    }
+}
@@ -1,21 +1,38 @@
+//! The ISA/GS/ST envelope structure of an interchange.
 
-
+use super::delimiters::Delimiters;
 use super::segments::*;
 use std::fmt;
 
-
+/// An ISA/IEA interchange.
+#[derive(Debug, Clone)]
 pub struct X12Interchange {
+    /// Interchange control header.
     pub isa: IsaSegment,
+    /// Interchange acknowledgment, written right after the ISA.
+    pub ta1: Option<Ta1Segment>,
+    /// Functional groups in the interchange.
     pub functional_groups: Vec<FunctionalGroup>,
+    /// Interchange control trailer.
     pub iea: IeaSegment,
+    /// Delimiters used when the interchange is displayed.
+    pub delimiters: Delimiters,
 }
 
 impl X12Interchange {
-    
-    pub fn new(sender_id: &str, receiver_id: &str, control_number: &str) -> Self {
+    /// Creates an empty production interchange.
+    pub fn new(
+        sender_id: &str,
+        receiver_id: &str,
+        control_number: &str,
+        date: &str,
+        time: &str,
+    ) -> Self {
         let isa = IsaSegment {
             isa06_sender_id: sender_id.to_string(),
             isa08_receiver_id: receiver_id.to_string(),
+            isa09_date: date.to_string(),
+            isa10_time: time.to_string(),
             isa12_control_number: control_number.to_string(),
             isa13_usage_indicator: 'P', 
         };
@@ -27,53 +44,74 @@ impl X12Interchange {
 
         Self {
             isa,
+            ta1: None,
             functional_groups: Vec::new(),
             iea,
+            delimiters: Delimiters::default(),
         }
     }
 
-    
+    /// Adds a functional group and updates IEA01.
     pub fn add_functional_group(&mut self, group: FunctionalGroup) {
         self.functional_groups.push(group);
         self.iea.iea01_number_of_included_functional_groups = self.functional_groups.len() as u32;
     }
 }
 
-impl fmt::Display for X12Interchange {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        
-        writeln!(f, "{}", self.isa)?;
+impl X12Interchange {
+    /// Writes the interchange using `delimiters`.
+    pub fn write_to(&self, out: &mut dyn fmt::Write, delimiters: &Delimiters) -> fmt::Result {
+        self.isa.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)?;
+
+        if let Some(ref ta1) = self.ta1 {
+            ta1.write_segment(out, delimiters)?;
+            delimiters.end_segment(out)?;
+        }
 
-        
         for group in &self.functional_groups {
-            writeln!(f, "{}", group)?;
+            group.write_to(out, delimiters)?;
         }
 
-        
-        write!(f, "{}", self.iea)
+        self.iea.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)
     }
 }
 
+impl fmt::Display for X12Interchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &self.delimiters)
+    }
+}
 
+/// A GS/GE functional group.
+#[derive(Debug, Clone)]
 pub struct FunctionalGroup {
+    /// Functional group header.
     pub gs: GsSegment,
+    /// Transaction sets in the group.
     pub transaction_sets: Vec<TransactionSet>,
+    /// Functional group trailer.
     pub ge: GeSegment,
 }
 
 impl FunctionalGroup {
-    
+    /// Creates an empty health care claim payment (`HP`) group.
     pub fn new(
         sender_id: &str,
         receiver_id: &str,
         control_number: &str,
-        _date: &str,
-        _time: &str,
+        date: &str,
+        time: &str,
     ) -> Self {
         let gs = GsSegment {
+            gs01_functional_identifier_code: "HP".to_string(),
             gs02_sender_id: sender_id.to_string(),
             gs03_receiver_id: receiver_id.to_string(),
+            gs04_date: date.to_string(),
+            gs05_time: time.to_string(),
             gs06_group_control_number: control_number.to_string(),
+            gs08_version: "005010X221A1".to_string(),
         };
 
         let ge = GeSegment {
@@ -88,40 +126,52 @@ impl FunctionalGroup {
         }
     }
 
-    
+    /// Adds a transaction set and updates GE01.
     pub fn add_transaction_set(&mut self, transaction: TransactionSet) {
         self.transaction_sets.push(transaction);
         self.ge.ge01_number_of_transaction_sets = self.transaction_sets.len() as u32;
     }
 }
 
-impl fmt::Display for FunctionalGroup {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        
-        writeln!(f, "{}", self.gs)?;
+impl FunctionalGroup {
+    /// Writes the group using `delimiters`.
+    pub fn write_to(&self, out: &mut dyn fmt::Write, delimiters: &Delimiters) -> fmt::Result {
+        self.gs.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)?;
 
-        
         for transaction in &self.transaction_sets {
-            writeln!(f, "{}", transaction)?;
+            transaction.write_to(out, delimiters)?;
         }
 
-        
-        write!(f, "{}", self.ge)
+        self.ge.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)
     }
 }
 
+impl fmt::Display for FunctionalGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &Delimiters::default())
+    }
+}
 
+/// An ST/SE transaction set.
+#[derive(Debug, Clone)]
 pub struct TransactionSet {
+    /// Transaction set header.
     pub st: StSegment,
-    pub segments: Vec<Box<dyn X12Segment>>,
+    /// Segments between ST and SE.
+    pub segments: Vec<Segment>,
+    /// Transaction set trailer.
     pub se: SeSegment,
 }
 
 impl TransactionSet {
-    
+    /// Creates an empty 835 transaction set.
     pub fn new(control_number: &str) -> Self {
         let st = StSegment {
+            st01_transaction_set_id: "835".to_string(),
             st02_control_number: control_number.to_string(),
+            st03_implementation_reference: "005010X221A1".to_string(),
         };
 
         let se = SeSegment {
@@ -136,37 +186,46 @@ impl TransactionSet {
         }
     }
 
-    
-    pub fn add_segment<S: X12Segment + 'static>(&mut self, segment: S) {
-        self.segments.push(Box::new(segment));
+    /// Appends a segment and updates SE01.
+    pub fn add_segment<S: Into<Segment>>(&mut self, segment: S) {
+        self.segments.push(segment.into());
         
         self.se.se01_segment_count = (self.segments.len() + 2) as u32;
     }
 }
 
-impl fmt::Display for TransactionSet {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        
-        writeln!(f, "{}", self.st)?;
+impl TransactionSet {
+    /// Writes the transaction set using `delimiters`.
+    pub fn write_to(&self, out: &mut dyn fmt::Write, delimiters: &Delimiters) -> fmt::Result {
+        self.st.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)?;
 
-        
         for segment in &self.segments {
-            writeln!(f, "{}", segment)?;
+            segment.write_segment(out, delimiters)?;
+            delimiters.end_segment(out)?;
         }
 
-        
-        write!(f, "{}", self.se)
+        self.se.write_segment(out, delimiters)?;
+        delimiters.end_segment(out)
+    }
+}
+
+impl fmt::Display for TransactionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, &Delimiters::default())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::x12::segments::*;
+    use crate::money::Money;
+    use crate::x12::delimiters::LineEnding;
 
     #[test]
     fn test_x12_interchange() {
-        let mut interchange = X12Interchange::new("SENDER123", "RECEIVER456", "000000001");
+        let mut interchange =
+            X12Interchange::new("SENDER123", "RECEIVER456", "000000001", "230518", "1200");
 
         let mut group =
             FunctionalGroup::new("SENDER123", "RECEIVER456", "000000001", "20230518", "1200");
@@ -175,9 +234,10 @@ mod tests {
 
         
         let bpr = BprSegment {
-            bpr02_payment_amount: 1000.50,
+            bpr02_payment_amount: Money::from_cents(100050),
             bpr03_credit_debit: 'C',
             bpr04_payment_method: "ACH".to_string(),
+            bpr10_originating_company_id: "1512345678".to_string(),
             bpr16_payment_date: "20230518".to_string(),
         };
         transaction.add_segment(bpr);
@@ -201,5 +261,45 @@ mod tests {
         assert!(x12_output.contains("SE*"));
         assert!(x12_output.contains("GE*"));
         assert!(x12_output.contains("IEA*"));
+        assert!(x12_output.contains("*230518*1200*^*"));
+        assert!(x12_output.contains("GS*HP*SENDER123*RECEIVER456*20230518*1200*"));
+    }
+
+    #[test]
+    fn test_x12_interchange_custom_delimiters() {
+        let mut interchange =
+            X12Interchange::new("SENDER123", "RECEIVER456", "000000001", "230518", "1200");
+        interchange.delimiters = Delimiters {
+            element: '|',
+            component: '>',
+            repetition: '!',
+            segment: '\n',
+            line_ending: LineEnding::None,
+        };
+
+        let mut group =
+            FunctionalGroup::new("SENDER123", "RECEIVER456", "000000001", "20230518", "1200");
+        let mut transaction = TransactionSet::new("0001");
+        transaction.add_segment(PlbSegment {
+            plb01_provider_id: "1234567890".to_string(),
+            plb02_fiscal_period_date: "20231231".to_string(),
+            adjustments: vec![PlbAdjustment {
+                reason_code: "L6".to_string(),
+                reference_id: Some("CLM1".to_string()),
+                amount: Money::from_cents(-125),
+            }],
+        });
+        group.add_transaction_set(transaction);
+        interchange.add_functional_group(group);
+
+        let x12_output = interchange.to_string();
+        let isa: Vec<&str> = x12_output.lines().next().unwrap().split('|').collect();
+        assert_eq!(isa.len(), 17);
+        assert_eq!(isa[11], "!");
+        assert_eq!(isa[16], ">");
+        assert!(x12_output.contains("\nPLB|1234567890|20231231|L6>CLM1|-1.25\n"));
+        assert!(x12_output.ends_with("IEA|1|000000001\n"));
+        assert!(!x12_output.contains('*'));
+        assert!(!x12_output.contains('~'));
     }
 }
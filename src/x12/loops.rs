@@ -0,0 +1,220 @@
+//! The 835 loop hierarchy built from a parsed transaction set.
+
+use super::envelope::TransactionSet;
+use super::parser::ParseError;
+use super::segments::*;
+
+/// An 835 transaction set arranged into its loops.
+#[derive(Debug, Clone)]
+pub struct RemittanceAdvice {
+    /// Financial information (BPR).
+    pub bpr: BprSegment,
+
+    /// Header segments between BPR and the payer loop (TRN, CUR, REF, DTM).
+    pub header: Vec<Segment>,
+
+    /// Loop 1000A payer identification.
+    pub payer: PartyLoop,
+
+    /// Loop 1000B payee identification.
+    pub payee: PartyLoop,
+
+    /// Loop 2000 header numbers, each holding its claims.
+    pub header_numbers: Vec<HeaderNumberLoop>,
+
+    /// Provider level adjustments (PLB) from the summary.
+    pub provider_adjustments: Vec<PlbSegment>,
+}
+
+/// A payer (1000A) or payee (1000B) identification loop.
+#[derive(Debug, Clone)]
+pub struct PartyLoop {
+    /// Party identification (N1).
+    pub n1: N1Segment,
+
+    /// The segments following N1 within the loop.
+    pub segments: Vec<Segment>,
+}
+
+/// Loop 2000 header number with its provider summaries and claims.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderNumberLoop {
+    /// Header number (LX); `None` for claims sent without an LX.
+    pub lx: Option<LxSegment>,
+
+    /// Provider summary information (TS3).
+    pub ts3: Option<Ts3Segment>,
+
+    /// Provider supplemental summary information (TS2).
+    pub ts2: Option<Ts2Segment>,
+
+    /// Loop 2100 claims under the header number.
+    pub claims: Vec<ClaimPaymentLoop>,
+}
+
+/// Loop 2100 claim payment information.
+#[derive(Debug, Clone)]
+pub struct ClaimPaymentLoop {
+    /// Claim payment information (CLP).
+    pub clp: ClpSegment,
+
+    /// The claim level segments following CLP.
+    pub segments: Vec<Segment>,
+
+    /// Loop 2110 service lines of the claim.
+    pub service_lines: Vec<ServicePaymentLoop>,
+}
+
+/// Loop 2110 service payment information.
+#[derive(Debug, Clone)]
+pub struct ServicePaymentLoop {
+    /// Service payment information (SVC).
+    pub svc: SvcSegment,
+
+    /// The service line segments following SVC.
+    pub segments: Vec<Segment>,
+}
+
+impl RemittanceAdvice {
+    /// Arranges the segments of `transaction` into the 835 loops.
+    pub fn from_transaction(transaction: &TransactionSet) -> Result<Self, ParseError> {
+        let error = |index: usize, segment: &Segment, message: &str| ParseError {
+            segment_id: Some(segment.id().to_string()),
+            ..ParseError::new(index + 2, message)
+        };
+
+        let mut segments = transaction.segments.iter().enumerate();
+        let bpr = match segments.next() {
+            Some((_, Segment::Bpr(bpr))) => bpr.clone(),
+            Some((i, segment)) => return Err(error(i, segment, "expected BPR after ST")),
+            None => return Err(ParseError::new(2, "expected BPR after ST")),
+        };
+
+        let mut header = Vec::new();
+        let mut payer: Option<PartyLoop> = None;
+        let mut payee: Option<PartyLoop> = None;
+        let mut header_numbers: Vec<HeaderNumberLoop> = Vec::new();
+        let mut provider_adjustments = Vec::new();
+
+        for (i, segment) in segments {
+            if !provider_adjustments.is_empty() && !matches!(segment, Segment::Plb(_)) {
+                return Err(error(i, segment, "only PLB segments may follow PLB"));
+            }
+            match segment {
+                Segment::N1(n1) if n1.n101_entity_id == "PR" => {
+                    if payer.is_some() || !header_numbers.is_empty() {
+                        return Err(error(i, segment, "unexpected payer identification loop"));
+                    }
+                    payer = Some(PartyLoop {
+                        n1: n1.clone(),
+                        segments: Vec::new(),
+                    });
+                }
+                Segment::N1(n1) if n1.n101_entity_id == "PE" => {
+                    if payer.is_none() || payee.is_some() || !header_numbers.is_empty() {
+                        return Err(error(i, segment, "unexpected payee identification loop"));
+                    }
+                    payee = Some(PartyLoop {
+                        n1: n1.clone(),
+                        segments: Vec::new(),
+                    });
+                }
+                _ if payee.is_none() => {
+                    let is_detail = matches!(
+                        segment,
+                        Segment::Lx(_)
+                            | Segment::Ts3(_)
+                            | Segment::Ts2(_)
+                            | Segment::Clp(_)
+                            | Segment::Svc(_)
+                            | Segment::Plb(_)
+                    );
+                    match payer {
+                        _ if is_detail => {
+                            return Err(error(i, segment, "missing payee identification loop"))
+                        }
+                        Some(ref mut payer) => payer.segments.push(segment.clone()),
+                        None => header.push(segment.clone()),
+                    }
+                }
+                Segment::Lx(lx) => header_numbers.push(HeaderNumberLoop {
+                    lx: Some(lx.clone()),
+                    ..Default::default()
+                }),
+                Segment::Ts3(ts3) => match header_numbers.last_mut() {
+                    Some(number) if number.ts3.is_none() && number.claims.is_empty() => {
+                        number.ts3 = Some(ts3.clone())
+                    }
+                    _ => return Err(error(i, segment, "TS3 must follow LX")),
+                },
+                Segment::Ts2(ts2) => match header_numbers.last_mut() {
+                    Some(number) if number.ts2.is_none() && number.claims.is_empty() => {
+                        number.ts2 = Some(ts2.clone())
+                    }
+                    _ => return Err(error(i, segment, "TS2 must follow LX or TS3")),
+                },
+                Segment::Clp(clp) => {
+                    if header_numbers.is_empty() {
+                        header_numbers.push(HeaderNumberLoop::default());
+                    }
+                    if let Some(number) = header_numbers.last_mut() {
+                        number.claims.push(ClaimPaymentLoop {
+                            clp: clp.clone(),
+                            segments: Vec::new(),
+                            service_lines: Vec::new(),
+                        });
+                    }
+                }
+                Segment::Svc(svc) => match header_numbers
+                    .last_mut()
+                    .and_then(|number| number.claims.last_mut())
+                {
+                    Some(claim) => claim.service_lines.push(ServicePaymentLoop {
+                        svc: svc.clone(),
+                        segments: Vec::new(),
+                    }),
+                    None => return Err(error(i, segment, "SVC must follow CLP")),
+                },
+                Segment::Plb(plb) => provider_adjustments.push(plb.clone()),
+                _ if header_numbers.is_empty() => {
+                    if let Some(ref mut payee) = payee {
+                        payee.segments.push(segment.clone());
+                    }
+                }
+                _ => {
+                    let claim = header_numbers
+                        .last_mut()
+                        .and_then(|number| number.claims.last_mut());
+                    match claim {
+                        Some(claim) => match claim.service_lines.last_mut() {
+                            Some(line) => line.segments.push(segment.clone()),
+                            None => claim.segments.push(segment.clone()),
+                        },
+                        None => return Err(error(i, segment, "segment is outside of any loop")),
+                    }
+                }
+            }
+        }
+
+        let end = transaction.segments.len() + 2;
+        let payer =
+            payer.ok_or_else(|| ParseError::new(end, "missing payer identification loop"))?;
+        let payee =
+            payee.ok_or_else(|| ParseError::new(end, "missing payee identification loop"))?;
+        Ok(Self {
+            bpr,
+            header,
+            payer,
+            payee,
+            header_numbers,
+            provider_adjustments,
+        })
+    }
+
+    /// Every claim, across all header numbers.
+    pub fn claims(&self) -> impl Iterator<Item = &ClaimPaymentLoop> {
+        self.header_numbers
+            .iter()
+            .flat_map(|number| number.claims.iter())
+    }
+}
@@ -1,6 +1,11 @@
+//! X12 envelopes, segments, delimiters and parsing.
 
-
+pub mod control_numbers;
+pub mod delimiters;
 pub mod envelope;
+pub mod loops;
+pub mod parser;
+pub mod schema;
 pub mod segments;
 
 pub use envelope::*;
@@ -0,0 +1,901 @@
+//! Parsing of X12 text into segments and interchanges.
+
+use super::delimiters::{Delimiters, Element, LineEnding};
+use super::envelope::{FunctionalGroup, TransactionSet, X12Interchange};
+use super::loops::RemittanceAdvice;
+use super::segments::*;
+use std::fmt;
+use std::io::{self, BufRead, Cursor, Read};
+use std::str::FromStr;
+
+/// Number of elements in an ISA segment.
+const ISA_ELEMENT_COUNT: usize = 16;
+
+/// Bytes read to detect the delimiters from the ISA.
+const HEADER_PEEK: u64 = 1024;
+
+/// An error found while parsing X12 input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based position of the segment the error was found in.
+    pub segment: usize,
+
+    /// Byte offset of the segment, when known.
+    pub offset: Option<usize>,
+
+    /// Identifier of the segment, when known.
+    pub segment_id: Option<String>,
+
+    /// 1-based position of the offending element.
+    pub element: Option<usize>,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ParseError {
+    /// Creates an error for the segment at `segment`.
+    pub fn new(segment: usize, message: impl Into<String>) -> Self {
+        Self {
+            segment,
+            offset: None,
+            segment_id: None,
+            element: None,
+            message: message.into(),
+        }
+    }
+
+    /// Creates an error located at `raw`.
+    pub fn at(raw: &RawSegment, element: Option<usize>, message: impl Into<String>) -> Self {
+        Self {
+            segment: raw.position,
+            offset: Some(raw.offset),
+            segment_id: Some(raw.id.clone()),
+            element,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "segment {}", self.segment)?;
+        if let Some(ref id) = self.segment_id {
+            match self.element {
+                Some(element) => write!(f, " ({}{:02})", id, element)?,
+                None => write!(f, " ({})", id)?,
+            }
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {}", offset)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A segment split into elements but not yet interpreted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawSegment {
+    /// Segment identifier.
+    pub id: String,
+
+    /// Elements in position order, without the identifier.
+    pub elements: Vec<Element>,
+
+    /// 1-based position of the segment in the input.
+    pub position: usize,
+
+    /// Byte offset of the segment in the input.
+    pub offset: usize,
+
+    /// 1-based line number the segment starts on.
+    pub line: usize,
+}
+
+/// Detects the delimiters from the ISA segment at the start of `input`.
+pub fn detect_delimiters(input: &str) -> Result<Delimiters, ParseError> {
+    let start = input.len() - input.trim_start().len();
+    let isa = &input[start..];
+    if !isa.starts_with("ISA") {
+        return Err(ParseError {
+            offset: Some(start),
+            ..ParseError::new(1, "interchange must start with an ISA segment")
+        });
+    }
+
+    let element = isa[3..]
+        .chars()
+        .next()
+        .ok_or_else(|| ParseError::new(1, "ISA segment is truncated"))?;
+    let separators: Vec<usize> = isa
+        .char_indices()
+        .filter(|(_, c)| *c == element)
+        .map(|(i, _)| i)
+        .take(ISA_ELEMENT_COUNT)
+        .collect();
+    let last = separators
+        .get(ISA_ELEMENT_COUNT - 1)
+        .ok_or_else(|| ParseError::new(1, "ISA segment must have 16 elements"))?;
+    let mut tail = isa[last + element.len_utf8()..].chars();
+    let (component, segment) = match (tail.next(), tail.next()) {
+        (Some(component), Some(segment)) => (component, segment),
+        _ => return Err(ParseError::new(1, "ISA segment is truncated")),
+    };
+    let rest = tail.as_str();
+
+    let isa11 = &isa[separators[10] + element.len_utf8()..separators[11]];
+    let repetition = match isa11.chars().next() {
+        Some(c) if isa11.len() == c.len_utf8() && !c.is_alphanumeric() => c,
+        _ => Delimiters::default().repetition,
+    };
+    let line_ending = if segment == '\n' {
+        LineEnding::None
+    } else if rest.starts_with("\r\n") {
+        LineEnding::CrLf
+    } else if rest.starts_with('\n') {
+        LineEnding::Lf
+    } else {
+        LineEnding::None
+    };
+
+    let delimiters = Delimiters {
+        element,
+        component,
+        repetition,
+        segment,
+        line_ending,
+    };
+    delimiters
+        .validate()
+        .map_err(|message| ParseError::new(1, message))?;
+    Ok(delimiters)
+}
+
+/// Splits `input` into raw segments.
+pub fn tokenize(input: &str, delimiters: &Delimiters) -> Vec<RawSegment> {
+    let mut segments = Vec::new();
+    let mut offset = 0;
+    let mut line = 1;
+    for piece in input.split(delimiters.segment) {
+        let trimmed = piece.trim_start_matches(['\r', '\n', ' ', '\t']);
+        let leading = &piece[..piece.len() - trimmed.len()];
+        let start = offset + leading.len();
+        let start_line = line + leading.matches('\n').count();
+        offset += piece.len() + delimiters.segment.len_utf8();
+        line += piece.matches('\n').count() + usize::from(delimiters.segment == '\n');
+        if trimmed.trim().is_empty() {
+            continue;
+        }
+        let position = segments.len() + 1;
+        let segment = split_segment(trimmed, delimiters, position, start, start_line);
+        segments.push(segment);
+    }
+    segments
+}
+
+/// Splits one segment into its identifier and elements; ISA elements are never split into
+/// components.
+fn split_segment(
+    text: &str,
+    delimiters: &Delimiters,
+    position: usize,
+    offset: usize,
+    line: usize,
+) -> RawSegment {
+    let mut fields = text.split(delimiters.element);
+    let id = fields.next().unwrap_or_default().to_string();
+    let elements = fields
+        .map(|field| {
+            if id == "ISA" {
+                Element::from(field)
+            } else {
+                Element::composite(
+                    field
+                        .split(delimiters.component)
+                        .map(str::to_string)
+                        .collect(),
+                )
+            }
+        })
+        .collect();
+    RawSegment {
+        id,
+        elements,
+        position,
+        offset,
+        line,
+    }
+}
+
+/// Reads raw segments one at a time from a buffered reader.
+pub struct SegmentReader<R> {
+    reader: io::Chain<Cursor<Vec<u8>>, R>,
+    detected: Result<Delimiters, ParseError>,
+    delimiters: Delimiters,
+    terminator: Vec<u8>,
+    buffer: Vec<u8>,
+    offset: usize,
+    line: usize,
+    position: usize,
+}
+
+impl<R: BufRead> SegmentReader<R> {
+    /// Creates a reader, detecting the delimiters from the start of the input.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = Vec::new();
+        reader.by_ref().take(HEADER_PEEK).read_to_end(&mut header)?;
+        let detected = detect_delimiters(&String::from_utf8_lossy(&header));
+        let delimiters = detected.clone().unwrap_or_default();
+        Ok(Self {
+            reader: Cursor::new(header).chain(reader),
+            detected,
+            delimiters,
+            terminator: delimiters.segment.to_string().into_bytes(),
+            buffer: Vec::new(),
+            offset: 0,
+            line: 1,
+            position: 0,
+        })
+    }
+
+    /// The detected delimiters, or why they could not be detected.
+    pub fn delimiters(&self) -> Result<&Delimiters, &ParseError> {
+        self.detected.as_ref()
+    }
+
+    /// Number of bytes consumed so far.
+    pub fn bytes_read(&self) -> usize {
+        self.offset
+    }
+
+    /// Reads up to and including the next segment terminator into the buffer.
+    fn read_piece(&mut self) -> io::Result<usize> {
+        let last = self.terminator[self.terminator.len() - 1];
+        self.buffer.clear();
+        loop {
+            let read = self.reader.read_until(last, &mut self.buffer)?;
+            if read == 0 || self.buffer.ends_with(&self.terminator) {
+                return Ok(self.buffer.len());
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SegmentReader<R> {
+    type Item = io::Result<RawSegment>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let read = match self.read_piece() {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(e) => return Some(Err(e)),
+            };
+            let end = if self.buffer.ends_with(&self.terminator) {
+                read - self.terminator.len()
+            } else {
+                read
+            };
+            let piece = String::from_utf8_lossy(&self.buffer[..end]);
+            let trimmed = piece.trim_start_matches(['\r', '\n', ' ', '\t']);
+            let leading = &piece[..piece.len() - trimmed.len()];
+            let start = self.offset + leading.len();
+            let line = self.line + leading.matches('\n').count();
+            self.offset += read;
+            self.line += self.buffer.iter().filter(|b| **b == b'\n').count();
+            if trimmed.trim().is_empty() {
+                continue;
+            }
+            self.position += 1;
+            let segment = split_segment(trimmed, &self.delimiters, self.position, start, line);
+            return Some(Ok(segment));
+        }
+    }
+}
+
+/// Interprets a raw segment as a typed segment.
+pub fn parse_segment(raw: &RawSegment, delimiters: &Delimiters) -> Result<Segment, ParseError> {
+    let r = Reader {
+        raw,
+        component: delimiters.component,
+    };
+    let segment = match raw.id.as_str() {
+        "ISA" => Segment::Isa(IsaSegment {
+            isa06_sender_id: r.text(6)?.trim_end().to_string(),
+            isa08_receiver_id: r.text(8)?.trim_end().to_string(),
+            isa09_date: r.text(9)?,
+            isa10_time: r.text(10)?,
+            isa12_control_number: r.text(13)?,
+            isa13_usage_indicator: r.char(15)?,
+        }),
+        "GS" => Segment::Gs(GsSegment {
+            gs01_functional_identifier_code: r.text(1)?,
+            gs02_sender_id: r.text(2)?,
+            gs03_receiver_id: r.text(3)?,
+            gs04_date: r.text(4)?,
+            gs05_time: r.text(5)?,
+            gs06_group_control_number: r.text(6)?,
+            gs08_version: r.text(8)?,
+        }),
+        "ST" => {
+            if r.text(1)? != "835" {
+                return Err(r.error(1, "only 835 transaction sets are supported"));
+            }
+            Segment::St(StSegment {
+                st01_transaction_set_id: r.text(1)?,
+                st02_control_number: r.text(2)?,
+                st03_implementation_reference: r.optional_text(3).unwrap_or_default(),
+            })
+        }
+        "BPR" => Segment::Bpr(BprSegment {
+            bpr02_payment_amount: r.number(2)?,
+            bpr03_credit_debit: r.char(3)?,
+            bpr04_payment_method: r.text(4)?,
+            bpr10_originating_company_id: r.optional_text(10).unwrap_or_default(),
+            bpr16_payment_date: r.text(16)?,
+        }),
+        "TRN" => Segment::Trn(TrnSegment {
+            trn02_reference_id: r.text(2)?,
+            trn03_orig_company_id: r.text(3)?,
+        }),
+        "DTM" => Segment::Dtm(DtmSegment {
+            dtm01_qualifier: r.text(1)?,
+            dtm02_date: r.text(2)?,
+        }),
+        "N1" => Segment::N1(N1Segment {
+            n101_entity_id: r.text(1)?,
+            n102_name: r.optional_text(2).unwrap_or_default(),
+            n103_id_qual: r.optional_text(3).unwrap_or_default(),
+            n104_id: r.optional_text(4).unwrap_or_default(),
+        }),
+        "N3" => Segment::N3(N3Segment {
+            n301_address_line1: r.text(1)?,
+            n302_address_line2: r.optional_text(2),
+        }),
+        "N4" => Segment::N4(N4Segment {
+            n401_city: r.text(1)?,
+            n402_state: r.optional_text(2).unwrap_or_default(),
+            n403_postal_code: r.optional_text(3).unwrap_or_default(),
+        }),
+        "REF" => Segment::Ref(RefSegment {
+            ref01_qualifier: r.text(1)?,
+            ref02_identifier: r.text(2)?,
+        }),
+        "PER" => Segment::Per(PerSegment {
+            per01_function_code: r.text(1)?,
+            per02_name: r.optional_text(2),
+            communications: (3..raw.elements.len())
+                .step_by(2)
+                .filter_map(|i| Some((r.optional_text(i)?, r.optional_text(i + 1)?)))
+                .collect(),
+        }),
+        "RDM" => Segment::Rdm(RdmSegment {
+            rdm01_transmission_code: r.text(1)?,
+            rdm02_name: r.optional_text(2),
+            rdm03_communication_number: r.optional_text(3),
+        }),
+        "LX" => Segment::Lx(LxSegment {
+            lx01_assigned_number: r.number(1)?,
+        }),
+        "TS3" => Segment::Ts3(Ts3Segment {
+            ts301_provider_id: r.text(1)?,
+            ts302_facility_type: r.text(2)?,
+            ts303_fiscal_period_date: r.text(3)?,
+            ts304_claim_count: r.number(4)?,
+            ts305_total_charge: r.number(5)?,
+            ts313_msp_primary_payer: r.optional_number(13)?,
+            ts315_non_lab_charges: r.optional_number(15)?,
+            ts317_hcpcs_reported_charges: r.optional_number(17)?,
+            ts318_hcpcs_payable: r.optional_number(18)?,
+            ts320_professional_component: r.optional_number(20)?,
+            ts321_msp_patient_liability_met: r.optional_number(21)?,
+            ts322_patient_reimbursement: r.optional_number(22)?,
+            ts323_pip_claim_count: r.optional_number(23)?,
+            ts324_pip_adjustment: r.optional_number(24)?,
+        }),
+        "TS2" => Segment::Ts2(Ts2Segment {
+            ts201_drg_amount: r.optional_number(1)?,
+            ts202_federal_specific_amount: r.optional_number(2)?,
+            ts203_hospital_specific_amount: r.optional_number(3)?,
+            ts204_disproportionate_share_amount: r.optional_number(4)?,
+            ts205_capital_amount: r.optional_number(5)?,
+            ts206_indirect_medical_education_amount: r.optional_number(6)?,
+            ts207_outlier_days: r.optional_number(7)?,
+            ts208_day_outlier_amount: r.optional_number(8)?,
+            ts209_cost_outlier_amount: r.optional_number(9)?,
+            ts210_average_length_of_stay: r.optional_number(10)?,
+            ts211_discharges: r.optional_number(11)?,
+            ts212_cost_report_days: r.optional_number(12)?,
+            ts213_covered_days: r.optional_number(13)?,
+            ts214_noncovered_days: r.optional_number(14)?,
+            ts215_msp_pass_through_amount: r.optional_number(15)?,
+            ts216_average_drg_weight: r.optional_number(16)?,
+            ts217_pps_capital_fsp_drg_amount: r.optional_number(17)?,
+            ts218_pps_capital_hsp_drg_amount: r.optional_number(18)?,
+            ts219_pps_dsh_drg_amount: r.optional_number(19)?,
+        }),
+        "CLP" => Segment::Clp(ClpSegment {
+            clp01_claim_id: r.optional_text(1).unwrap_or_default(),
+            clp02_claim_status: r.text(2)?,
+            clp03_charge_amount: r.number(3)?,
+            clp04_paid_amount: r.number(4)?,
+            clp05_patient_responsibility: r.optional_number(5)?.unwrap_or_default(),
+            clp06_claim_type: r.optional_text(6).unwrap_or_default(),
+            clp07_payer_claim_number: r.optional_text(7).unwrap_or_default(),
+            clp08_facility_type: r.optional_text(8).unwrap_or_default(),
+            clp09_frequency_code: r.optional_text(9).unwrap_or_default(),
+        }),
+        "NM1" => Segment::Nm1(Nm1Segment {
+            nm101_entity_id: r.text(1)?,
+            nm102_entity_type: r.text(2)?,
+            nm103_last_name: r.optional_text(3).unwrap_or_default(),
+            nm104_first_name: r.optional_text(4),
+            nm105_middle_name: r.optional_text(5),
+            nm107_suffix: r.optional_text(7),
+            nm108_id_qualifier: r.optional_text(8),
+            nm109_id: r.optional_text(9),
+        }),
+        "AMT" => Segment::Amt(AmtSegment {
+            amt01_qualifier: r.text(1)?,
+            amt02_amount: r.number(2)?,
+        }),
+        "QTY" => Segment::Qty(QtySegment {
+            qty01_qualifier: r.text(1)?,
+            qty02_quantity: r.number(2)?,
+        }),
+        "SVC" => Segment::Svc(SvcSegment {
+            svc01_procedure: r.procedure(1)?,
+            svc02_charge_amount: r.number(2)?,
+            svc03_paid_amount: r.number(3)?,
+            svc04_revenue_code: r.optional_text(4),
+            svc05_units: r.optional_number(5)?.unwrap_or(1.0),
+            svc06_original_procedure: match r.element(6) {
+                Some(_) => Some(r.procedure(6)?),
+                None => None,
+            },
+            svc07_original_units: r.optional_number(7)?,
+        }),
+        "CAS" => {
+            let mut adjustments = Vec::new();
+            for i in (2..raw.elements.len()).step_by(3) {
+                if r.element(i).is_none() {
+                    continue;
+                }
+                adjustments.push(CasAdjustment {
+                    reason_code: r.text(i)?,
+                    amount: r.number(i + 1)?,
+                    quantity: r.optional_number(i + 2)?,
+                });
+            }
+            Segment::Cas(CasSegment {
+                cas01_group_code: r.text(1)?,
+                adjustments,
+            })
+        }
+        "LQ" => Segment::Lq(LqSegment {
+            lq01_code_list_qualifier: r.text(1)?,
+            lq02_remark_code: r.text(2)?,
+        }),
+        "PLB" => {
+            let mut adjustments = Vec::new();
+            for i in (3..raw.elements.len()).step_by(2) {
+                let Some(identifier) = r.element(i) else {
+                    continue;
+                };
+                let components = identifier.components();
+                adjustments.push(PlbAdjustment {
+                    reason_code: components[0].clone(),
+                    reference_id: components.get(1).filter(|c| !c.is_empty()).cloned(),
+                    amount: r.number(i + 1)?,
+                });
+            }
+            Segment::Plb(PlbSegment {
+                plb01_provider_id: r.text(1)?,
+                plb02_fiscal_period_date: r.text(2)?,
+                adjustments,
+            })
+        }
+        "SE" => Segment::Se(SeSegment {
+            se01_segment_count: r.number(1)?,
+            se02_transaction_control_number: r.text(2)?,
+        }),
+        "GE" => Segment::Ge(GeSegment {
+            ge01_number_of_transaction_sets: r.number(1)?,
+            ge02_group_control_number: r.text(2)?,
+        }),
+        "IEA" => Segment::Iea(IeaSegment {
+            iea01_number_of_included_functional_groups: r.number(1)?,
+            iea02_interchange_control_number: r.text(2)?,
+        }),
+        "TA1" => Segment::Ta1(Ta1Segment {
+            ta101_interchange_control_number: r.text(1)?,
+            ta102_date: r.text(2)?,
+            ta103_time: r.text(3)?,
+            ta104_acknowledgment_code: r.char(4)?,
+            ta105_note_code: r.text(5)?,
+        }),
+        _ => Segment::Other(GenericSegment {
+            id: raw.id.clone(),
+            elements: raw.elements.clone(),
+        }),
+    };
+    Ok(segment)
+}
+
+/// Parses an X12 835 interchange.
+pub fn parse(input: &str) -> Result<X12Interchange, ParseError> {
+    let delimiters = detect_delimiters(input)?;
+    let raw_segments = tokenize(input, &delimiters);
+    let mut segments = Vec::with_capacity(raw_segments.len());
+    for raw in &raw_segments {
+        segments.push(parse_segment(raw, &delimiters)?);
+    }
+
+    let unexpected = |i: usize, expected: &str| {
+        ParseError::at(
+            &raw_segments[i],
+            None,
+            format!("expected {}, found {}", expected, raw_segments[i].id),
+        )
+    };
+    let end_of_input = |expected: &str| {
+        let position = raw_segments.len() + 1;
+        ParseError {
+            offset: Some(input.len()),
+            ..ParseError::new(
+                position,
+                format!("expected {}, found end of input", expected),
+            )
+        }
+    };
+
+    let mut iter = segments.into_iter().enumerate().peekable();
+    let isa = match iter.next() {
+        Some((_, Segment::Isa(isa))) => isa,
+        Some((i, _)) => return Err(unexpected(i, "ISA")),
+        None => return Err(end_of_input("ISA")),
+    };
+    let ta1 = match iter.next_if(|(_, segment)| matches!(segment, Segment::Ta1(_))) {
+        Some((_, Segment::Ta1(ta1))) => Some(ta1),
+        _ => None,
+    };
+
+    let mut functional_groups = Vec::new();
+    let iea = loop {
+        let gs = match iter.next() {
+            Some((_, Segment::Gs(gs))) => gs,
+            Some((_, Segment::Iea(iea))) => break iea,
+            Some((i, _)) => return Err(unexpected(i, "GS or IEA")),
+            None => return Err(end_of_input("IEA")),
+        };
+
+        let mut transaction_sets = Vec::new();
+        let ge = loop {
+            let (st_index, st) = match iter.next() {
+                Some((i, Segment::St(st))) => (i, st),
+                Some((_, Segment::Ge(ge))) => break ge,
+                Some((i, _)) => return Err(unexpected(i, "ST or GE")),
+                None => return Err(end_of_input("GE")),
+            };
+
+            let mut body = Vec::new();
+            let se = loop {
+                match iter.next() {
+                    Some((_, Segment::Se(se))) => break se,
+                    Some((
+                        i,
+                        Segment::Isa(_)
+                        | Segment::Gs(_)
+                        | Segment::St(_)
+                        | Segment::Ge(_)
+                        | Segment::Iea(_),
+                    )) => return Err(unexpected(i, "SE")),
+                    Some((_, segment)) => body.push(segment),
+                    None => return Err(end_of_input("SE")),
+                }
+            };
+
+            let transaction = TransactionSet {
+                st,
+                segments: body,
+                se,
+            };
+            if let Err(mut error) = RemittanceAdvice::from_transaction(&transaction) {
+                let raw = &raw_segments[st_index + error.segment - 1];
+                error.segment = raw.position;
+                error.offset = Some(raw.offset);
+                return Err(error);
+            }
+            transaction_sets.push(transaction);
+        };
+
+        functional_groups.push(FunctionalGroup {
+            gs,
+            transaction_sets,
+            ge,
+        });
+    };
+
+    if let Some((i, _)) = iter.next() {
+        return Err(unexpected(i, "end of input after IEA"));
+    }
+
+    Ok(X12Interchange {
+        isa,
+        ta1,
+        functional_groups,
+        iea,
+        delimiters,
+    })
+}
+
+/// Reads typed elements from a raw segment.
+struct Reader<'a> {
+    raw: &'a RawSegment,
+    component: char,
+}
+
+impl Reader<'_> {
+    fn error(&self, element: usize, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.raw, Some(element), message)
+    }
+
+    fn element(&self, position: usize) -> Option<&Element> {
+        self.raw
+            .elements
+            .get(position - 1)
+            .filter(|element| !element.is_empty())
+    }
+
+    fn optional_text(&self, position: usize) -> Option<String> {
+        self.element(position)
+            .map(|element| element.components().join(&self.component.to_string()))
+    }
+
+    fn text(&self, position: usize) -> Result<String, ParseError> {
+        self.optional_text(position)
+            .ok_or_else(|| self.error(position, "missing required element"))
+    }
+
+    fn char(&self, position: usize) -> Result<char, ParseError> {
+        let text = self.text(position)?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(self.error(
+                position,
+                format!("expected a single character, found {:?}", text),
+            )),
+        }
+    }
+
+    fn optional_number<T: FromStr>(&self, position: usize) -> Result<Option<T>, ParseError> {
+        match self.element(position) {
+            Some(element) => {
+                element.value().parse().map(Some).map_err(|_| {
+                    self.error(position, format!("invalid number {:?}", element.value()))
+                })
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn number<T: FromStr>(&self, position: usize) -> Result<T, ParseError> {
+        self.optional_number(position)?
+            .ok_or_else(|| self.error(position, "missing required element"))
+    }
+
+    fn procedure(&self, position: usize) -> Result<MedicalProcedure, ParseError> {
+        let element = self
+            .element(position)
+            .ok_or_else(|| self.error(position, "missing required element"))?;
+        let components = element.components();
+        let qualifier = ProductServiceQualifier::from_code(&components[0]).ok_or_else(|| {
+            self.error(
+                position,
+                format!("unknown product/service qualifier {:?}", components[0]),
+            )
+        })?;
+        let code = components
+            .get(1)
+            .filter(|code| !code.is_empty())
+            .cloned()
+            .ok_or_else(|| self.error(position, "missing procedure code"))?;
+        Ok(MedicalProcedure {
+            qualifier,
+            code,
+            modifiers: components
+                .iter()
+                .skip(2)
+                .take(4)
+                .filter(|m| !m.is_empty())
+                .cloned()
+                .collect(),
+            description: components.get(6).filter(|d| !d.is_empty()).cloned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::generator::generate_to_string;
+    use crate::money::Money;
+    use chrono::NaiveDate;
+
+    fn generated_interchange(config: Config) -> String {
+        generate_to_string(Config {
+            claim_count: 12,
+            seed: Some(21),
+            as_of: NaiveDate::from_ymd_opt(2024, 3, 15),
+            batch_remittances: true,
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_parse_round_trips_generated_output() {
+        let input = generated_interchange(Config {
+            anomaly_rate: 0.5,
+            ..Default::default()
+        });
+
+        let interchange = parse(&input).unwrap();
+
+        assert_eq!(interchange.to_string(), input);
+        let group = &interchange.functional_groups[0];
+        assert_eq!(interchange.isa.isa12_control_number, "000000001");
+        assert_eq!(group.gs.gs04_date, "20240315");
+        let claims: usize = group
+            .transaction_sets
+            .iter()
+            .map(|t| {
+                RemittanceAdvice::from_transaction(t)
+                    .unwrap()
+                    .claims()
+                    .count()
+            })
+            .sum();
+        assert_eq!(claims, 12);
+    }
+
+    #[test]
+    fn test_parse_detects_custom_delimiters() {
+        let config = Config {
+            delimiters: Delimiters {
+                element: '|',
+                component: '>',
+                repetition: '!',
+                segment: '\n',
+                line_ending: LineEnding::None,
+            },
+            anomaly_rate: 0.0,
+            ..Default::default()
+        };
+        let input = generated_interchange(config.clone());
+
+        let interchange = parse(&input).unwrap();
+
+        assert_eq!(interchange.delimiters, config.delimiters);
+        assert_eq!(interchange.to_string(), input);
+
+        let raw = &tokenize("N1|PR|ACME>WEST|XV|12345\n", &config.delimiters)[0];
+        match parse_segment(raw, &config.delimiters).unwrap() {
+            Segment::N1(n1) => assert_eq!(n1.n102_name, "ACME>WEST"),
+            other => panic!("expected N1, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_loop_hierarchy() {
+        let input = "ISA*00*          *00*          *ZZ*SENDER         *ZZ*RECEIVER       *240315*1200*^*00501*000000001*0*P*:~\n\
+GS*HP*SENDER*RECEIVER*20240315*1200*1*X*005010X221A1~\n\
+ST*835*0001*005010X221A1~\n\
+BPR*I*150.00*C*ACH*CCP*01*999999999*DA*123456*1512345678**01*999988880*DA*98765*20240315~\n\
+TRN*1*12345*1512345678~\n\
+N1*PR*PAYER~\n\
+N3*1 MAIN ST~\n\
+N1*PE*CLINIC*XX*1234567890~\n\
+LX*1~\n\
+CLP*CLM1*1*200.00*150.00**12*PCN1*11*1~\n\
+CAS*PR*1*50.00~\n\
+SVC*HC:99213:25*200.00*150.00**1~\n\
+DTM*472*20240301~\n\
+CAS*CO*45*50.00~\n\
+PLB*1234567890*20241231*L6:CLM1*-1.00~\n\
+SE*14*0001~\n\
+GE*1*1~\n\
+IEA*1*000000001~\n";
+
+        let interchange = parse(input).unwrap();
+        let transaction = &interchange.functional_groups[0].transaction_sets[0];
+        let remittance = RemittanceAdvice::from_transaction(transaction).unwrap();
+
+        assert_eq!(
+            remittance.bpr.bpr02_payment_amount,
+            Money::from_cents(15000)
+        );
+        assert_eq!(remittance.payer.segments.len(), 1);
+        assert_eq!(remittance.payee.n1.n104_id, "1234567890");
+        let claim = remittance.claims().next().unwrap();
+        assert_eq!(claim.clp.clp01_claim_id, "CLM1");
+        assert_eq!(claim.segments.len(), 1);
+        let line = &claim.service_lines[0];
+        assert_eq!(line.svc.svc01_procedure.modifiers, ["25"]);
+        assert_eq!(line.segments.len(), 2);
+        assert_eq!(
+            remittance.provider_adjustments[0].adjustments[0].amount,
+            Money::from_cents(-100)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_are_positioned() {
+        let input = "ISA*00*          *00*          *ZZ*SENDER         *ZZ*RECEIVER       *240315*1200*^*00501*000000001*0*P*:~\
+GS*HP*SENDER*RECEIVER*20240315*1200*1*X*005010X221A1~\
+ST*835*0001*005010X221A1~\
+BPR*I*abc*C*ACH*CCP*01*999999999*DA*123456*1512345678**01*999988880*DA*98765*20240315~";
+
+        let error = parse(input).unwrap_err();
+        assert_eq!(error.segment, 4);
+        assert_eq!(error.segment_id.as_deref(), Some("BPR"));
+        assert_eq!(error.element, Some(2));
+        assert_eq!(error.offset, Some(input.find("BPR").unwrap()));
+        assert!(error.to_string().starts_with("segment 4 (BPR02) at byte"));
+
+        let missing_payee = input.replace("abc", "1.00") + "N1*PR*PAYER~CLP*C1*1*1*1~SE*4*0001~";
+        let error = parse(&missing_payee).unwrap_err();
+        assert_eq!(error.segment, 6);
+        assert_eq!(error.message, "missing payee identification loop");
+
+        assert!(parse("GS*HP~").is_err());
+        let truncated = input.replace("abc", "1.00");
+        assert!(parse(&truncated)
+            .unwrap_err()
+            .message
+            .contains("found end of input"));
+    }
+
+    #[test]
+    fn test_segment_reader_streams_like_tokenize() {
+        for delimiters in [
+            Delimiters::default(),
+            Delimiters {
+                element: '|',
+                segment: '\n',
+                line_ending: LineEnding::None,
+                ..Default::default()
+            },
+            Delimiters {
+                line_ending: LineEnding::CrLf,
+                ..Default::default()
+            },
+        ] {
+            let input = generated_interchange(Config {
+                delimiters,
+                anomaly_rate: 0.0,
+                ..Default::default()
+            });
+            let expected = tokenize(&input, &detect_delimiters(&input).unwrap());
+
+            let buffered = io::BufReader::with_capacity(7, input.as_bytes());
+            let reader = SegmentReader::new(buffered).unwrap();
+            assert_eq!(reader.delimiters().unwrap(), &delimiters);
+            let streamed: Vec<RawSegment> = reader.map(Result::unwrap).collect();
+
+            assert_eq!(streamed, expected);
+            let last = streamed.last().unwrap();
+            assert_eq!(last.id, "IEA");
+            assert_eq!(&input[last.offset..last.offset + 3], "IEA");
+            let lines = input[..last.offset].matches('\n').count() + 1;
+            assert_eq!(last.line, lines);
+        }
+
+        let reader = SegmentReader::new("GS*HP~ST*835~".as_bytes()).unwrap();
+        assert!(reader.delimiters().is_err());
+        assert_eq!(reader.count(), 2);
+    }
+}
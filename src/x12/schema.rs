@@ -0,0 +1,1341 @@
+//! The 005010X221A1 segment and element specifications used for validation.
+
+use DataType::*;
+
+/// A loop of the 835, plus the interchange envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LoopId {
+    /// ISA/GS/GE/IEA envelope segments.
+    Interchange,
+
+    /// Transaction header, ST through the segments before loop 1000A.
+    Header,
+
+    /// Loop 1000A payer identification.
+    Payer,
+
+    /// Loop 1000B payee identification.
+    Payee,
+
+    /// Loop 2000 header number.
+    HeaderNumber,
+
+    /// Loop 2100 claim payment information.
+    ClaimPayment,
+
+    /// Loop 2110 service payment information.
+    ServicePayment,
+
+    /// Summary, PLB and SE.
+    Summary,
+}
+
+impl LoopId {
+    /// The name used in findings, e.g. "loop 2100".
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoopId::Interchange => "interchange envelope",
+            LoopId::Header => "header",
+            LoopId::Payer => "loop 1000A",
+            LoopId::Payee => "loop 1000B",
+            LoopId::HeaderNumber => "loop 2000",
+            LoopId::ClaimPayment => "loop 2100",
+            LoopId::ServicePayment => "loop 2110",
+            LoopId::Summary => "summary",
+        }
+    }
+
+    /// The loop identifier used in IK303, e.g. "2100"; `None` outside a numbered loop.
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            LoopId::Payer => Some("1000A"),
+            LoopId::Payee => Some("1000B"),
+            LoopId::HeaderNumber => Some("2000"),
+            LoopId::ClaimPayment => Some("2100"),
+            LoopId::ServicePayment => Some("2110"),
+            LoopId::Interchange | LoopId::Header | LoopId::Summary => None,
+        }
+    }
+
+    /// How often the loop may repeat; `None` when unbounded.
+    pub fn max_repeat(&self) -> Option<usize> {
+        match self {
+            LoopId::ServicePayment => Some(999),
+            LoopId::HeaderNumber | LoopId::ClaimPayment => None,
+            _ => Some(1),
+        }
+    }
+}
+
+/// Whether a segment or element must be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    /// Always required.
+    Required,
+
+    /// Required only in some situations.
+    Situational,
+}
+
+/// X12 element data types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    /// Identifier drawn from a code list (ID).
+    Id,
+
+    /// String (AN).
+    Alphanumeric,
+
+    /// Decimal number (R).
+    Decimal,
+
+    /// Integer (N0).
+    Numeric,
+
+    /// Date (DT).
+    Date,
+
+    /// Time (TM).
+    Time,
+
+    /// Composite of components.
+    Composite,
+}
+
+/// The specification of one element or component.
+#[derive(Debug, Clone, Copy)]
+pub struct ElementSpec {
+    /// 1-based element position.
+    pub position: usize,
+
+    /// 1-based component position, or 0 for a simple element.
+    pub component: usize,
+
+    /// Element usage.
+    pub usage: Usage,
+
+    /// Element data type.
+    pub data_type: DataType,
+
+    /// Minimum length; 0 when not checked.
+    pub min: usize,
+
+    /// Maximum length; 0 when not checked.
+    pub max: usize,
+
+    /// Allowed codes; empty when any value is allowed.
+    pub codes: &'static [&'static str],
+}
+
+/// An X12 syntax rule between elements of a segment, by element position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxRule {
+    /// P: if either element is present, both are.
+    Paired(usize, usize),
+
+    /// C: if the first element is present, the second is too.
+    Conditional(usize, usize),
+
+    /// L: if the first element is present, at least one of the others is too.
+    ListConditional(usize, &'static [usize]),
+
+    /// E: at most one of the elements is present.
+    Exclusion(usize, usize),
+
+    /// R: at least one of the elements is present.
+    AtLeastOne(&'static [usize]),
+}
+
+/// The specification of a segment in one loop.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentSpec {
+    /// Segment identifier.
+    pub id: &'static str,
+
+    /// Segment name from the implementation guide.
+    pub name: &'static str,
+
+    /// Loop the segment belongs to.
+    pub loop_id: LoopId,
+
+    /// Position within the loop, used to check segment order.
+    pub position: u16,
+
+    /// Segment usage.
+    pub usage: Usage,
+
+    /// Maximum uses per loop; `None` when unbounded.
+    pub max_use: Option<usize>,
+
+    /// Element and component specifications.
+    pub elements: &'static [ElementSpec],
+
+    /// Syntax rules between the elements.
+    pub rules: &'static [SyntaxRule],
+}
+
+impl SegmentSpec {
+    /// The specification of the simple element at `position`.
+    pub fn element(&self, position: usize) -> Option<&ElementSpec> {
+        self.elements
+            .iter()
+            .find(|e| e.position == position && e.component == 0)
+    }
+
+    /// The component specifications of the composite element at `position`.
+    pub fn components(&self, position: usize) -> impl Iterator<Item = &ElementSpec> {
+        self.elements
+            .iter()
+            .filter(move |e| e.position == position && e.component > 0)
+    }
+
+    /// The highest element position in the specification.
+    pub fn max_position(&self) -> usize {
+        self.elements.iter().map(|e| e.position).max().unwrap_or(0)
+    }
+
+    /// The codes allowed in the first element when it is a qualifier.
+    pub fn qualifiers(&self) -> &'static [&'static str] {
+        match self.elements.first() {
+            Some(e) if e.position == 1 && e.data_type == DataType::Id => e.codes,
+            _ => NONE,
+        }
+    }
+}
+
+/// The specifications of segment `id` in `loop_id`; a segment may have several uses.
+pub fn segment_specs(loop_id: LoopId, id: &str) -> impl Iterator<Item = &'static SegmentSpec> + '_ {
+    SEGMENTS
+        .iter()
+        .filter(move |spec| spec.loop_id == loop_id && spec.id == id)
+}
+
+/// Every segment specification in `loop_id`.
+pub fn loop_specs(loop_id: LoopId) -> impl Iterator<Item = &'static SegmentSpec> {
+    SEGMENTS.iter().filter(move |spec| spec.loop_id == loop_id)
+}
+
+/// The specification of envelope segment `id`.
+pub fn envelope_spec(id: &str) -> Option<&'static SegmentSpec> {
+    segment_specs(LoopId::Interchange, id).next()
+}
+
+/// No code list.
+const NONE: &[&str] = &[];
+
+/// CLP02 claim status codes.
+const CLAIM_STATUS_CODES: &[&str] = &["1", "2", "3", "4", "19", "20", "21", "22", "23", "25"];
+
+/// CLP06 claim filing indicator codes.
+const CLAIM_FILING_INDICATORS: &[&str] = &[
+    "12", "13", "14", "15", "16", "17", "AM", "CH", "DS", "HM", "LM", "MA", "MB", "MC", "OF", "TV",
+    "VA", "WC", "ZZ",
+];
+
+/// Loop 2100 service provider identification code qualifiers.
+const PROVIDER_ID_QUALIFIERS: &[&str] = &["BD", "BS", "FI", "MC", "PC", "SL", "UP", "XX"];
+
+/// Loop 2100 other claim related identification qualifiers.
+const CLAIM_REFERENCE_QUALIFIERS: &[&str] = &[
+    "1K", "1L", "1W", "6P", "9A", "9C", "28", "BB", "CE", "EA", "F8", "G1", "G3", "IG", "SY",
+];
+
+/// Loop 2100 rendering provider identification qualifiers.
+const CLAIM_PROVIDER_QUALIFIERS: &[&str] = &[
+    "0B", "1A", "1B", "1C", "1D", "1G", "1H", "1J", "D3", "G2", "LU",
+];
+
+/// Loop 2100 claim supplemental amount qualifiers.
+const CLAIM_AMOUNT_QUALIFIERS: &[&str] = &[
+    "AU", "D8", "DY", "F5", "I", "NL", "T", "T2", "ZK", "ZL", "ZM", "ZN", "ZO",
+];
+
+/// Loop 2100 claim supplemental quantity qualifiers.
+const CLAIM_QUANTITY_QUALIFIERS: &[&str] = &[
+    "CA", "CD", "LA", "LE", "NE", "NR", "OU", "PS", "VS", "ZK", "ZL", "ZM", "ZN", "ZO",
+];
+
+/// SVC01 product or service ID qualifiers.
+const PRODUCT_QUALIFIERS: &[&str] = &["AD", "ER", "HC", "HP", "IV", "N4", "N6", "NU", "UI", "WK"];
+
+/// SVC06 product or service ID qualifiers.
+const ORIGINAL_PRODUCT_QUALIFIERS: &[&str] = &["AD", "ER", "HC", "HP", "IV", "N4", "NU", "WK"];
+
+/// Loop 2110 service identification qualifiers.
+const SERVICE_REFERENCE_QUALIFIERS: &[&str] = &["1S", "APC", "BB", "E9", "G1", "G3", "LU", "RB"];
+
+/// Loop 2110 rendering provider information qualifiers.
+const LINE_PROVIDER_QUALIFIERS: &[&str] = &[
+    "0B", "1A", "1B", "1C", "1D", "1G", "1H", "1J", "D3", "G2", "HPI", "SY", "TJ",
+];
+
+/// Loop 2110 service supplemental amount qualifiers.
+const SERVICE_AMOUNT_QUALIFIERS: &[&str] = &["B6", "KH", "T", "T2", "ZK", "ZL", "ZM", "ZN", "ZO"];
+
+/// PLB provider adjustment reason codes.
+const PROVIDER_ADJUSTMENT_REASONS: &[&str] = &[
+    "50", "51", "72", "90", "AH", "AM", "AP", "B2", "B3", "BD", "BN", "C5", "CR", "CS", "CT", "CV",
+    "CW", "DM", "E3", "FB", "FC", "GO", "HM", "IP", "IR", "IS", "J1", "L3", "L6", "LE", "LS", "OA",
+    "OB", "PI", "PL", "RA", "RE", "SL", "TL", "WO", "WU",
+];
+
+/// A required simple element.
+const fn req(
+    position: usize,
+    data_type: DataType,
+    min: usize,
+    max: usize,
+    codes: &'static [&'static str],
+) -> ElementSpec {
+    req_c(position, 0, data_type, min, max, codes)
+}
+
+/// A situational simple element.
+const fn sit(
+    position: usize,
+    data_type: DataType,
+    min: usize,
+    max: usize,
+    codes: &'static [&'static str],
+) -> ElementSpec {
+    sit_c(position, 0, data_type, min, max, codes)
+}
+
+/// A required element or component.
+const fn req_c(
+    position: usize,
+    component: usize,
+    data_type: DataType,
+    min: usize,
+    max: usize,
+    codes: &'static [&'static str],
+) -> ElementSpec {
+    ElementSpec {
+        position,
+        component,
+        usage: Usage::Required,
+        data_type,
+        min,
+        max,
+        codes,
+    }
+}
+
+/// A situational element or component.
+const fn sit_c(
+    position: usize,
+    component: usize,
+    data_type: DataType,
+    min: usize,
+    max: usize,
+    codes: &'static [&'static str],
+) -> ElementSpec {
+    ElementSpec {
+        usage: Usage::Situational,
+        ..req_c(position, component, data_type, min, max, codes)
+    }
+}
+
+/// Every segment specification of the 835 and its envelope.
+pub static SEGMENTS: &[SegmentSpec] = &[
+    SegmentSpec {
+        id: "ISA",
+        name: "Interchange Control Header",
+        loop_id: LoopId::Interchange,
+        position: 0,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["00"]),
+            req(2, Alphanumeric, 10, 10, NONE),
+            req(3, Id, 0, 0, &["00"]),
+            req(4, Alphanumeric, 10, 10, NONE),
+            req(5, Id, 2, 2, NONE),
+            req(6, Alphanumeric, 15, 15, NONE),
+            req(7, Id, 2, 2, NONE),
+            req(8, Alphanumeric, 15, 15, NONE),
+            req(9, Date, 6, 6, NONE),
+            req(10, Time, 4, 4, NONE),
+            req(11, Alphanumeric, 1, 1, NONE),
+            req(12, Id, 0, 0, &["00501"]),
+            req(13, Numeric, 9, 9, NONE),
+            req(14, Id, 1, 1, &["0", "1"]),
+            req(15, Id, 1, 1, &["I", "P", "T"]),
+            req(16, Alphanumeric, 1, 1, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "GS",
+        name: "Functional Group Header",
+        loop_id: LoopId::Interchange,
+        position: 0,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["HP"]),
+            req(2, Alphanumeric, 2, 15, NONE),
+            req(3, Alphanumeric, 2, 15, NONE),
+            req(4, Date, 8, 8, NONE),
+            req(5, Time, 4, 8, NONE),
+            req(6, Numeric, 1, 9, NONE),
+            req(7, Id, 1, 2, &["T", "X"]),
+            req(8, Alphanumeric, 0, 0, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "ST",
+        name: "Transaction Set Header",
+        loop_id: LoopId::Header,
+        position: 100,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["835"]),
+            req(2, Numeric, 4, 9, NONE),
+            req(3, Alphanumeric, 1, 35, &["005010X221A1"]),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "BPR",
+        name: "Financial Information",
+        loop_id: LoopId::Header,
+        position: 200,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["C", "D", "H", "I", "P", "U", "X"]),
+            req(2, Decimal, 1, 15, NONE),
+            req(3, Id, 0, 0, &["C", "D"]),
+            req(4, Id, 0, 0, &["ACH", "BOP", "CHK", "FWT", "NON"]),
+            sit(5, Id, 0, 0, &["CCP", "CTX"]),
+            sit(6, Id, 0, 0, &["01", "04"]),
+            sit(7, Alphanumeric, 3, 12, NONE),
+            sit(8, Id, 0, 0, &["DA"]),
+            sit(9, Alphanumeric, 1, 35, NONE),
+            sit(10, Alphanumeric, 10, 10, NONE),
+            sit(11, Alphanumeric, 9, 9, NONE),
+            sit(12, Id, 0, 0, &["01", "04"]),
+            sit(13, Alphanumeric, 3, 12, NONE),
+            sit(14, Id, 0, 0, &["DA", "SG"]),
+            sit(15, Alphanumeric, 1, 35, NONE),
+            req(16, Date, 8, 8, NONE),
+        ],
+        rules: &[
+            SyntaxRule::Paired(6, 7),
+            SyntaxRule::Conditional(8, 9),
+            SyntaxRule::Paired(12, 13),
+            SyntaxRule::Conditional(14, 15),
+        ],
+    },
+    SegmentSpec {
+        id: "TRN",
+        name: "Reassociation Trace Number",
+        loop_id: LoopId::Header,
+        position: 400,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["1"]),
+            req(2, Alphanumeric, 1, 50, NONE),
+            req(3, Alphanumeric, 10, 10, NONE),
+            sit(4, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "CUR",
+        name: "Foreign Currency Information",
+        loop_id: LoopId::Header,
+        position: 500,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["PR"]), req(2, Id, 3, 3, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Receiver Identification",
+        loop_id: LoopId::Header,
+        position: 600,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["EV"]), req(2, Alphanumeric, 1, 50, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Version Identification",
+        loop_id: LoopId::Header,
+        position: 600,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["F2"]), req(2, Alphanumeric, 1, 50, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "DTM",
+        name: "Production Date",
+        loop_id: LoopId::Header,
+        position: 700,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["405"]), req(2, Date, 8, 8, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "N1",
+        name: "Payer Identification",
+        loop_id: LoopId::Payer,
+        position: 800,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["PR"]),
+            req(2, Alphanumeric, 1, 60, NONE),
+            sit(3, Id, 0, 0, &["XV"]),
+            sit(4, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[SyntaxRule::Paired(3, 4)],
+    },
+    SegmentSpec {
+        id: "N3",
+        name: "Payer Address",
+        loop_id: LoopId::Payer,
+        position: 1000,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 1, 55, NONE),
+            sit(2, Alphanumeric, 1, 55, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "N4",
+        name: "Payer City, State, ZIP Code",
+        loop_id: LoopId::Payer,
+        position: 1100,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 2, 30, NONE),
+            sit(2, Id, 2, 2, NONE),
+            sit(3, Id, 3, 15, NONE),
+            sit(4, Id, 2, 3, NONE),
+            sit(7, Id, 1, 3, NONE),
+        ],
+        rules: &[SyntaxRule::Exclusion(2, 7), SyntaxRule::Conditional(7, 4)],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Additional Payer Identification",
+        loop_id: LoopId::Payer,
+        position: 1200,
+        usage: Usage::Situational,
+        max_use: Some(4),
+        elements: &[
+            req(1, Id, 0, 0, &["2U", "EO", "HI", "NF"]),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "PER",
+        name: "Payer Business Contact Information",
+        loop_id: LoopId::Payer,
+        position: 1300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["CX"]),
+            sit(2, Alphanumeric, 1, 60, NONE),
+            sit(3, Id, 0, 0, &["EM", "FX", "TE"]),
+            sit(4, Alphanumeric, 1, 256, NONE),
+            sit(5, Id, 0, 0, &["EM", "EX", "FX", "TE"]),
+            sit(6, Alphanumeric, 1, 256, NONE),
+            sit(7, Id, 0, 0, &["EX"]),
+            sit(8, Alphanumeric, 1, 256, NONE),
+        ],
+        rules: &[
+            SyntaxRule::Paired(3, 4),
+            SyntaxRule::Paired(5, 6),
+            SyntaxRule::Paired(7, 8),
+        ],
+    },
+    SegmentSpec {
+        id: "PER",
+        name: "Payer Technical Contact Information",
+        loop_id: LoopId::Payer,
+        position: 1300,
+        usage: Usage::Required,
+        max_use: None,
+        elements: &[
+            req(1, Id, 0, 0, &["BL"]),
+            sit(2, Alphanumeric, 1, 60, NONE),
+            sit(3, Id, 0, 0, &["EM", "TE", "UR"]),
+            sit(4, Alphanumeric, 1, 256, NONE),
+            sit(5, Id, 0, 0, &["EM", "EX", "FX", "TE", "UR"]),
+            sit(6, Alphanumeric, 1, 256, NONE),
+            sit(7, Id, 0, 0, &["EM", "EX", "FX", "UR"]),
+            sit(8, Alphanumeric, 1, 256, NONE),
+        ],
+        rules: &[
+            SyntaxRule::Paired(3, 4),
+            SyntaxRule::Paired(5, 6),
+            SyntaxRule::Paired(7, 8),
+        ],
+    },
+    SegmentSpec {
+        id: "PER",
+        name: "Payer WEB Site",
+        loop_id: LoopId::Payer,
+        position: 1300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["IC"]),
+            req(3, Id, 0, 0, &["UR"]),
+            req(4, Alphanumeric, 1, 256, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "N1",
+        name: "Payee Identification",
+        loop_id: LoopId::Payee,
+        position: 800,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["PE"]),
+            req(2, Alphanumeric, 1, 60, NONE),
+            req(3, Id, 0, 0, &["FI", "XV", "XX"]),
+            req(4, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "N3",
+        name: "Payee Address",
+        loop_id: LoopId::Payee,
+        position: 1000,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 1, 55, NONE),
+            sit(2, Alphanumeric, 1, 55, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "N4",
+        name: "Payee City, State, ZIP Code",
+        loop_id: LoopId::Payee,
+        position: 1100,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 2, 30, NONE),
+            sit(2, Id, 2, 2, NONE),
+            sit(3, Id, 3, 15, NONE),
+            sit(4, Id, 2, 3, NONE),
+            sit(7, Id, 1, 3, NONE),
+        ],
+        rules: &[SyntaxRule::Exclusion(2, 7), SyntaxRule::Conditional(7, 4)],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Payee Additional Identification",
+        loop_id: LoopId::Payee,
+        position: 1200,
+        usage: Usage::Situational,
+        max_use: None,
+        elements: &[
+            req(1, Id, 0, 0, &["0B", "D3", "PQ", "TJ"]),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "RDM",
+        name: "Remittance Delivery Method",
+        loop_id: LoopId::Payee,
+        position: 1400,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["BM", "EM", "FT", "OL"]),
+            sit(2, Alphanumeric, 1, 60, NONE),
+            sit(3, Alphanumeric, 1, 256, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "LX",
+        name: "Header Number",
+        loop_id: LoopId::HeaderNumber,
+        position: 30,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[req(1, Numeric, 1, 6, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "TS3",
+        name: "Provider Summary Information",
+        loop_id: LoopId::HeaderNumber,
+        position: 50,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 1, 50, NONE),
+            req(2, Alphanumeric, 1, 2, NONE),
+            req(3, Date, 8, 8, NONE),
+            req(4, Decimal, 1, 15, NONE),
+            req(5, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 15, NONE),
+            sit(15, Decimal, 1, 15, NONE),
+            sit(17, Decimal, 1, 15, NONE),
+            sit(18, Decimal, 1, 15, NONE),
+            sit(20, Decimal, 1, 15, NONE),
+            sit(21, Decimal, 1, 15, NONE),
+            sit(22, Decimal, 1, 15, NONE),
+            sit(23, Decimal, 1, 15, NONE),
+            sit(24, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "TS2",
+        name: "Provider Supplemental Summary Information",
+        loop_id: LoopId::HeaderNumber,
+        position: 70,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            sit(1, Decimal, 1, 15, NONE),
+            sit(2, Decimal, 1, 15, NONE),
+            sit(3, Decimal, 1, 15, NONE),
+            sit(4, Decimal, 1, 15, NONE),
+            sit(5, Decimal, 1, 15, NONE),
+            sit(6, Decimal, 1, 15, NONE),
+            sit(7, Decimal, 1, 15, NONE),
+            sit(8, Decimal, 1, 15, NONE),
+            sit(9, Decimal, 1, 15, NONE),
+            sit(10, Decimal, 1, 15, NONE),
+            sit(11, Decimal, 1, 15, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 15, NONE),
+            sit(14, Decimal, 1, 15, NONE),
+            sit(15, Decimal, 1, 15, NONE),
+            sit(16, Decimal, 1, 15, NONE),
+            sit(17, Decimal, 1, 15, NONE),
+            sit(18, Decimal, 1, 15, NONE),
+            sit(19, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "CLP",
+        name: "Claim Payment Information",
+        loop_id: LoopId::ClaimPayment,
+        position: 100,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Alphanumeric, 1, 38, NONE),
+            req(2, Id, 0, 0, CLAIM_STATUS_CODES),
+            req(3, Decimal, 1, 15, NONE),
+            req(4, Decimal, 1, 15, NONE),
+            sit(5, Decimal, 1, 15, NONE),
+            req(6, Id, 0, 0, CLAIM_FILING_INDICATORS),
+            req(7, Alphanumeric, 1, 50, NONE),
+            sit(8, Alphanumeric, 1, 2, NONE),
+            sit(9, Id, 1, 1, NONE),
+            sit(11, Id, 1, 4, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 10, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "CAS",
+        name: "Claim Adjustment",
+        loop_id: LoopId::ClaimPayment,
+        position: 200,
+        usage: Usage::Situational,
+        max_use: Some(99),
+        elements: &[
+            req(1, Id, 0, 0, &["CO", "OA", "PI", "PR"]),
+            req(2, Id, 1, 5, NONE),
+            req(3, Decimal, 1, 15, NONE),
+            sit(4, Decimal, 1, 15, NONE),
+            sit(5, Id, 1, 5, NONE),
+            sit(6, Decimal, 1, 15, NONE),
+            sit(7, Decimal, 1, 15, NONE),
+            sit(8, Id, 1, 5, NONE),
+            sit(9, Decimal, 1, 15, NONE),
+            sit(10, Decimal, 1, 15, NONE),
+            sit(11, Id, 1, 5, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 15, NONE),
+            sit(14, Id, 1, 5, NONE),
+            sit(15, Decimal, 1, 15, NONE),
+            sit(16, Decimal, 1, 15, NONE),
+            sit(17, Id, 1, 5, NONE),
+            sit(18, Decimal, 1, 15, NONE),
+            sit(19, Decimal, 1, 15, NONE),
+        ],
+        rules: &[
+            SyntaxRule::ListConditional(5, &[6, 7]),
+            SyntaxRule::Conditional(6, 5),
+            SyntaxRule::Conditional(7, 5),
+            SyntaxRule::ListConditional(8, &[9, 10]),
+            SyntaxRule::Conditional(9, 8),
+            SyntaxRule::Conditional(10, 8),
+            SyntaxRule::ListConditional(11, &[12, 13]),
+            SyntaxRule::Conditional(12, 11),
+            SyntaxRule::Conditional(13, 11),
+            SyntaxRule::ListConditional(14, &[15, 16]),
+            SyntaxRule::Conditional(15, 14),
+            SyntaxRule::Conditional(16, 14),
+            SyntaxRule::ListConditional(17, &[18, 19]),
+            SyntaxRule::Conditional(18, 17),
+            SyntaxRule::Conditional(19, 17),
+        ],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Patient Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["QC"]),
+            req(2, Id, 0, 0, &["1"]),
+            sit(3, Alphanumeric, 1, 60, NONE),
+            sit(4, Alphanumeric, 1, 35, NONE),
+            sit(5, Alphanumeric, 1, 25, NONE),
+            sit(7, Alphanumeric, 1, 10, NONE),
+            sit(8, Id, 0, 0, &["34", "HN", "II", "MI", "MR"]),
+            sit(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[SyntaxRule::Paired(8, 9)],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Insured Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["IL"]),
+            req(2, Id, 0, 0, &["1", "2"]),
+            sit(3, Alphanumeric, 1, 60, NONE),
+            sit(4, Alphanumeric, 1, 35, NONE),
+            sit(5, Alphanumeric, 1, 25, NONE),
+            sit(7, Alphanumeric, 1, 10, NONE),
+            req(8, Id, 0, 0, &["FI", "II", "MI"]),
+            req(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Corrected Patient/Insured Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["74"]),
+            req(2, Id, 0, 0, &["1", "2"]),
+            sit(3, Alphanumeric, 1, 60, NONE),
+            sit(4, Alphanumeric, 1, 35, NONE),
+            sit(5, Alphanumeric, 1, 25, NONE),
+            sit(7, Alphanumeric, 1, 10, NONE),
+            sit(8, Id, 0, 0, &["C"]),
+            sit(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[SyntaxRule::Paired(8, 9)],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Service Provider Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["82"]),
+            req(2, Id, 0, 0, &["1", "2"]),
+            sit(3, Alphanumeric, 1, 60, NONE),
+            sit(4, Alphanumeric, 1, 35, NONE),
+            sit(5, Alphanumeric, 1, 25, NONE),
+            sit(7, Alphanumeric, 1, 10, NONE),
+            req(8, Id, 0, 0, PROVIDER_ID_QUALIFIERS),
+            req(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Crossover Carrier Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["TT"]),
+            req(2, Id, 0, 0, &["2"]),
+            req(3, Alphanumeric, 1, 60, NONE),
+            req(8, Id, 0, 0, &["AD", "FI", "NI", "PI", "PP", "XV"]),
+            req(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Corrected Priority Payer Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["PR"]),
+            req(2, Id, 0, 0, &["2"]),
+            req(3, Alphanumeric, 1, 60, NONE),
+            req(8, Id, 0, 0, &["AD", "FI", "NI", "PI", "PP", "XV"]),
+            req(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "NM1",
+        name: "Other Subscriber Name",
+        loop_id: LoopId::ClaimPayment,
+        position: 300,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Id, 0, 0, &["GB"]),
+            req(2, Id, 0, 0, &["1", "2"]),
+            sit(3, Alphanumeric, 1, 60, NONE),
+            sit(4, Alphanumeric, 1, 35, NONE),
+            sit(5, Alphanumeric, 1, 25, NONE),
+            sit(7, Alphanumeric, 1, 10, NONE),
+            sit(8, Id, 0, 0, &["FI", "II", "MI"]),
+            sit(9, Alphanumeric, 2, 80, NONE),
+        ],
+        rules: &[SyntaxRule::Paired(8, 9), SyntaxRule::AtLeastOne(&[3, 9])],
+    },
+    SegmentSpec {
+        id: "MIA",
+        name: "Inpatient Adjudication Information",
+        loop_id: LoopId::ClaimPayment,
+        position: 330,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            req(1, Decimal, 1, 15, NONE),
+            sit(2, Decimal, 1, 15, NONE),
+            sit(3, Decimal, 1, 15, NONE),
+            sit(4, Decimal, 1, 15, NONE),
+            sit(5, Alphanumeric, 1, 50, NONE),
+            sit(6, Decimal, 1, 15, NONE),
+            sit(7, Decimal, 1, 15, NONE),
+            sit(8, Decimal, 1, 15, NONE),
+            sit(9, Decimal, 1, 15, NONE),
+            sit(10, Decimal, 1, 15, NONE),
+            sit(11, Decimal, 1, 15, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 15, NONE),
+            sit(14, Decimal, 1, 15, NONE),
+            sit(15, Decimal, 1, 15, NONE),
+            sit(16, Decimal, 1, 15, NONE),
+            sit(17, Decimal, 1, 15, NONE),
+            sit(18, Decimal, 1, 15, NONE),
+            sit(19, Decimal, 1, 15, NONE),
+            sit(20, Alphanumeric, 1, 50, NONE),
+            sit(21, Alphanumeric, 1, 50, NONE),
+            sit(22, Alphanumeric, 1, 50, NONE),
+            sit(23, Alphanumeric, 1, 50, NONE),
+            sit(24, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "MOA",
+        name: "Outpatient Adjudication Information",
+        loop_id: LoopId::ClaimPayment,
+        position: 350,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[
+            sit(1, Decimal, 1, 10, NONE),
+            sit(2, Decimal, 1, 15, NONE),
+            sit(3, Alphanumeric, 1, 50, NONE),
+            sit(4, Alphanumeric, 1, 50, NONE),
+            sit(5, Alphanumeric, 1, 50, NONE),
+            sit(6, Alphanumeric, 1, 50, NONE),
+            sit(7, Alphanumeric, 1, 50, NONE),
+            sit(8, Decimal, 1, 15, NONE),
+            sit(9, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Other Claim Related Identification",
+        loop_id: LoopId::ClaimPayment,
+        position: 400,
+        usage: Usage::Situational,
+        max_use: Some(5),
+        elements: &[
+            req(1, Id, 0, 0, CLAIM_REFERENCE_QUALIFIERS),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Rendering Provider Identification",
+        loop_id: LoopId::ClaimPayment,
+        position: 400,
+        usage: Usage::Situational,
+        max_use: Some(10),
+        elements: &[
+            req(1, Id, 0, 0, CLAIM_PROVIDER_QUALIFIERS),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "DTM",
+        name: "Statement From or To Date",
+        loop_id: LoopId::ClaimPayment,
+        position: 500,
+        usage: Usage::Situational,
+        max_use: Some(2),
+        elements: &[req(1, Id, 0, 0, &["232", "233"]), req(2, Date, 8, 8, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "DTM",
+        name: "Coverage Expiration Date",
+        loop_id: LoopId::ClaimPayment,
+        position: 500,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["036"]), req(2, Date, 8, 8, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "DTM",
+        name: "Claim Received Date",
+        loop_id: LoopId::ClaimPayment,
+        position: 500,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["050"]), req(2, Date, 8, 8, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "PER",
+        name: "Claim Contact Information",
+        loop_id: LoopId::ClaimPayment,
+        position: 600,
+        usage: Usage::Situational,
+        max_use: Some(2),
+        elements: &[
+            req(1, Id, 0, 0, &["CX"]),
+            sit(2, Alphanumeric, 1, 60, NONE),
+            req(3, Id, 0, 0, &["EM", "FX", "TE"]),
+            req(4, Alphanumeric, 1, 256, NONE),
+            sit(5, Id, 0, 0, &["EM", "EX", "FX", "TE"]),
+            sit(6, Alphanumeric, 1, 256, NONE),
+            sit(7, Id, 0, 0, &["EX"]),
+            sit(8, Alphanumeric, 1, 256, NONE),
+        ],
+        rules: &[SyntaxRule::Paired(5, 6), SyntaxRule::Paired(7, 8)],
+    },
+    SegmentSpec {
+        id: "AMT",
+        name: "Claim Supplemental Information",
+        loop_id: LoopId::ClaimPayment,
+        position: 620,
+        usage: Usage::Situational,
+        max_use: Some(13),
+        elements: &[
+            req(1, Id, 0, 0, CLAIM_AMOUNT_QUALIFIERS),
+            req(2, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "QTY",
+        name: "Claim Supplemental Information Quantity",
+        loop_id: LoopId::ClaimPayment,
+        position: 640,
+        usage: Usage::Situational,
+        max_use: Some(14),
+        elements: &[
+            req(1, Id, 0, 0, CLAIM_QUANTITY_QUALIFIERS),
+            req(2, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "SVC",
+        name: "Service Payment Information",
+        loop_id: LoopId::ServicePayment,
+        position: 700,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[
+            req(1, Composite, 0, 0, NONE),
+            req_c(1, 1, Id, 0, 0, PRODUCT_QUALIFIERS),
+            req_c(1, 2, Alphanumeric, 1, 48, NONE),
+            sit_c(1, 3, Alphanumeric, 2, 2, NONE),
+            sit_c(1, 4, Alphanumeric, 2, 2, NONE),
+            sit_c(1, 5, Alphanumeric, 2, 2, NONE),
+            sit_c(1, 6, Alphanumeric, 2, 2, NONE),
+            req(2, Decimal, 1, 15, NONE),
+            req(3, Decimal, 1, 15, NONE),
+            sit(4, Alphanumeric, 1, 48, NONE),
+            sit(5, Decimal, 1, 15, NONE),
+            sit(6, Composite, 0, 0, NONE),
+            req_c(6, 1, Id, 0, 0, ORIGINAL_PRODUCT_QUALIFIERS),
+            req_c(6, 2, Alphanumeric, 1, 48, NONE),
+            sit_c(6, 3, Alphanumeric, 2, 2, NONE),
+            sit_c(6, 4, Alphanumeric, 2, 2, NONE),
+            sit_c(6, 5, Alphanumeric, 2, 2, NONE),
+            sit_c(6, 6, Alphanumeric, 2, 2, NONE),
+            sit_c(6, 7, Alphanumeric, 1, 80, NONE),
+            sit(7, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "DTM",
+        name: "Service Date",
+        loop_id: LoopId::ServicePayment,
+        position: 800,
+        usage: Usage::Situational,
+        max_use: Some(2),
+        elements: &[
+            req(1, Id, 0, 0, &["150", "151", "472"]),
+            req(2, Date, 8, 8, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "CAS",
+        name: "Service Adjustment",
+        loop_id: LoopId::ServicePayment,
+        position: 900,
+        usage: Usage::Situational,
+        max_use: Some(99),
+        elements: &[
+            req(1, Id, 0, 0, &["CO", "OA", "PI", "PR"]),
+            req(2, Id, 1, 5, NONE),
+            req(3, Decimal, 1, 15, NONE),
+            sit(4, Decimal, 1, 15, NONE),
+            sit(5, Id, 1, 5, NONE),
+            sit(6, Decimal, 1, 15, NONE),
+            sit(7, Decimal, 1, 15, NONE),
+            sit(8, Id, 1, 5, NONE),
+            sit(9, Decimal, 1, 15, NONE),
+            sit(10, Decimal, 1, 15, NONE),
+            sit(11, Id, 1, 5, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Decimal, 1, 15, NONE),
+            sit(14, Id, 1, 5, NONE),
+            sit(15, Decimal, 1, 15, NONE),
+            sit(16, Decimal, 1, 15, NONE),
+            sit(17, Id, 1, 5, NONE),
+            sit(18, Decimal, 1, 15, NONE),
+            sit(19, Decimal, 1, 15, NONE),
+        ],
+        rules: &[
+            SyntaxRule::ListConditional(5, &[6, 7]),
+            SyntaxRule::Conditional(6, 5),
+            SyntaxRule::Conditional(7, 5),
+            SyntaxRule::ListConditional(8, &[9, 10]),
+            SyntaxRule::Conditional(9, 8),
+            SyntaxRule::Conditional(10, 8),
+            SyntaxRule::ListConditional(11, &[12, 13]),
+            SyntaxRule::Conditional(12, 11),
+            SyntaxRule::Conditional(13, 11),
+            SyntaxRule::ListConditional(14, &[15, 16]),
+            SyntaxRule::Conditional(15, 14),
+            SyntaxRule::Conditional(16, 14),
+            SyntaxRule::ListConditional(17, &[18, 19]),
+            SyntaxRule::Conditional(18, 17),
+            SyntaxRule::Conditional(19, 17),
+        ],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Service Identification",
+        loop_id: LoopId::ServicePayment,
+        position: 1000,
+        usage: Usage::Situational,
+        max_use: Some(8),
+        elements: &[
+            req(1, Id, 0, 0, SERVICE_REFERENCE_QUALIFIERS),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Line Item Control Number",
+        loop_id: LoopId::ServicePayment,
+        position: 1000,
+        usage: Usage::Situational,
+        max_use: Some(1),
+        elements: &[req(1, Id, 0, 0, &["6R"]), req(2, Alphanumeric, 1, 50, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "Rendering Provider Information",
+        loop_id: LoopId::ServicePayment,
+        position: 1000,
+        usage: Usage::Situational,
+        max_use: Some(10),
+        elements: &[
+            req(1, Id, 0, 0, LINE_PROVIDER_QUALIFIERS),
+            req(2, Alphanumeric, 1, 50, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "REF",
+        name: "HealthCare Policy Identification",
+        loop_id: LoopId::ServicePayment,
+        position: 1000,
+        usage: Usage::Situational,
+        max_use: Some(5),
+        elements: &[req(1, Id, 0, 0, &["0K"]), req(2, Alphanumeric, 1, 50, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "AMT",
+        name: "Service Supplemental Amount",
+        loop_id: LoopId::ServicePayment,
+        position: 1100,
+        usage: Usage::Situational,
+        max_use: Some(9),
+        elements: &[
+            req(1, Id, 0, 0, SERVICE_AMOUNT_QUALIFIERS),
+            req(2, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "QTY",
+        name: "Service Supplemental Quantity",
+        loop_id: LoopId::ServicePayment,
+        position: 1200,
+        usage: Usage::Situational,
+        max_use: Some(6),
+        elements: &[
+            req(1, Id, 0, 0, &["ZK", "ZL", "ZM", "ZN", "ZO"]),
+            req(2, Decimal, 1, 15, NONE),
+        ],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "LQ",
+        name: "Health Care Remark Codes",
+        loop_id: LoopId::ServicePayment,
+        position: 1300,
+        usage: Usage::Situational,
+        max_use: Some(99),
+        elements: &[
+            req(1, Id, 0, 0, &["HE", "RX"]),
+            req(2, Alphanumeric, 1, 30, NONE),
+        ],
+        rules: &[SyntaxRule::Conditional(1, 2)],
+    },
+    SegmentSpec {
+        id: "PLB",
+        name: "Provider Adjustment",
+        loop_id: LoopId::Summary,
+        position: 100,
+        usage: Usage::Situational,
+        max_use: None,
+        elements: &[
+            req(1, Alphanumeric, 1, 50, NONE),
+            req(2, Date, 8, 8, NONE),
+            req(3, Composite, 0, 0, NONE),
+            req_c(3, 1, Id, 0, 0, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(3, 2, Alphanumeric, 1, 50, NONE),
+            req(4, Decimal, 1, 15, NONE),
+            sit(5, Composite, 0, 0, NONE),
+            req_c(5, 1, Id, 2, 2, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(5, 2, Alphanumeric, 1, 50, NONE),
+            sit(6, Decimal, 1, 15, NONE),
+            sit(7, Composite, 0, 0, NONE),
+            req_c(7, 1, Id, 2, 2, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(7, 2, Alphanumeric, 1, 50, NONE),
+            sit(8, Decimal, 1, 15, NONE),
+            sit(9, Composite, 0, 0, NONE),
+            req_c(9, 1, Id, 2, 2, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(9, 2, Alphanumeric, 1, 50, NONE),
+            sit(10, Decimal, 1, 15, NONE),
+            sit(11, Composite, 0, 0, NONE),
+            req_c(11, 1, Id, 2, 2, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(11, 2, Alphanumeric, 1, 50, NONE),
+            sit(12, Decimal, 1, 15, NONE),
+            sit(13, Composite, 0, 0, NONE),
+            req_c(13, 1, Id, 2, 2, PROVIDER_ADJUSTMENT_REASONS),
+            sit_c(13, 2, Alphanumeric, 1, 50, NONE),
+            sit(14, Decimal, 1, 15, NONE),
+        ],
+        rules: &[
+            SyntaxRule::Paired(5, 6),
+            SyntaxRule::Paired(7, 8),
+            SyntaxRule::Paired(9, 10),
+            SyntaxRule::Paired(11, 12),
+            SyntaxRule::Paired(13, 14),
+        ],
+    },
+    SegmentSpec {
+        id: "SE",
+        name: "Transaction Set Trailer",
+        loop_id: LoopId::Summary,
+        position: 200,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[req(1, Numeric, 1, 10, NONE), req(2, Numeric, 4, 9, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "GE",
+        name: "Functional Group Trailer",
+        loop_id: LoopId::Interchange,
+        position: 0,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[req(1, Numeric, 1, 6, NONE), req(2, Numeric, 1, 9, NONE)],
+        rules: &[],
+    },
+    SegmentSpec {
+        id: "IEA",
+        name: "Interchange Control Trailer",
+        loop_id: LoopId::Interchange,
+        position: 0,
+        usage: Usage::Required,
+        max_use: Some(1),
+        elements: &[req(1, Numeric, 1, 5, NONE), req(2, Numeric, 9, 9, NONE)],
+        rules: &[],
+    },
+];
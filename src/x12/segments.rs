@@ -1,287 +1,1307 @@
+//! Typed X12 835 segments.
 
-
+use super::delimiters::{Delimiters, Element};
+use crate::money::Money;
 use std::fmt;
 
+/// A typed X12 segment that can be written with any set of delimiters.
+pub trait X12Segment: fmt::Display {
+    /// The segment identifier, e.g. `CLP`.
+    fn id(&self) -> &str;
+
+    /// The segment's elements in position order, without the identifier.
+    fn elements(&self) -> Vec<Element>;
+
+    /// Writes the segment, including its terminator, using `delimiters`.
+    fn write_segment(&self, out: &mut dyn fmt::Write, delimiters: &Delimiters) -> fmt::Result {
+        delimiters.write_segment(out, self.id(), &self.elements())
+    }
+}
+
+/// Implements `Display` with the default delimiters.
+macro_rules! display_with_default_delimiters {
+    ($segment:ty) => {
+        impl fmt::Display for $segment {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.write_segment(f, &Delimiters::default())
+            }
+        }
+    };
+}
+
+/// An amount element.
+fn amount(value: Money) -> Element {
+    value.to_string().into()
+}
 
-pub trait X12Segment: fmt::Display {}
+/// An optional amount element.
+fn optional_amount(value: Option<Money>) -> Element {
+    value.map(amount).unwrap_or_default()
+}
 
+/// An optional element.
+fn optional_value<T: ToString>(value: Option<T>) -> Element {
+    value.map(|v| v.to_string().into()).unwrap_or_default()
+}
 
+/// ISA interchange control header.
 #[derive(Debug, Clone)]
 pub struct IsaSegment {
+    /// ISA06 interchange sender ID.
     pub isa06_sender_id: String,      
+    /// ISA08 interchange receiver ID.
     pub isa08_receiver_id: String,    
+    /// ISA09 interchange date (YYMMDD).
+    pub isa09_date: String,
+    /// ISA10 interchange time (HHMM).
+    pub isa10_time: String,
+    /// ISA13 interchange control number.
     pub isa12_control_number: String, 
+    /// ISA15 usage indicator (`P` production, `T` test).
     pub isa13_usage_indicator: char,  
 }
 
-impl fmt::Display for IsaSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "ISA*00*          *00*          *ZZ*{:15}*ZZ*{:15}*{}*{}*^*00501*{}*0*{}*~",
-            self.isa06_sender_id,
-            self.isa08_receiver_id,
-            chrono::Local::now().format("%y%m%d"),
-            chrono::Local::now().format("%H%M"),
-            self.isa12_control_number,
-            self.isa13_usage_indicator
-        )
+impl IsaSegment {
+    fn elements_with(&self, delimiters: &Delimiters) -> Vec<Element> {
+        vec![
+            "00".into(),
+            format!("{:10}", "").into(),
+            "00".into(),
+            format!("{:10}", "").into(),
+            "ZZ".into(),
+            format!("{:15}", self.isa06_sender_id).into(),
+            "ZZ".into(),
+            format!("{:15}", self.isa08_receiver_id).into(),
+            (&self.isa09_date).into(),
+            (&self.isa10_time).into(),
+            delimiters.repetition.to_string().into(),
+            "00501".into(),
+            (&self.isa12_control_number).into(),
+            "0".into(),
+            self.isa13_usage_indicator.to_string().into(),
+            delimiters.component.to_string().into(),
+        ]
     }
 }
 
-impl X12Segment for IsaSegment {}
+impl X12Segment for IsaSegment {
+    fn id(&self) -> &str {
+        "ISA"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        self.elements_with(&Delimiters::default())
+    }
+
+    fn write_segment(&self, out: &mut dyn fmt::Write, delimiters: &Delimiters) -> fmt::Result {
+        delimiters.write_segment(out, self.id(), &self.elements_with(delimiters))
+    }
+}
 
+display_with_default_delimiters!(IsaSegment);
 
+/// GS functional group header.
 #[derive(Debug, Clone)]
 pub struct GsSegment {
+    /// GS01 functional identifier code (`HP` for 835s).
+    pub gs01_functional_identifier_code: String,
+    /// GS02 application sender's code.
     pub gs02_sender_id: String,            
+    /// GS03 application receiver's code.
     pub gs03_receiver_id: String,          
+    /// GS04 group date (CCYYMMDD).
+    pub gs04_date: String,
+    /// GS05 group time (HHMM).
+    pub gs05_time: String,
+    /// GS06 group control number.
     pub gs06_group_control_number: String, 
+    /// GS08 version, release and industry identifier code.
+    pub gs08_version: String,
 }
 
-impl fmt::Display for GsSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "GS*HP*{}*{}*{}*{}*{}*X*005010X221A1~",
-            self.gs02_sender_id,
-            self.gs03_receiver_id,
-            chrono::Local::now().format("%Y%m%d"),
-            chrono::Local::now().format("%H%M"),
-            self.gs06_group_control_number
-        )
+impl X12Segment for GsSegment {
+    fn id(&self) -> &str {
+        "GS"
     }
-}
 
-impl X12Segment for GsSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.gs01_functional_identifier_code).into(),
+            (&self.gs02_sender_id).into(),
+            (&self.gs03_receiver_id).into(),
+            (&self.gs04_date).into(),
+            (&self.gs05_time).into(),
+            (&self.gs06_group_control_number).into(),
+            "X".into(),
+            (&self.gs08_version).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(GsSegment);
 
+/// ST transaction set header.
 #[derive(Debug, Clone)]
 pub struct StSegment {
+    /// ST01 transaction set identifier code, e.g. `835`.
+    pub st01_transaction_set_id: String,
+    /// ST02 transaction set control number.
     pub st02_control_number: String, 
+    /// ST03 implementation convention reference.
+    pub st03_implementation_reference: String,
 }
 
-impl fmt::Display for StSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "ST*835*0001*005010X221A1~")
+impl X12Segment for StSegment {
+    fn id(&self) -> &str {
+        "ST"
     }
-}
 
-impl X12Segment for StSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.st01_transaction_set_id).into(),
+            (&self.st02_control_number).into(),
+            (&self.st03_implementation_reference).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(StSegment);
 
+/// BPR financial information.
 #[derive(Debug, Clone)]
 pub struct BprSegment {
-    pub bpr02_payment_amount: f64,
+    /// BPR02 total actual provider payment amount.
+    pub bpr02_payment_amount: Money,
+    /// BPR03 credit/debit flag.
     pub bpr03_credit_debit: char,     
+    /// BPR04 payment method code.
     pub bpr04_payment_method: String, 
+    /// BPR10 payer identifier (originating company ID).
+    pub bpr10_originating_company_id: String,
+    /// BPR16 check issue or EFT effective date.
     pub bpr16_payment_date: String,   
 }
 
-impl fmt::Display for BprSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "BPR*C*{:.2}*C*ACH*CC*01*999999999*DA*999999999*{}*{}*01*999999999*DA*999999999*{}*{}~",
-            self.bpr02_payment_amount,
-            self.bpr04_payment_method,
-            "9999999999",
-            self.bpr16_payment_date,
-            chrono::Local::now().format("%Y%m%d")
-        )
+impl X12Segment for BprSegment {
+    fn id(&self) -> &str {
+        "BPR"
     }
-}
 
-impl X12Segment for BprSegment {}
+    fn elements(&self) -> Vec<Element> {
+        
+        let method = self.bpr04_payment_method.as_str();
+        let transfer = matches!(method, "ACH" | "BOP" | "FWT");
+        let funds = |value: &str| {
+            if transfer {
+                value.into()
+            } else {
+                Element::default()
+            }
+        };
+        vec![
+            if method == "NON" { "H" } else { "C" }.into(),
+            amount(self.bpr02_payment_amount),
+            self.bpr03_credit_debit.to_string().into(),
+            method.into(),
+            if method == "ACH" {
+                "CCP".into()
+            } else {
+                Element::default()
+            },
+            funds("01"),
+            funds("999999999"),
+            funds("DA"),
+            funds("999999999"),
+            funds(&self.bpr10_originating_company_id),
+            Element::default(),
+            funds("01"),
+            funds("999999999"),
+            funds("DA"),
+            funds("999999999"),
+            (&self.bpr16_payment_date).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(BprSegment);
 
+/// TRN reassociation trace number.
 #[derive(Debug, Clone)]
 pub struct TrnSegment {
+    /// TRN02 check or EFT trace number.
     pub trn02_reference_id: String,
+    /// TRN03 payer identifier.
     pub trn03_orig_company_id: String,
 }
 
-impl fmt::Display for TrnSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "TRN*1*{}*{}~",
-            self.trn02_reference_id, self.trn03_orig_company_id
-        )
+impl X12Segment for TrnSegment {
+    fn id(&self) -> &str {
+        "TRN"
     }
-}
 
-impl X12Segment for TrnSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            "1".into(),
+            (&self.trn02_reference_id).into(),
+            (&self.trn03_orig_company_id).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(TrnSegment);
 
+/// DTM date/time reference.
 #[derive(Debug, Clone)]
 pub struct DtmSegment {
+    /// DTM01 date/time qualifier.
     pub dtm01_qualifier: String, 
+    /// DTM02 date (CCYYMMDD).
     pub dtm02_date: String,      
 }
 
-impl fmt::Display for DtmSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "DTM*{}*{}~", self.dtm01_qualifier, self.dtm02_date)
+impl X12Segment for DtmSegment {
+    fn id(&self) -> &str {
+        "DTM"
     }
-}
 
-impl X12Segment for DtmSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![(&self.dtm01_qualifier).into(), (&self.dtm02_date).into()]
+    }
+}
 
+display_with_default_delimiters!(DtmSegment);
 
+/// N1 party identification.
 #[derive(Debug, Clone)]
 pub struct N1Segment {
+    /// N101 entity identifier code (`PR` payer, `PE` payee).
     pub n101_entity_id: String, 
+    /// N102 name.
     pub n102_name: String,
+    /// N103 identification code qualifier.
     pub n103_id_qual: String, 
+    /// N104 identification code.
     pub n104_id: String,
 }
 
-impl fmt::Display for N1Segment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "N1*{}*{}*{}*{}~",
-            self.n101_entity_id, self.n102_name, self.n103_id_qual, self.n104_id
-        )
+impl X12Segment for N1Segment {
+    fn id(&self) -> &str {
+        "N1"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.n101_entity_id).into(),
+            (&self.n102_name).into(),
+            (&self.n103_id_qual).into(),
+            (&self.n104_id).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(N1Segment);
+
+/// N3 party address.
+#[derive(Debug, Clone)]
+pub struct N3Segment {
+    /// N301 address line.
+    pub n301_address_line1: String,
+    /// N302 second address line.
+    pub n302_address_line2: Option<String>,
+}
+
+impl X12Segment for N3Segment {
+    fn id(&self) -> &str {
+        "N3"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.n301_address_line1).into(),
+            self.n302_address_line2.as_deref().into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(N3Segment);
+
+/// N4 party city, state and ZIP code.
+#[derive(Debug, Clone)]
+pub struct N4Segment {
+    /// N401 city name.
+    pub n401_city: String,
+    /// N402 state or province code.
+    pub n402_state: String,
+    /// N403 postal code.
+    pub n403_postal_code: String,
+}
+
+impl X12Segment for N4Segment {
+    fn id(&self) -> &str {
+        "N4"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.n401_city).into(),
+            (&self.n402_state).into(),
+            (&self.n403_postal_code).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(N4Segment);
+
+/// REF reference identification.
+#[derive(Debug, Clone)]
+pub struct RefSegment {
+    /// REF01 reference identification qualifier.
+    pub ref01_qualifier: String,
+    /// REF02 reference identification.
+    pub ref02_identifier: String,
+}
+
+impl X12Segment for RefSegment {
+    fn id(&self) -> &str {
+        "REF"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.ref01_qualifier).into(),
+            (&self.ref02_identifier).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(RefSegment);
+
+/// PER contact information.
+#[derive(Debug, Clone)]
+pub struct PerSegment {
+    /// PER01 contact function code.
+    pub per01_function_code: String,
+    /// PER02 contact name.
+    pub per02_name: Option<String>,
+    /// PER03-PER08 communication number qualifier and number pairs.
+    pub communications: Vec<(String, String)>,
+}
+
+impl X12Segment for PerSegment {
+    fn id(&self) -> &str {
+        "PER"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        let mut elements = vec![
+            (&self.per01_function_code).into(),
+            self.per02_name.as_deref().into(),
+        ];
+        for (qualifier, number) in self.communications.iter().take(3) {
+            elements.push(qualifier.into());
+            elements.push(number.into());
+        }
+        elements
+    }
+}
+
+display_with_default_delimiters!(PerSegment);
+
+/// RDM remittance delivery method.
+#[derive(Debug, Clone)]
+pub struct RdmSegment {
+    /// RDM01 report transmission code.
+    pub rdm01_transmission_code: String,
+    /// RDM02 name.
+    pub rdm02_name: Option<String>,
+    /// RDM03 communication number.
+    pub rdm03_communication_number: Option<String>,
+}
+
+impl X12Segment for RdmSegment {
+    fn id(&self) -> &str {
+        "RDM"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.rdm01_transmission_code).into(),
+            self.rdm02_name.as_deref().into(),
+            self.rdm03_communication_number.as_deref().into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(RdmSegment);
+
+/// LX header number.
+#[derive(Debug, Clone)]
+pub struct LxSegment {
+    /// LX01 assigned number.
+    pub lx01_assigned_number: u32,
+}
+
+impl X12Segment for LxSegment {
+    fn id(&self) -> &str {
+        "LX"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![self.lx01_assigned_number.to_string().into()]
+    }
+}
+
+display_with_default_delimiters!(LxSegment);
+
+/// TS3 provider summary information.
+#[derive(Debug, Clone, Default)]
+pub struct Ts3Segment {
+    /// TS301 provider identifier.
+    pub ts301_provider_id: String,
+    /// TS302 facility type code.
+    pub ts302_facility_type: String,
+    /// TS303 last day of the provider's fiscal year (CCYYMMDD).
+    pub ts303_fiscal_period_date: String,
+    /// TS304 total claim count.
+    pub ts304_claim_count: u32,
+    /// TS305 total claim charge amount.
+    pub ts305_total_charge: Money,
+    /// TS313 total MSP payer amount.
+    pub ts313_msp_primary_payer: Option<Money>,
+    /// TS315 total non-lab charge amount.
+    pub ts315_non_lab_charges: Option<Money>,
+    /// TS317 total HCPCS reported charge amount.
+    pub ts317_hcpcs_reported_charges: Option<Money>,
+    /// TS318 total HCPCS payable amount.
+    pub ts318_hcpcs_payable: Option<Money>,
+    /// TS320 total professional component amount.
+    pub ts320_professional_component: Option<Money>,
+    /// TS321 total MSP patient liability met amount.
+    pub ts321_msp_patient_liability_met: Option<Money>,
+    /// TS322 total patient reimbursement amount.
+    pub ts322_patient_reimbursement: Option<Money>,
+    /// TS323 total periodic interim payment claim count.
+    pub ts323_pip_claim_count: Option<u32>,
+    /// TS324 total periodic interim payment adjustment amount.
+    pub ts324_pip_adjustment: Option<Money>,
+}
+
+impl X12Segment for Ts3Segment {
+    fn id(&self) -> &str {
+        "TS3"
     }
+
+    fn elements(&self) -> Vec<Element> {
+        let mut elements = vec![
+            (&self.ts301_provider_id).into(),
+            (&self.ts302_facility_type).into(),
+            (&self.ts303_fiscal_period_date).into(),
+            self.ts304_claim_count.to_string().into(),
+            amount(self.ts305_total_charge),
+        ];
+        elements.resize(12, Element::default());
+        elements.extend([
+            optional_amount(self.ts313_msp_primary_payer),
+            Element::default(),
+            optional_amount(self.ts315_non_lab_charges),
+            Element::default(),
+            optional_amount(self.ts317_hcpcs_reported_charges),
+            optional_amount(self.ts318_hcpcs_payable),
+            Element::default(),
+            optional_amount(self.ts320_professional_component),
+            optional_amount(self.ts321_msp_patient_liability_met),
+            optional_amount(self.ts322_patient_reimbursement),
+            optional_value(self.ts323_pip_claim_count),
+            optional_amount(self.ts324_pip_adjustment),
+        ]);
+        elements
+    }
+}
+
+display_with_default_delimiters!(Ts3Segment);
+
+/// TS2 provider supplemental summary information.
+#[derive(Debug, Clone, Default)]
+pub struct Ts2Segment {
+    /// TS201 total DRG amount.
+    pub ts201_drg_amount: Option<Money>,
+    /// TS202 total federal specific amount.
+    pub ts202_federal_specific_amount: Option<Money>,
+    /// TS203 total hospital specific amount.
+    pub ts203_hospital_specific_amount: Option<Money>,
+    /// TS204 total disproportionate share amount.
+    pub ts204_disproportionate_share_amount: Option<Money>,
+    /// TS205 total capital amount.
+    pub ts205_capital_amount: Option<Money>,
+    /// TS206 total indirect medical education amount.
+    pub ts206_indirect_medical_education_amount: Option<Money>,
+    /// TS207 total outlier day count.
+    pub ts207_outlier_days: Option<u32>,
+    /// TS208 total day outlier amount.
+    pub ts208_day_outlier_amount: Option<Money>,
+    /// TS209 total cost outlier amount.
+    pub ts209_cost_outlier_amount: Option<Money>,
+    /// TS210 average DRG length of stay.
+    pub ts210_average_length_of_stay: Option<f64>,
+    /// TS211 total discharge count.
+    pub ts211_discharges: Option<u32>,
+    /// TS212 total cost report day count.
+    pub ts212_cost_report_days: Option<u32>,
+    /// TS213 total covered day count.
+    pub ts213_covered_days: Option<u32>,
+    /// TS214 total non-covered day count.
+    pub ts214_noncovered_days: Option<u32>,
+    /// TS215 total MSP pass-through amount.
+    pub ts215_msp_pass_through_amount: Option<Money>,
+    /// TS216 average DRG weight.
+    pub ts216_average_drg_weight: Option<f64>,
+    /// TS217 total PPS capital FSP DRG amount.
+    pub ts217_pps_capital_fsp_drg_amount: Option<Money>,
+    /// TS218 total PPS capital HSP DRG amount.
+    pub ts218_pps_capital_hsp_drg_amount: Option<Money>,
+    /// TS219 total PPS DSH DRG amount.
+    pub ts219_pps_dsh_drg_amount: Option<Money>,
 }
 
-impl X12Segment for N1Segment {}
+impl X12Segment for Ts2Segment {
+    fn id(&self) -> &str {
+        "TS2"
+    }
 
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            optional_amount(self.ts201_drg_amount),
+            optional_amount(self.ts202_federal_specific_amount),
+            optional_amount(self.ts203_hospital_specific_amount),
+            optional_amount(self.ts204_disproportionate_share_amount),
+            optional_amount(self.ts205_capital_amount),
+            optional_amount(self.ts206_indirect_medical_education_amount),
+            optional_value(self.ts207_outlier_days),
+            optional_amount(self.ts208_day_outlier_amount),
+            optional_amount(self.ts209_cost_outlier_amount),
+            optional_value(self.ts210_average_length_of_stay),
+            optional_value(self.ts211_discharges),
+            optional_value(self.ts212_cost_report_days),
+            optional_value(self.ts213_covered_days),
+            optional_value(self.ts214_noncovered_days),
+            optional_amount(self.ts215_msp_pass_through_amount),
+            optional_value(self.ts216_average_drg_weight),
+            optional_amount(self.ts217_pps_capital_fsp_drg_amount),
+            optional_amount(self.ts218_pps_capital_hsp_drg_amount),
+            optional_amount(self.ts219_pps_dsh_drg_amount),
+        ]
+    }
+}
 
+display_with_default_delimiters!(Ts2Segment);
+
+/// CLP claim payment information.
 #[derive(Debug, Clone)]
 pub struct ClpSegment {
+    /// CLP01 patient control number.
     pub clp01_claim_id: String,
+    /// CLP02 claim status code.
     pub clp02_claim_status: String, 
-    pub clp03_charge_amount: f64,
-    pub clp04_paid_amount: f64,
-    pub clp05_patient_responsibility: f64,
+    /// CLP03 total claim charge amount.
+    pub clp03_charge_amount: Money,
+    /// CLP04 claim payment amount.
+    pub clp04_paid_amount: Money,
+    /// CLP05 patient responsibility amount.
+    pub clp05_patient_responsibility: Money,
+    /// CLP06 claim filing indicator code.
     pub clp06_claim_type: String, 
+    /// CLP07 payer claim control number.
     pub clp07_payer_claim_number: String,
+    /// CLP08 facility type code.
+    pub clp08_facility_type: String,
+    /// CLP09 claim frequency type code.
+    pub clp09_frequency_code: String,
+}
+
+impl X12Segment for ClpSegment {
+    fn id(&self) -> &str {
+        "CLP"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.clp01_claim_id).into(),
+            (&self.clp02_claim_status).into(),
+            amount(self.clp03_charge_amount),
+            amount(self.clp04_paid_amount),
+            amount(self.clp05_patient_responsibility),
+            (&self.clp06_claim_type).into(),
+            (&self.clp07_payer_claim_number).into(),
+            (&self.clp08_facility_type).into(),
+            (&self.clp09_frequency_code).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(ClpSegment);
+
+/// NM1 individual or organizational name.
+#[derive(Debug, Clone)]
+pub struct Nm1Segment {
+    /// NM101 entity identifier code.
+    pub nm101_entity_id: String,
+    /// NM102 entity type qualifier (`1` person, `2` non-person).
+    pub nm102_entity_type: String,
+    /// NM103 last or organization name.
+    pub nm103_last_name: String,
+    /// NM104 first name.
+    pub nm104_first_name: Option<String>,
+    /// NM105 middle name.
+    pub nm105_middle_name: Option<String>,
+    /// NM107 name suffix.
+    pub nm107_suffix: Option<String>,
+    /// NM108 identification code qualifier.
+    pub nm108_id_qualifier: Option<String>,
+    /// NM109 identification code.
+    pub nm109_id: Option<String>,
 }
 
-impl fmt::Display for ClpSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "CLP*{}*{}*{:.2}*{:.2}**{}*{}*11*1~",
-            self.clp01_claim_id,
-            self.clp02_claim_status,
-            self.clp03_charge_amount,
-            self.clp04_paid_amount,
-            self.clp06_claim_type,
-            self.clp07_payer_claim_number
-        )
+impl X12Segment for Nm1Segment {
+    fn id(&self) -> &str {
+        "NM1"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.nm101_entity_id).into(),
+            (&self.nm102_entity_type).into(),
+            (&self.nm103_last_name).into(),
+            self.nm104_first_name.as_deref().into(),
+            self.nm105_middle_name.as_deref().into(),
+            Element::default(),
+            self.nm107_suffix.as_deref().into(),
+            self.nm108_id_qualifier.as_deref().into(),
+            self.nm109_id.as_deref().into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(Nm1Segment);
+
+/// AMT monetary amount information.
+#[derive(Debug, Clone)]
+pub struct AmtSegment {
+    /// AMT01 amount qualifier code.
+    pub amt01_qualifier: String,
+    /// AMT02 monetary amount.
+    pub amt02_amount: Money,
+}
+
+impl X12Segment for AmtSegment {
+    fn id(&self) -> &str {
+        "AMT"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![(&self.amt01_qualifier).into(), amount(self.amt02_amount)]
+    }
+}
+
+display_with_default_delimiters!(AmtSegment);
+
+/// QTY quantity information.
+#[derive(Debug, Clone)]
+pub struct QtySegment {
+    /// QTY01 quantity qualifier.
+    pub qty01_qualifier: String,
+    /// QTY02 quantity.
+    pub qty02_quantity: f64,
+}
+
+impl X12Segment for QtySegment {
+    fn id(&self) -> &str {
+        "QTY"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.qty01_qualifier).into(),
+            self.qty02_quantity.to_string().into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(QtySegment);
+
+/// Product/service ID qualifier of a composite medical procedure identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProductServiceQualifier {
+    /// `HC` HCPCS/CPT codes.
+    Hcpcs,
+    /// `AD` ADA dental codes.
+    Ada,
+    /// `NU` NUBC revenue codes.
+    Nubc,
+    /// `N4` National Drug Codes.
+    Ndc,
+    /// `ZZ` mutually defined codes.
+    MutuallyDefined,
+    /// `HP` HIPPS skilled nursing facility rate codes.
+    Hipps,
+}
+
+impl ProductServiceQualifier {
+    /// The X12 qualifier code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProductServiceQualifier::Hcpcs => "HC",
+            ProductServiceQualifier::Ada => "AD",
+            ProductServiceQualifier::Nubc => "NU",
+            ProductServiceQualifier::Ndc => "N4",
+            ProductServiceQualifier::MutuallyDefined => "ZZ",
+            ProductServiceQualifier::Hipps => "HP",
+        }
+    }
+
+    /// Looks up a qualifier by its X12 code.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "HC" => Some(ProductServiceQualifier::Hcpcs),
+            "AD" => Some(ProductServiceQualifier::Ada),
+            "NU" => Some(ProductServiceQualifier::Nubc),
+            "N4" => Some(ProductServiceQualifier::Ndc),
+            "ZZ" => Some(ProductServiceQualifier::MutuallyDefined),
+            "HP" => Some(ProductServiceQualifier::Hipps),
+            _ => None,
+        }
     }
 }
 
-impl X12Segment for ClpSegment {}
+/// Composite medical procedure identifier (SVC01 and SVC06).
+#[derive(Debug, Clone)]
+pub struct MedicalProcedure {
+    /// Code list the procedure code comes from.
+    pub qualifier: ProductServiceQualifier,
+    /// Procedure code.
+    pub code: String,
+    /// Up to four procedure modifiers.
+    pub modifiers: Vec<String>,
+    /// Procedure code description.
+    pub description: Option<String>,
+}
 
+impl MedicalProcedure {
+    /// Builds the composite element, dropping trailing empty components.
+    pub fn to_element(&self) -> Element {
+        let mut components = vec![self.qualifier.code().to_string(), self.code.clone()];
+        components.extend(self.modifiers.iter().take(4).cloned());
+        if let Some(ref description) = self.description {
+            components.resize(6, String::new());
+            components.push(description.clone());
+        }
+        Element::composite(components)
+    }
+}
 
+/// SVC service payment information.
 #[derive(Debug, Clone)]
 pub struct SvcSegment {
-    pub svc01_procedure_code: String,
-    pub svc02_charge_amount: f64,
-    pub svc03_paid_amount: f64,
+    /// SVC01 adjudicated procedure.
+    pub svc01_procedure: MedicalProcedure,
+    /// SVC02 line item charge amount.
+    pub svc02_charge_amount: Money,
+    /// SVC03 line item provider payment amount.
+    pub svc03_paid_amount: Money,
+    /// SVC04 NUBC revenue code.
     pub svc04_revenue_code: Option<String>,
+    /// SVC05 units of service paid.
     pub svc05_units: f64,
+    /// SVC06 submitted procedure, when it differs from SVC01.
+    pub svc06_original_procedure: Option<MedicalProcedure>,
+    /// SVC07 original units of service submitted.
+    pub svc07_original_units: Option<f64>,
 }
 
-impl fmt::Display for SvcSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "SVC*{}:{:.2}*{:.2}*{:.2}*{}*{}~",
-            self.svc01_procedure_code,
-            self.svc02_charge_amount,
-            self.svc03_paid_amount,
-            self.svc03_paid_amount, 
-            self.svc04_revenue_code.as_deref().unwrap_or(""),
-            self.svc05_units
-        )
+impl X12Segment for SvcSegment {
+    fn id(&self) -> &str {
+        "SVC"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            self.svc01_procedure.to_element(),
+            amount(self.svc02_charge_amount),
+            amount(self.svc03_paid_amount),
+            self.svc04_revenue_code.as_deref().into(),
+            self.svc05_units.to_string().into(),
+            self.svc06_original_procedure
+                .as_ref()
+                .map(MedicalProcedure::to_element)
+                .unwrap_or_default(),
+            optional_value(self.svc07_original_units),
+        ]
     }
 }
 
-impl X12Segment for SvcSegment {}
+display_with_default_delimiters!(SvcSegment);
 
+/// CAS claim or service adjustment.
 #[derive(Debug, Clone)]
 pub struct CasSegment {
-    
+    /// CAS01 claim adjustment group code.
     pub cas01_group_code: String,
-    
-    pub cas02_reason_code: String,
-    
-    pub cas03_amount: f64,
-    
-    pub cas04_units: f64,
+
+    /// Reason, amount and quantity triplets (CAS02-CAS19).
+    pub adjustments: Vec<CasAdjustment>,
+}
+
+/// One reason/amount/quantity triplet of a CAS segment.
+#[derive(Debug, Clone)]
+pub struct CasAdjustment {
+    /// Claim adjustment reason code.
+    pub reason_code: String,
+    /// Adjustment amount.
+    pub amount: Money,
+    /// Adjustment quantity.
+    pub quantity: Option<f64>,
+}
+
+impl CasSegment {
+    /// Number of triplets a single CAS segment can carry.
+    pub const MAX_ADJUSTMENTS: usize = 6;
+}
+
+impl X12Segment for CasSegment {
+    fn id(&self) -> &str {
+        "CAS"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        let mut elements = vec![(&self.cas01_group_code).into()];
+        for adjustment in self.adjustments.iter().take(Self::MAX_ADJUSTMENTS) {
+            elements.push((&adjustment.reason_code).into());
+            elements.push(amount(adjustment.amount));
+            elements.push(optional_value(adjustment.quantity));
+        }
+        elements
+    }
+}
+
+display_with_default_delimiters!(CasSegment);
+
+/// LQ health care remark code.
+#[derive(Debug, Clone)]
+pub struct LqSegment {
+    /// LQ01 code list qualifier (`HE` for RARCs).
+    pub lq01_code_list_qualifier: String,
+    /// LQ02 remark code.
+    pub lq02_remark_code: String,
 }
 
-impl fmt::Display for CasSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "CAS*{}*{}*{:.2}*{:.0}~",
-            self.cas01_group_code, self.cas02_reason_code, self.cas03_amount, self.cas04_units
-        )
+impl X12Segment for LqSegment {
+    fn id(&self) -> &str {
+        "LQ"
     }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.lq01_code_list_qualifier).into(),
+            (&self.lq02_remark_code).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(LqSegment);
+
+/// PLB provider level adjustment.
+#[derive(Debug, Clone)]
+pub struct PlbSegment {
+    /// PLB01 provider identifier.
+    pub plb01_provider_id: String,
+    /// PLB02 last day of the provider's fiscal period (CCYYMMDD).
+    pub plb02_fiscal_period_date: String,
+    /// Reason and amount pairs (PLB03-PLB14).
+    pub adjustments: Vec<PlbAdjustment>,
+}
+
+/// One adjustment identifier/amount pair of a PLB segment.
+#[derive(Debug, Clone)]
+pub struct PlbAdjustment {
+    /// Provider adjustment reason code.
+    pub reason_code: String,
+    /// Reference identification, the second component of the adjustment identifier.
+    pub reference_id: Option<String>,
+    /// Provider adjustment amount.
+    pub amount: Money,
+}
+
+impl PlbSegment {
+    /// Number of adjustments a single PLB segment can carry.
+    pub const MAX_ADJUSTMENTS: usize = 6;
 }
 
-impl X12Segment for CasSegment {}
+impl X12Segment for PlbSegment {
+    fn id(&self) -> &str {
+        "PLB"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        let mut elements = vec![
+            (&self.plb01_provider_id).into(),
+            (&self.plb02_fiscal_period_date).into(),
+        ];
+        for adjustment in self.adjustments.iter().take(Self::MAX_ADJUSTMENTS) {
+            elements.push(Element::composite(vec![
+                adjustment.reason_code.clone(),
+                adjustment.reference_id.clone().unwrap_or_default(),
+            ]));
+            elements.push(amount(adjustment.amount));
+        }
+        elements
+    }
+}
 
+display_with_default_delimiters!(PlbSegment);
 
+/// SE transaction set trailer.
 #[derive(Debug, Clone)]
 pub struct SeSegment {
+    /// SE01 number of included segments, ST and SE inclusive.
     pub se01_segment_count: u32,
+    /// SE02 transaction set control number.
     pub se02_transaction_control_number: String,
 }
 
-impl fmt::Display for SeSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "SE*{}*{}~",
-            self.se01_segment_count, self.se02_transaction_control_number
-        )
+impl X12Segment for SeSegment {
+    fn id(&self) -> &str {
+        "SE"
     }
-}
 
-impl X12Segment for SeSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            self.se01_segment_count.to_string().into(),
+            (&self.se02_transaction_control_number).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(SeSegment);
 
+/// GE functional group trailer.
 #[derive(Debug, Clone)]
 pub struct GeSegment {
+    /// GE01 number of included transaction sets.
     pub ge01_number_of_transaction_sets: u32,
+    /// GE02 group control number.
     pub ge02_group_control_number: String,
 }
 
-impl fmt::Display for GeSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "GE*{}*{}~",
-            self.ge01_number_of_transaction_sets, self.ge02_group_control_number
-        )
+impl X12Segment for GeSegment {
+    fn id(&self) -> &str {
+        "GE"
     }
-}
 
-impl X12Segment for GeSegment {}
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            self.ge01_number_of_transaction_sets.to_string().into(),
+            (&self.ge02_group_control_number).into(),
+        ]
+    }
+}
 
+display_with_default_delimiters!(GeSegment);
 
+/// IEA interchange control trailer.
 #[derive(Debug, Clone)]
 pub struct IeaSegment {
+    /// IEA01 number of included functional groups.
     pub iea01_number_of_included_functional_groups: u32,
+    /// IEA02 interchange control number.
     pub iea02_interchange_control_number: String,
 }
 
-impl fmt::Display for IeaSegment {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "IEA*{}*{}~",
-            self.iea01_number_of_included_functional_groups, self.iea02_interchange_control_number
-        )
+impl X12Segment for IeaSegment {
+    fn id(&self) -> &str {
+        "IEA"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            self.iea01_number_of_included_functional_groups
+                .to_string()
+                .into(),
+            (&self.iea02_interchange_control_number).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(IeaSegment);
+
+/// TA1 interchange acknowledgment.
+#[derive(Debug, Clone)]
+pub struct Ta1Segment {
+    /// TA101 interchange control number being acknowledged.
+    pub ta101_interchange_control_number: String,
+    /// TA102 interchange date (YYMMDD).
+    pub ta102_date: String,
+    /// TA103 interchange time (HHMM).
+    pub ta103_time: String,
+    /// TA104 interchange acknowledgment code.
+    pub ta104_acknowledgment_code: char,
+    /// TA105 interchange note code.
+    pub ta105_note_code: String,
+}
+
+impl X12Segment for Ta1Segment {
+    fn id(&self) -> &str {
+        "TA1"
+    }
+
+    fn elements(&self) -> Vec<Element> {
+        vec![
+            (&self.ta101_interchange_control_number).into(),
+            (&self.ta102_date).into(),
+            (&self.ta103_time).into(),
+            self.ta104_acknowledgment_code.to_string().into(),
+            (&self.ta105_note_code).into(),
+        ]
+    }
+}
+
+display_with_default_delimiters!(Ta1Segment);
+
+/// A segment without a typed representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericSegment {
+    /// The segment identifier.
+    pub id: String,
+    /// The segment's elements in position order.
+    pub elements: Vec<Element>,
+}
+
+impl X12Segment for GenericSegment {
+    fn id(&self) -> &str {
+        &self.id
     }
+
+    fn elements(&self) -> Vec<Element> {
+        self.elements.clone()
+    }
+}
+
+display_with_default_delimiters!(GenericSegment);
+
+/// Defines `Segment` with one variant per typed segment.
+macro_rules! segment_enum {
+    ($($variant:ident($segment:ty)),* $(,)?) => {
+        /// Any segment the generator writes or the parser reads.
+        #[derive(Debug, Clone)]
+        pub enum Segment {
+            $(
+                #[doc = concat!("A typed `", stringify!($segment), "`.")]
+                $variant($segment),
+            )*
+            /// A segment without a typed representation.
+            Other(GenericSegment),
+        }
+
+        $(
+            impl From<$segment> for Segment {
+                fn from(segment: $segment) -> Self {
+                    Segment::$variant(segment)
+                }
+            }
+        )*
+
+        impl From<GenericSegment> for Segment {
+            fn from(segment: GenericSegment) -> Self {
+                Segment::Other(segment)
+            }
+        }
+
+        impl X12Segment for Segment {
+            fn id(&self) -> &str {
+                match self {
+                    $(Segment::$variant(segment) => segment.id(),)*
+                    Segment::Other(segment) => segment.id(),
+                }
+            }
+
+            fn elements(&self) -> Vec<Element> {
+                match self {
+                    $(Segment::$variant(segment) => segment.elements(),)*
+                    Segment::Other(segment) => segment.elements(),
+                }
+            }
+
+            fn write_segment(
+                &self,
+                out: &mut dyn fmt::Write,
+                delimiters: &Delimiters,
+            ) -> fmt::Result {
+                match self {
+                    $(Segment::$variant(segment) => segment.write_segment(out, delimiters),)*
+                    Segment::Other(segment) => segment.write_segment(out, delimiters),
+                }
+            }
+        }
+
+        display_with_default_delimiters!(Segment);
+    };
 }
 
-impl X12Segment for IeaSegment {}
+segment_enum!(
+    Isa(IsaSegment),
+    Gs(GsSegment),
+    St(StSegment),
+    Bpr(BprSegment),
+    Trn(TrnSegment),
+    Dtm(DtmSegment),
+    N1(N1Segment),
+    N3(N3Segment),
+    N4(N4Segment),
+    Ref(RefSegment),
+    Per(PerSegment),
+    Rdm(RdmSegment),
+    Lx(LxSegment),
+    Ts3(Ts3Segment),
+    Ts2(Ts2Segment),
+    Clp(ClpSegment),
+    Nm1(Nm1Segment),
+    Amt(AmtSegment),
+    Qty(QtySegment),
+    Svc(SvcSegment),
+    Cas(CasSegment),
+    Lq(LqSegment),
+    Plb(PlbSegment),
+    Se(SeSegment),
+    Ge(GeSegment),
+    Iea(IeaSegment),
+    Ta1(Ta1Segment),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cas_repeated_triplets() {
+        let cas = CasSegment {
+            cas01_group_code: "PR".to_string(),
+            adjustments: vec![
+                CasAdjustment {
+                    reason_code: "1".to_string(),
+                    amount: Money::from_cents(2500),
+                    quantity: None,
+                },
+                CasAdjustment {
+                    reason_code: "2".to_string(),
+                    amount: Money::from_cents(1250),
+                    quantity: Some(1.0),
+                },
+                CasAdjustment {
+                    reason_code: "3".to_string(),
+                    amount: Money::from_cents(2000),
+                    quantity: None,
+                },
+            ],
+        };
+        assert_eq!(cas.to_string(), "CAS*PR*1*25.00**2*12.50*1*3*20.00~");
+    }
+
+    #[test]
+    fn test_plb_adjustment_composites() {
+        let plb = PlbSegment {
+            plb01_provider_id: "1234567890".to_string(),
+            plb02_fiscal_period_date: "20241231".to_string(),
+            adjustments: vec![
+                PlbAdjustment {
+                    reason_code: "L6".to_string(),
+                    reference_id: Some("CLM12345678".to_string()),
+                    amount: Money::from_cents(-425),
+                },
+                PlbAdjustment {
+                    reason_code: "AH".to_string(),
+                    reference_id: None,
+                    amount: Money::from_cents(200),
+                },
+            ],
+        };
+        assert_eq!(
+            plb.to_string(),
+            "PLB*1234567890*20241231*L6:CLM12345678*-4.25*AH*2.00~"
+        );
+    }
+
+    #[test]
+    fn test_ts3_positions() {
+        let ts3 = Ts3Segment {
+            ts301_provider_id: "1234567890".to_string(),
+            ts302_facility_type: "13".to_string(),
+            ts303_fiscal_period_date: "20241231".to_string(),
+            ts304_claim_count: 2,
+            ts305_total_charge: Money::from_cents(90000),
+            ts315_non_lab_charges: Some(Money::from_cents(75000)),
+            ts317_hcpcs_reported_charges: Some(Money::from_cents(90000)),
+            ts318_hcpcs_payable: Some(Money::from_cents(61250)),
+            ..Default::default()
+        };
+        assert_eq!(
+            ts3.to_string(),
+            "TS3*1234567890*13*20241231*2*900.00**********750.00**900.00*612.50~"
+        );
+    }
+
+    #[test]
+    fn test_ts2_positions() {
+        let ts2 = Ts2Segment {
+            ts201_drg_amount: Some(Money::from_cents(540000)),
+            ts210_average_length_of_stay: Some(3.5),
+            ts211_discharges: Some(2),
+            ts213_covered_days: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(ts2.to_string(), "TS2*5400.00*********3.5*2**7~");
+    }
+
+    #[test]
+    fn test_svc_composite_procedure() {
+        let svc = SvcSegment {
+            svc01_procedure: MedicalProcedure {
+                qualifier: ProductServiceQualifier::Hcpcs,
+                code: "99213".to_string(),
+                modifiers: vec!["25".to_string(), "59".to_string()],
+                description: None,
+            },
+            svc02_charge_amount: Money::from_cents(15000),
+            svc03_paid_amount: Money::from_cents(9850),
+            svc04_revenue_code: None,
+            svc05_units: 1.0,
+            svc06_original_procedure: None,
+            svc07_original_units: None,
+        };
+        assert_eq!(svc.to_string(), "SVC*HC:99213:25:59*150.00*98.50**1~");
+    }
+
+    #[test]
+    fn test_svc_original_procedure_and_description() {
+        let svc = SvcSegment {
+            svc01_procedure: MedicalProcedure {
+                qualifier: ProductServiceQualifier::Hcpcs,
+                code: "J3490".to_string(),
+                modifiers: vec!["JW".to_string()],
+                description: Some("UNCLASSIFIED DRUGS".to_string()),
+            },
+            svc02_charge_amount: Money::from_cents(8000),
+            svc03_paid_amount: Money::from_cents(4000),
+            svc04_revenue_code: Some("0636".to_string()),
+            svc05_units: 2.0,
+            svc06_original_procedure: Some(MedicalProcedure {
+                qualifier: ProductServiceQualifier::Hcpcs,
+                code: "J3590".to_string(),
+                modifiers: Vec::new(),
+                description: None,
+            }),
+            svc07_original_units: Some(3.0),
+        };
+        assert_eq!(
+            svc.to_string(),
+            "SVC*HC:J3490:JW::::UNCLASSIFIED DRUGS*80.00*40.00*0636*2*HC:J3590*3~"
+        );
+    }
+}